@@ -1,6 +1,7 @@
 use criterion::{ criterion_group, criterion_main, BatchSize, Criterion};
 use x25519_dalek_ng::{PublicKey, StaticSecret};
-use edhoc::edhoc::{PartyI,PartyR};
+use edhoc::edhoc::{suite::{Aead, Hash, SUITE_0}, PartyI, PartyR, Version};
+use edhoc::oscore::{coap, SecurityContext};
 
 pub const C_I : [u8;1] = [0xC];
 pub const I_EPHEMEREAL_SK : [u8;32] = [0xB3,0x11,0x19,0x98,0xCB,0x3F,0x66,0x86,0x63,0xED,0x42,0x51,
@@ -65,10 +66,11 @@ fn edhoc_detailed(c: &mut Criterion) {
             PartyI::new(
                 DEVEUI.to_vec(),
                 Some(APPEUI.to_vec()),
-                I_EPHEMEREAL_SK,
+                StaticSecret::from(I_EPHEMEREAL_SK),
                 StaticSecret::from(I_STATIC_SK),
                 pub_st_i,
                 KID_I.to_vec(),
+                Version::Draft12,
 
             )
         })
@@ -80,14 +82,15 @@ fn edhoc_detailed(c: &mut Criterion) {
                 PartyI::new(
                     DEVEUI.to_vec(),
                     Some(APPEUI.to_vec()),
-                    I_EPHEMEREAL_SK,
+                    StaticSecret::from(I_EPHEMEREAL_SK),
                     StaticSecret::from(I_STATIC_SK),
                     pub_st_i,
                     KID_I.to_vec(),
+                    Version::Draft12,
     
                 )
             },
-            |msg1_sender| msg1_sender.generate_message_1(3,0).unwrap(),
+            |msg1_sender| msg1_sender.generate_message_1(3, &[SUITE_0]).unwrap(),
             BatchSize::SmallInput,
         )
     });
@@ -96,10 +99,11 @@ fn edhoc_detailed(c: &mut Criterion) {
     group.bench_function("party_r_build", |b| {
         b.iter(|| {
             PartyR::new(
-                R_EPHEMEREAL_SK,
+                StaticSecret::from(R_EPHEMEREAL_SK),
                 StaticSecret::from(R_STATIC_SK),
                 pub_st_r,
                 KID_R.to_vec(),
+                Version::Draft12,
 
             )
         })
@@ -111,10 +115,11 @@ fn edhoc_detailed(c: &mut Criterion) {
                 (
                     MSG1.to_vec(),
                     PartyR::new(
-                        R_EPHEMEREAL_SK,
+                        StaticSecret::from(R_EPHEMEREAL_SK),
                         StaticSecret::from(R_STATIC_SK),
                         pub_st_r,
                         KID_R.to_vec(),
+                        Version::Draft12,
         
                     ),
                 )
@@ -130,10 +135,11 @@ fn edhoc_detailed(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let msg1_receiver = PartyR::new(
-                    R_EPHEMEREAL_SK,
+                    StaticSecret::from(R_EPHEMEREAL_SK),
                     StaticSecret::from(R_STATIC_SK),
                     pub_st_r,
                     KID_R.to_vec(),
+                    Version::Draft12,
     
                 );
                 msg1_receiver.handle_message_1(MSG1.to_vec()).unwrap().0
@@ -149,13 +155,14 @@ fn edhoc_detailed(c: &mut Criterion) {
                 let msg1_sender = PartyI::new(
                     DEVEUI.to_vec(),
                     Some(APPEUI.to_vec()),
-                    I_EPHEMEREAL_SK,
+                    StaticSecret::from(I_EPHEMEREAL_SK),
                     StaticSecret::from(I_STATIC_SK),
                     pub_st_i,
                     KID_I.to_vec(),
+                    Version::Draft12,
                 );
                 let (_, msg2_receiver) =
-                    msg1_sender.generate_message_1(3,0).unwrap();
+                    msg1_sender.generate_message_1(3, &[SUITE_0]).unwrap();
 
                 (MSG2.to_vec(), msg2_receiver)
             },
@@ -172,13 +179,14 @@ fn edhoc_detailed(c: &mut Criterion) {
                 let msg1_sender = PartyI::new(
                     DEVEUI.to_vec(),
                     Some(APPEUI.to_vec()),
-                    I_EPHEMEREAL_SK,
+                    StaticSecret::from(I_EPHEMEREAL_SK),
                     StaticSecret::from(I_STATIC_SK),
                     pub_st_i,
                     KID_I.to_vec(),
+                    Version::Draft12,
                 );
                 let (_, msg2_receiver) =
-                    msg1_sender.generate_message_1(3,0).unwrap();
+                    msg1_sender.generate_message_1(3, &[SUITE_0]).unwrap();
                 let (_,_, msg2_verifier) = msg2_receiver
                     .unpack_message_2_return_kid(MSG2.to_vec())
                     .unwrap();
@@ -197,13 +205,14 @@ fn edhoc_detailed(c: &mut Criterion) {
                 let msg1_sender = PartyI::new(
                     DEVEUI.to_vec(),
                     Some(APPEUI.to_vec()),
-                    I_EPHEMEREAL_SK,
+                    StaticSecret::from(I_EPHEMEREAL_SK),
                     StaticSecret::from(I_STATIC_SK),
                     pub_st_i,
                     KID_I.to_vec(),
+                    Version::Draft12,
                 );
                 let (_, msg2_receiver) =
-                    msg1_sender.generate_message_1(3,0).unwrap();
+                    msg1_sender.generate_message_1(3, &[SUITE_0]).unwrap();
                 let (_,_, msg2_verifier) = msg2_receiver
                     .unpack_message_2_return_kid(MSG2.to_vec())
                     .unwrap();
@@ -218,10 +227,11 @@ fn edhoc_detailed(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let msg1_receiver = PartyR::new(
-                    R_EPHEMEREAL_SK,
+                    StaticSecret::from(R_EPHEMEREAL_SK),
                     StaticSecret::from(R_STATIC_SK),
                     pub_st_r,
                     KID_R.to_vec(),
+                    Version::Draft12,
                 );
                 let msg2_sender = msg1_receiver
                     .handle_message_1(MSG1.to_vec())
@@ -240,10 +250,11 @@ fn edhoc_detailed(c: &mut Criterion) {
         b.iter_batched(
             || {
                     let msg1_receiver = PartyR::new(
-                        R_EPHEMEREAL_SK,
+                        StaticSecret::from(R_EPHEMEREAL_SK),
                         StaticSecret::from(R_STATIC_SK),
                         pub_st_r,
                         KID_R.to_vec(),
+                        Version::Draft12,
                     );
                 let msg2_sender = msg1_receiver
                     .handle_message_1(MSG1.to_vec())
@@ -266,10 +277,11 @@ fn edhoc_detailed(c: &mut Criterion) {
         b.iter_batched(
             || {
                 let msg1_receiver = PartyR::new(
-                    R_EPHEMEREAL_SK,
+                    StaticSecret::from(R_EPHEMEREAL_SK),
                     StaticSecret::from(R_STATIC_SK),
                     pub_st_r,
                     KID_R.to_vec(),
+                    Version::Draft12,
     
                 );
                 let (msg2_sender,_devui,_appeui) = msg1_receiver.handle_message_1_ead(MSG1.to_vec()).unwrap();
@@ -290,13 +302,14 @@ group.bench_function("msg4_verify", |b| {
                 let msg1_sender = PartyI::new(
                     DEVEUI.to_vec(),
                     Some(APPEUI.to_vec()),
-                    I_EPHEMEREAL_SK,
+                    StaticSecret::from(I_EPHEMEREAL_SK),
                     StaticSecret::from(I_STATIC_SK),
                     pub_st_i,
                     KID_I.to_vec(),
+                    Version::Draft12,
                 );
                 let (_, msg2_receiver) =
-                    msg1_sender.generate_message_1(3,0).unwrap();
+                    msg1_sender.generate_message_1(3, &[SUITE_0]).unwrap();
 
                 let (_r_kid, _deveui,msg2_verifier) = msg2_receiver
                     .unpack_message_2_return_kid(MSG2.to_vec())
@@ -315,5 +328,43 @@ group.bench_function("msg4_verify", |b| {
 
 }
 
-criterion_group!(edhoc_benches, edhoc_detailed);
+// The security context of RFC 8613 Appendix C.1, and the request of C.4
+const MASTER_SECRET: [u8; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+const MASTER_SALT: [u8; 8] = [0x9E, 0x7C, 0xA9, 0x22, 0x23, 0x78, 0x63, 0x40];
+const REQUEST: [u8; 22] = [0x44, 0x01, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x39, 0x6C, 0x6F,
+    0x63, 0x61, 0x6C, 0x68, 0x6F, 0x73, 0x74, 0x83, 0x74, 0x76, 0x31];
+
+fn oscore_context(sender_id: &[u8], recipient_id: &[u8]) -> SecurityContext {
+    SecurityContext::new(
+        &MASTER_SECRET,
+        &MASTER_SALT,
+        None,
+        sender_id,
+        recipient_id,
+        Aead::AesCcm16_64_128,
+        Hash::Sha256,
+    )
+    .unwrap()
+}
+
+fn oscore_detailed(c: &mut Criterion) {
+    let request = coap::deserialize_message(&REQUEST).unwrap();
+    let mut client = oscore_context(&[], &[0x01]);
+
+    c.bench_function("protect_request", |b| {
+        b.iter(|| client.protect_request(&request).unwrap())
+    });
+
+    // Unprotecting the same request over and over is a replay
+    #[cfg(feature = "no_replay")]
+    {
+        let (protected, _) = client.protect_request(&request).unwrap();
+        let mut server = oscore_context(&[0x01], &[]);
+        c.bench_function("unprotect_request", |b| {
+            b.iter(|| server.unprotect_request(&protected).unwrap())
+        });
+    }
+}
+
+criterion_group!(edhoc_benches, edhoc_detailed, oscore_detailed);
 criterion_main!(edhoc_benches);
@@ -2,7 +2,8 @@
 
 use edhoc::edhoc::{
     error::{OwnError, OwnOrPeerError},
-    PartyI, PartyR,
+    suite::{CipherSuite, SUITE_0},
+    PartyI, PartyR, Version,
     
 };
 
@@ -10,7 +11,7 @@ use x25519_dalek_ng::{PublicKey,StaticSecret};
 
 
 
-const SUITE_I: u8 = 0;
+const SUITE_I: CipherSuite = SUITE_0;
 const METHOD_TYPE_I : u8 = 3;
 pub const I_EPHEMEREAL_SK : [u8;32] = [0xB3,0x11,0x19,0x98,0xCB,0x3F,0x66,0x86,0x63,0xED,0x42,0x51,
                             0xC7,0x8B,0xE6,0xE9,0x5A,0x4D,0xA1,0x27,0xE4,0xF6,0xFE,0xE2,
@@ -50,13 +51,13 @@ fn main() {
 
     // Using a static ephemeral key, which should obviously be dynamic
     let msg1_sender =
-        PartyI::new(deveui,Some(appeui), I_EPHEMEREAL_SK, i_static_priv, i_static_pub, KID_I.to_vec());
+        PartyI::new(deveui,Some(appeui), StaticSecret::from(I_EPHEMEREAL_SK), i_static_priv, i_static_pub, KID_I.to_vec(), Version::Rfc9528);
 
 
     let (msg1_bytes, msg2_receiver) =
         // If an error happens here, we just abort. No need to send a message,
         // since the protocol hasn't started yet.
-        msg1_sender.generate_message_1(METHOD_TYPE_I, SUITE_I).unwrap();
+        msg1_sender.generate_message_1(METHOD_TYPE_I, &[SUITE_I]).unwrap();
  
     /*
     /// Party R handle message 1
@@ -72,7 +73,7 @@ fn main() {
 // Using a static ephemeral key, which should obviously be dynamic
 
     let msg1_receiver =
-       PartyR::new(R_EPHEMEREAL_SK, r_static_priv, r_static_pub, KID_R.to_vec());
+       PartyR::new(StaticSecret::from(R_EPHEMEREAL_SK), r_static_priv, r_static_pub, KID_R.to_vec(), Version::Rfc9528);
        
     let (msg2_sender,_devui,appeui) = match msg1_receiver.handle_message_1_ead(msg1_bytes) {
         Err(OwnError(b)) => {
@@ -1,6 +1,5 @@
 use core::fmt;
 #[cfg(feature = "std")]
-
 /// The error type for the `cbor` module.
 #[derive(Debug)]
 pub enum CborError {
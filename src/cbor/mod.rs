@@ -1,7 +1,7 @@
 //! Helpful functionality around the `serde_cbor` crate.
 
 use alloc::vec::Vec;
-use serde::ser::{Serialize, Serializer, SerializeMap};
+use serde::ser::Serialize;
 #[cfg_attr(tarpaulin, skip)]
 mod error;
 pub use error::CborError;
@@ -16,25 +16,6 @@ pub fn encode(object: impl Serialize) -> Result<Vec<u8>> {
 }
 
 
-struct IdCred(u8);
-
-impl Serialize for IdCred {
-    fn serialize<S>(&self, s: S) -> core::result::Result<S::Ok, S::Error>
-    where
-        S: Serializer
-    {
-        let mut map = s.serialize_map(Some(1))?;
-        map.serialize_entry(&4, &self.0)?;
-        map.end()
-    }
-}
-pub fn build_map_single(kid: &[u8]) -> Result<Vec<u8>> {
-    match serde_cbor::to_vec(&IdCred(kid[0])) {
-        Ok(b ) => Ok(b),
-        _ => Err(CborError::TooManyItems)
-    }
-}
-
 /// Serializes an object into a sequence of CBOR encoded data items.
 ///
 /// Only works for objects that serialize to a CBOR array of at most 23 items.
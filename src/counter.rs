@@ -0,0 +1,122 @@
+//! Counters whose values must never repeat, even across reboots.
+
+use core::fmt;
+
+/// A counter of values that are used at most once each, like the sender
+/// sequence numbers of OSCORE or the frame counters of LoRaWAN.
+///
+/// These values are part of a nonce, so a party that forgets the counter in
+/// a reboot would encrypt under a nonce it already used. A counter is only
+/// kept in memory unless values are reserved with `reserve`, after which
+/// only reserved values are used. The bound `reserve` returns is what has to
+/// be stored persistently before any of them is used, so that `restore`
+/// continues past every value that may have been used before a reboot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Counter {
+    /// The next value.
+    next: u64,
+    /// The values below this may be used.
+    bound: u64,
+    /// One past the largest value.
+    end: u64,
+}
+
+impl Counter {
+    /// Returns a counter starting at 0, which can use every value below
+    /// `end` until values are reserved.
+    pub fn new(end: u64) -> Counter {
+        Counter {
+            next: 0,
+            bound: end,
+            end,
+        }
+    }
+
+    /// Returns the next value, which may be `end` once all were used.
+    pub fn value(&self) -> u64 {
+        self.next
+    }
+
+    /// Sets the next value.
+    pub fn set_value(&mut self, value: u64) {
+        self.next = value;
+    }
+
+    /// Reserves the next `count` values, and returns the bound that has to
+    /// be stored persistently before any of them is used.
+    pub fn reserve(&mut self, count: u64) -> u64 {
+        self.bound = self.next.saturating_add(count).min(self.end);
+
+        self.bound
+    }
+
+    /// Continues from the last bound `reserve` returned after a reboot,
+    /// which is where the counter starts again. Values need to be reserved
+    /// before the next one can be used.
+    pub fn restore(&mut self, bound: u64) {
+        self.next = bound;
+        self.bound = bound;
+    }
+
+    /// Returns the next value, and moves on to the one after it.
+    pub fn take(&mut self) -> Result<u64, CounterError> {
+        if self.next >= self.end {
+            return Err(CounterError::Exhausted);
+        }
+        if self.next >= self.bound {
+            return Err(CounterError::NotReserved);
+        }
+        let value = self.next;
+        self.next += 1;
+
+        Ok(value)
+    }
+}
+
+/// The reasons a `Counter` has no next value.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CounterError {
+    /// All values were used.
+    Exhausted,
+    /// All reserved values were used.
+    NotReserved,
+}
+
+impl fmt::Display for CounterError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CounterError::Exhausted => write!(f, "Counter exhausted"),
+            CounterError::NotReserved => {
+                write!(f, "All reserved counter values were used")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserve_restore() {
+        let mut counter = Counter::new(100);
+        assert_eq!(counter.take(), Ok(0));
+        assert_eq!(counter.reserve(2), 3);
+        assert_eq!(counter.take(), Ok(1));
+        assert_eq!(counter.take(), Ok(2));
+        assert_eq!(counter.take(), Err(CounterError::NotReserved));
+
+        // After a reboot, the counter continues past the stored bound
+        let mut counter = Counter::new(100);
+        counter.restore(3);
+        assert_eq!(counter.take(), Err(CounterError::NotReserved));
+        assert_eq!(counter.reserve(1000), 100);
+        assert_eq!(counter.take(), Ok(3));
+
+        counter.set_value(99);
+        assert_eq!(counter.take(), Ok(99));
+        assert_eq!(counter.value(), 100);
+        assert_eq!(counter.take(), Err(CounterError::Exhausted));
+        assert_eq!(counter.reserve(1), 100);
+    }
+}
@@ -1,15 +1,95 @@
 //! Structs used in the API.
 
+// Methods hand back their outputs as tuples together with the next state.
+#![allow(clippy::type_complexity)]
+
 use alloc::vec::Vec;
 use core::result::Result;
-use x25519_dalek_ng::{PublicKey, StaticSecret};
+use x25519_dalek_ng::StaticSecret;
 use super::{
     cose,
-    error::{EarlyError, Error, OwnError, OwnOrPeerError},
-    util::{self, Message1, Message2, Message3,Message4},
+    error::{EarlyError, Error, ErrorMessage, OwnError, OwnOrPeerError},
+    keys::{DhSecret, SigningKey},
+    session::Session,
+    suite::{CipherSuite, SUITES},
+    util::{self, Message1, Message2, Message3, Message4, Version},
 };
 
 
+/// The key a party authenticates itself with.
+///
+/// `K` is the private key type of the Diffie-Hellman group of the suite.
+#[allow(clippy::large_enum_variant)]
+pub enum AuthKey<K: DhSecret = StaticSecret> {
+    /// A static DH key pair, proving possession through a MAC.
+    StaticDh(K, K::Public),
+    /// A signature key, proving possession through a signature.
+    Signature(SigningKey),
+}
+
+impl<K: DhSecret> AuthKey<K> {
+    /// Returns the bytes of the public authentication key.
+    fn public_bytes(&self) -> Vec<u8> {
+        match self {
+            AuthKey::StaticDh(_, public) => K::encode_public(public),
+            AuthKey::Signature(key) => key.public_bytes(),
+        }
+    }
+
+    /// Returns the bytes of the public authentication key in its
+    /// `COSE_Key`, which for a static DH key on an EC2 curve is the
+    /// compressed point.
+    fn point_bytes(&self) -> Vec<u8> {
+        match self {
+            AuthKey::StaticDh(_, public) => K::encode_point(public),
+            AuthKey::Signature(key) => key.public_bytes(),
+        }
+    }
+
+    /// Returns whether this key can be used with the given suite.
+    fn supports_suite(&self, suite: &CipherSuite) -> bool {
+        match self {
+            AuthKey::StaticDh(..) => K::CURVE == suite.curve,
+            AuthKey::Signature(key) => key.algorithm() == suite.signature,
+        }
+    }
+
+    /// Returns whether this key and an ephemeral key of type `K` can be used
+    /// with the given suite.
+    fn can_select(&self, suite: &CipherSuite) -> bool {
+        K::CURVE == suite.curve && self.supports_suite(suite)
+    }
+
+    /// Returns the identifiers of all suites this key and an ephemeral key
+    /// of type `K` can be used with, in the order of the registry.
+    fn supported_suites(&self) -> Vec<u8> {
+        SUITES
+            .iter()
+            .filter(|suite| self.can_select(suite))
+            .map(|suite| suite.id)
+            .collect()
+    }
+
+    /// Returns whether this key authenticates with a signature.
+    fn signs(&self) -> bool {
+        matches!(self, AuthKey::Signature(_))
+    }
+
+    /// Returns CRED_x for this key, a CCS containing only its `COSE_Key`,
+    /// or the credential of draft-12.
+    fn credential(&self, version: Version, kid: &[u8]) -> Result<Vec<u8>, Error> {
+        let crv = match self {
+            AuthKey::StaticDh(..) => K::CURVE.cose_curve(),
+            AuthKey::Signature(key) => key.algorithm().cose_curve(),
+        };
+
+        match version {
+            Version::Draft12 => cose::build_draft12_cred(kid, &self.public_bytes()),
+            Version::Rfc9528 => cose::build_ccs(kid, crv, &self.point_bytes()),
+        }
+    }
+}
+
 // Party U constructs ---------------------------------------------------------
 
 /// The structure providing all operations for Party I.
@@ -17,149 +97,310 @@ pub struct PartyI<S: PartyIState>(pub S);
 
 // Necessary stuff for session types
 pub trait PartyIState {}
-impl PartyIState for Msg1Sender {}
-impl PartyIState for Msg2Receiver {}
-impl PartyIState for Msg2Verifier {}
-impl PartyIState for Msg3Sender {}
+impl<K: DhSecret> PartyIState for Msg1Sender<K> {}
+impl<K: DhSecret> PartyIState for Msg2Receiver<K> {}
+impl<K: DhSecret> PartyIState for Msg2Verifier<K> {}
+impl<K: DhSecret> PartyIState for Msg3Sender<K> {}
 impl PartyIState for Msg4ReceiveVerify {}
 
 
 
-pub struct Msg1Sender {
+/// Contains the state to build the first message.
+pub struct Msg1Sender<K: DhSecret = StaticSecret> {
+    version: Version,
     ead_1: Option<Vec<u8>>,
     c_i : Vec<u8>,
-    priv_ek_i: StaticSecret,
-    pub_ek_i: PublicKey,
-    pub_st_i: PublicKey,
-    priv_st_i: StaticSecret,
+    priv_ek_i: K,
+    auth: AuthKey<K>,
     kid: Vec<u8>,
 }
 
-impl PartyI<Msg1Sender> {
+impl<K: DhSecret> PartyI<Msg1Sender<K>> {
     /// Creates a new `PartyI` ready to build the first message.
     ///
     /// # Arguments
     /// * `c_i` - The chosen connection identifier.
     /// * ead - external auth data
-    /// * `ecdh_secret` - The ECDH secret to use for this protocol run. Ephemeral
-    /// * `stat_priv` - The private ed25519derivePRKauthentication key.
-    /// * `stat_public`, which is called 'id_cred_x in edho 14 .
+    /// * `ephemeral_secret` - The ECDH secret to use for this protocol run,
+    ///   in the group of the suite that is going to be selected.
+    /// * `priv_st_i` - The private static DH authentication key.
+    /// * `pub_st_i` - The public static DH authentication key.
     /// * `kid` - The key ID by which the other party is able to retrieve
-
+    ///   `pub_st_i`.
+    /// * `version` - The version of EDHOC R speaks.
     pub fn new(
         c_i: Vec<u8>,
         ead_1: Option<Vec<u8>>,
-        ephemeral_secret: [u8; 32],
-        priv_st_i: StaticSecret,
-        pub_st_i: PublicKey,
+        ephemeral_secret: K,
+        priv_st_i: K,
+        pub_st_i: K::Public,
         kid: Vec<u8>,
-    ) -> PartyI<Msg1Sender> {
+        version: Version,
+    ) -> PartyI<Msg1Sender<K>> {
+        PartyI::new_with_auth(
+            c_i,
+            ead_1,
+            ephemeral_secret,
+            AuthKey::StaticDh(priv_st_i, pub_st_i),
+            kid,
+            version,
+        )
+    }
 
-        let priv_ek_i = StaticSecret::from(ephemeral_secret);
-        // and from that build the corresponding public key
-        let pub_ek_i = PublicKey::from(&priv_ek_i);
+    /// Creates a new `PartyI` authenticating with a signature key.
+    ///
+    /// # Arguments
+    /// * `c_i` - The chosen connection identifier.
+    /// * `ead_1` - external auth data
+    /// * `ephemeral_secret` - The ECDH secret to use for this protocol run.
+    /// * `signing_key` - The private Ed25519 or P-256 authentication key.
+    /// * `kid` - The key ID by which the other party is able to retrieve
+    ///   the public part of `signing_key`.
+    /// * `version` - The version of EDHOC R speaks.
+    pub fn new_sig(
+        c_i: Vec<u8>,
+        ead_1: Option<Vec<u8>>,
+        ephemeral_secret: K,
+        signing_key: impl Into<SigningKey>,
+        kid: Vec<u8>,
+        version: Version,
+    ) -> PartyI<Msg1Sender<K>> {
+        PartyI::new_with_auth(
+            c_i,
+            ead_1,
+            ephemeral_secret,
+            AuthKey::Signature(signing_key.into()),
+            kid,
+            version,
+        )
+    }
 
-         PartyI(Msg1Sender {
+    fn new_with_auth(
+        c_i: Vec<u8>,
+        ead_1: Option<Vec<u8>>,
+        priv_ek_i: K,
+        auth: AuthKey<K>,
+        kid: Vec<u8>,
+        version: Version,
+    ) -> PartyI<Msg1Sender<K>> {
+        PartyI(Msg1Sender {
+            version,
             ead_1,
             c_i,
             priv_ek_i,
-            pub_ek_i,
-            pub_st_i,
-            priv_st_i,
+            auth,
             kid,
         })
     }
 
     /// Returns the bytes of the first message.
     ///
-
+    /// The `method` has to match the authentication key `PartyI` was created
+    /// with, which means I signs in methods 0 and 1 and uses its static DH
+    /// key in methods 2 and 3. It also determines how R authenticates.
+    ///
+    /// `suites` are the suites I supports, in order of preference. The most
+    /// preferred one the keys belong to is selected, and sent together with
+    /// all suites preceding it; if R doesn't support it, it answers with an
+    /// error message that can be passed to
+    /// [`PartyI::retry`](struct.PartyI.html#method.retry).
     pub fn generate_message_1(
         self,
         method: u8,
-        suites: u8,
-    ) -> Result<(Vec<u8>, PartyI<Msg2Receiver>), EarlyError> {
-        // Encode the necessary information into the first message
-        let msg_1 = Message1 {
+        suites: &[CipherSuite],
+    ) -> Result<(Vec<u8>, PartyI<Msg2Receiver<K>>), EarlyError> {
+        let (i_signs, _) = util::method_signers(method)?;
+        if i_signs != self.0.auth.signs() {
+            return Err(Error::UnsupportedMethod.into());
+        }
+        // Only a suite both our ephemeral and our authentication key belong
+        // to can be selected
+        let selected = suites
+            .iter()
+            .position(|suite| self.0.auth.can_select(suite))
+            .ok_or(Error::UnsupportedSuite)?;
+
+        build_message_1(
+            self.0.version,
+            self.0.c_i,
+            self.0.ead_1,
+            self.0.priv_ek_i,
+            self.0.auth,
+            self.0.kid,
             method,
-            suite: suites,
-            pub_ek_i: self.0.pub_ek_i.as_bytes().to_vec(), // sending PK as vector
-            c_i : self.0.c_i,
-            ead_1 : self.0.ead_1,
-        };
-        // Get CBOR sequence for message
-        let msg_1_seq = util::serialize_message_1(&msg_1)?;
-        // Copy for returning
-        let msg_1_bytes = msg_1_seq.clone();
-        Ok((
-            msg_1_bytes,
-            PartyI(Msg2Receiver {
-                priv_ek_i: self.0.priv_ek_i,
-                pub_st_i: self.0.pub_st_i,
-                priv_st_i: self.0.priv_st_i,
-                kid: self.0.kid,
-                msg_1_seq,
-            }),
-        ))
+            suites.to_vec(),
+            selected,
+        )
     }
 }
+
+/// Returns the bytes of the first message selecting `suites[selected]`,
+/// together with the state to receive the second message.
+#[allow(clippy::too_many_arguments)]
+fn build_message_1<K: DhSecret>(
+    version: Version,
+    c_i: Vec<u8>,
+    ead_1: Option<Vec<u8>>,
+    priv_ek_i: K,
+    auth: AuthKey<K>,
+    kid: Vec<u8>,
+    method: u8,
+    suites: Vec<CipherSuite>,
+    selected: usize,
+) -> Result<(Vec<u8>, PartyI<Msg2Receiver<K>>), EarlyError> {
+    // SUITES_I consists of the selected suite, preceded by all the ones we
+    // prefer over it
+    let suites_i = suites[..=selected].iter().map(|suite| suite.id).collect();
+    // Encode the necessary information into the first message
+    let msg_1 = Message1 {
+        method,
+        suites_i,
+        pub_ek_i: K::encode_public(&priv_ek_i.public_key()), // sending PK as vector
+        c_i : c_i.clone(),
+        ead_1 : ead_1.clone(),
+    };
+    // Get CBOR sequence for message
+    let msg_1_seq = util::serialize_message_1(version, &msg_1)?;
+    // Copy for returning
+    let msg_1_bytes = msg_1_seq.clone();
+    Ok((
+        msg_1_bytes,
+        PartyI(Msg2Receiver {
+            version,
+            priv_ek_i,
+            auth,
+            kid,
+            method,
+            suite: suites[selected],
+            suites,
+            c_i,
+            ead_1,
+            msg_1_seq,
+        }),
+    ))
+}
+
 /// Contains the state to receive the second message.
-pub struct Msg2Receiver {
-    priv_ek_i: StaticSecret,
-    pub_st_i : PublicKey,
-    priv_st_i : StaticSecret,
+pub struct Msg2Receiver<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ek_i: K,
+    auth: AuthKey<K>,
     kid: Vec<u8>,
+    method: u8,
+    suite: CipherSuite,
+    suites: Vec<CipherSuite>,
+    c_i: Vec<u8>,
+    ead_1: Option<Vec<u8>>,
     msg_1_seq: Vec<u8>,
 }
 
 
 
-impl PartyI<Msg2Receiver> {
+impl<K: DhSecret> PartyI<Msg2Receiver<K>> {
+    /// Returns the bytes of a new first message, if the second message is
+    /// R's error message rejecting the selected suite.
+    ///
+    /// The most preferred of our suites that R lists as supported in
+    /// `SUITES_R`, and that our keys belong to, is selected. Since R checks that none of the suites we
+    /// prefer over it is one it supports, this also makes sure that nobody
+    /// tampered with `SUITES_R`.
+    ///
+    /// # Arguments
+    /// * `msg_2` - The error message received instead of the second message.
+    /// * `ephemeral_secret` - A fresh ECDH secret for the new protocol run.
+    pub fn retry(
+        self,
+        msg_2: &[u8],
+        ephemeral_secret: K,
+    ) -> Result<(Vec<u8>, PartyI<Msg2Receiver<K>>), EarlyError> {
+        let suites_r = match util::extract_error_message(msg_2)? {
+            ErrorMessage::WrongSelectedSuite(suites_r) => suites_r,
+            // Pass on any other error message
+            err => return Err(Error::Edhoc(err).into()),
+        };
+        let selected = self
+            .0
+            .suites
+            .iter()
+            .position(|suite| {
+                suites_r.contains(&suite.id) && self.0.auth.can_select(suite)
+            })
+            .ok_or(Error::UnsupportedSuite)?;
+
+        build_message_1(
+            self.0.version,
+            self.0.c_i,
+            self.0.ead_1,
+            ephemeral_secret,
+            self.0.auth,
+            self.0.kid,
+            self.0.method,
+            self.0.suites,
+            selected,
+        )
+    }
+
     /// Returns the key ID of the other party's public authentication key, and the state for verification 
     pub fn unpack_message_2_return_kid_ead(
         self,
         msg_2: Vec<u8>,
-    ) -> Result<(Vec<u8>, Vec<u8>, Option<Vec<u8>>,PartyI<Msg2Verifier>), OwnOrPeerError> {
+    ) -> Result<(Vec<u8>, Vec<u8>, Option<Vec<u8>>,PartyI<Msg2Verifier<K>>), OwnOrPeerError> {
 
         util::fail_on_error_message(&msg_2)?;
 
-        let msg_2 = util::deserialize_message_2(&msg_2).unwrap();
+        let version = self.0.version;
+        let msg_2 = util::deserialize_message_2(
+            version,
+            &msg_2,
+            self.0.suite.curve.public_key_length(),
+        )?;
 
-        let mut pub_ephemeral_r_bytes = [0; 32];
-        pub_ephemeral_r_bytes.copy_from_slice(&msg_2.ephemeral_key_r[..32]);
-        let pub_ephemeral_r = x25519_dalek_ng::PublicKey::from(pub_ephemeral_r_bytes);
+        let pub_ephemeral_r = K::decode_public(&msg_2.ephemeral_key_r)?;
         // Constructing shared secret 0 for initiator 
 
        let shared_secret_0 = self.0.priv_ek_i.diffie_hellman(&pub_ephemeral_r);
 
         // reconstructing keystream2
-        let c_r_cpy = msg_2.c_r.clone();
-        let th_2 = util::compute_th_2(self.0.msg_1_seq, &msg_2.c_r, pub_ephemeral_r)?;
-        let (prk_2e,prk_2e_hkdf) = util::extract_prk(None, shared_secret_0.as_bytes())?;
+        // draft-12 sends C_R in the clear, while it is encrypted in RFC 9528
+        let msg_c_r = msg_2.c_r.clone().unwrap_or_default();
+        let th_2 = util::compute_th_2(
+            version,
+            self.0.suite.hash,
+            &msg_2.ephemeral_key_r,
+            &msg_c_r,
+            &self.0.msg_1_seq,
+        )?;
+        let prk_2e = util::extract_prk_2e(version, self.0.suite.hash, &th_2, &shared_secret_0)?;
 
 
-        let keystream2 = util::edhoc_kdf(
-                                            &prk_2e_hkdf, 
+        let keystream2 = util::edhoc_kdf_th(
+                                            version,
+                                            &prk_2e, 
+                                            util::KEYSTREAM_2,
                                             &th_2, 
-                                            "KEYSTREAM_2",
-                                            b"",
                                             msg_2.ciphertext_2.len(), 
                                             )?;
 
         
 
-        let decryptedlaintext = util::xor(&keystream2, &msg_2.ciphertext_2)?;
+        let plaintext_2 = util::xor(&keystream2, &msg_2.ciphertext_2)?;
 
-        let (kid_r,mac_2,ead_2 ) = util::extract_plaintext(decryptedlaintext)?;
+        let (c_r, kid_r, mac_2, ead_2) = util::extract_plaintext_2(version, &plaintext_2)?;
+        let c_r = c_r.unwrap_or(msg_c_r);
         Ok((
             kid_r.clone(),
-            c_r_cpy,
+            c_r.clone(),
             ead_2.clone(),
             PartyI(Msg2Verifier {
+                version,
                 priv_ek_i : self.0.priv_ek_i,
-                priv_st_i: self.0.priv_st_i,
-                pub_st_i : self.0.pub_st_i,
+                auth: self.0.auth,
                 kid: self.0.kid,
-                msg_2,
+                method: self.0.method,
+                suite: self.0.suite,
+                plaintext_2,
+                ciphertext_2: msg_2.ciphertext_2,
+                c_r,
                 mac_2,
                 ead_2,
                 prk_2e,
@@ -174,7 +415,7 @@ impl PartyI<Msg2Receiver> {
     pub fn unpack_message_2_return_kid(
         self,
         msg_2: Vec<u8>,
-    ) -> Result<(Vec<u8>, Vec<u8>,PartyI<Msg2Verifier>), OwnOrPeerError> {
+    ) -> Result<(Vec<u8>, Vec<u8>,PartyI<Msg2Verifier<K>>), OwnOrPeerError> {
         let (kid, c_r , _ead, msg2_receiver) = self.unpack_message_2_return_kid_ead(msg_2)?;
 
         Ok((kid,c_r, msg2_receiver))
@@ -186,253 +427,316 @@ impl PartyI<Msg2Receiver> {
 
 
 /// Contains the state to verify the second message.
-pub struct Msg2Verifier {
-    priv_ek_i : StaticSecret,
-    priv_st_i : StaticSecret,
-    pub_st_i : PublicKey,
+pub struct Msg2Verifier<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ek_i : K,
+    auth: AuthKey<K>,
     kid: Vec<u8>,
-    msg_2: Message2,
+    method: u8,
+    suite: CipherSuite,
+    plaintext_2: Vec<u8>,
+    ciphertext_2: Vec<u8>,
+    c_r: Vec<u8>,
     mac_2: Vec<u8>,
     ead_2 : Option<Vec<u8>>,
-    prk_2e : Vec<u8>,
+    prk_2e : util::Prk,
     th_2: Vec<u8>,
     kid_r: Vec<u8>,
-    pub_ephemeral_r : PublicKey,
+    pub_ephemeral_r : K::Public,
 }
 
 
-impl PartyI<Msg2Verifier> {
+impl<K: DhSecret> PartyI<Msg2Verifier<K>> {
     /// Checks the authenticity of the second message with the other party's
     /// public authentication key.
+    ///
+    /// CRED_R is taken to be a CCS containing only the `COSE_Key` of the key.
+    /// Since that needs the sign of y, a static DH key on an EC2 curve is
+    /// expected as the compressed point, like `DhSecret::encode_point` gives.
     pub fn verify_message_2(
         self,
         pub_static_r_bytes: &[u8],
-    ) -> Result<PartyI<Msg3Sender>, OwnError> {
-
-        // build cred_x and id_cred_x (for responder party)
-        let id_cred_r = cose::build_id_cred_x(&self.0.kid_r)?;
-
-
-        let cred_r = cose::serialize_cred_x(pub_static_r_bytes,&self.0.kid_r )?; 
-
-        // Generating static public key of initiator
-        let mut buf = [0; 32];
-        buf.copy_from_slice(&pub_static_r_bytes[..32]);
-        let pub_static_r = x25519_dalek_ng::PublicKey::from(buf);
+    ) -> Result<PartyI<Msg3Sender<K>>, OwnError> {
+        let (_, r_signs) = util::method_signers(self.0.method)?;
+        let cred_r = peer_credential::<K>(
+            self.0.version,
+            &self.0.suite,
+            r_signs,
+            &self.0.kid_r,
+            pub_static_r_bytes,
+        )?;
 
-        // Generating shared secret 1 for initiator
+        self.verify_message_2_cred(pub_static_r_bytes, &cred_r)
+    }
 
-        let shared_secret_1 = self.0.priv_ek_i.diffie_hellman(&pub_static_r);
+    /// Like `verify_message_2`, for a given CRED_R.
+    ///
+    /// # Arguments
+    /// * `pub_static_r_bytes` - The public authentication key of R.
+    /// * `cred_r` - The CBOR encoded credential containing that key.
+    pub fn verify_message_2_cred(
+        self,
+        pub_static_r_bytes: &[u8],
+        cred_r: &[u8],
+    ) -> Result<PartyI<Msg3Sender<K>>, OwnError> {
+
+        let version = self.0.version;
+        // build id_cred_x (for responder party)
+        let id_cred_r = cose::build_id_cred_x(version, &self.0.kid_r)?;
+        let context_2 = util::MacContext {
+            version,
+            c_r: Some(&self.0.c_r),
+            id_cred_x: &id_cred_r,
+            th: &self.0.th_2,
+            cred_x: cred_r,
+            ead: &self.0.ead_2,
+        };
 
-        // generating prk_3
+        let (_, r_signs) = util::method_signers(self.0.method)?;
+        let prk_3e2m = if r_signs {
+            // R signs, so there is no static DH secret to add to the PRK
+            util::verify_signature(
+                self.0.suite.signature,
+                &self.0.prk_2e,
+                util::MAC_2,
+                &context_2,
+                pub_static_r_bytes,
+                &self.0.mac_2)?;
+
+            self.0.prk_2e
+        } else {
+            // Generating static public key of responder
+            let pub_static_r = K::decode_point(pub_static_r_bytes)?;
+
+            // Generating shared secret 1 for initiator
+            let shared_secret_1 = self.0.priv_ek_i.diffie_hellman(&pub_static_r);
+
+            // generating prk_3
+            let salt_3e2m = util::derive_salt(
+                version,
+                &self.0.prk_2e,
+                util::SALT_3E2M,
+                &self.0.th_2)?;
+            let (_, prk_3e2m) = util::extract_prk(self.0.suite.hash, Some(&salt_3e2m)
+                ,&shared_secret_1)?;
+
+            let mac_2 = util::create_mac_with_kdf(&prk_3e2m, 
+                self.0.suite.mac_length, 
+                util::MAC_2, 
+                &context_2)?;
+
+            if self.0.mac_2 != mac_2{
+                return Err(Error::BadMac.into())
+            }
 
-        let (prk_3e2m,prk_3e2m_hkdf) = util::extract_prk(Some(&self.0.prk_2e)
-            ,shared_secret_1.as_bytes())?;
+            prk_3e2m
+        };
 
-        
-        let mac_2 = util::create_mac_with_kdf(&prk_3e2m_hkdf, 
-            util::EDHOC_MAC /8, 
-            &self.0.th_2, 
-            "MAC_2", 
-            id_cred_r, 
-            cred_r,
-            &self.0.ead_2)?;
-      
-
-        if self.0.mac_2 != mac_2{
-
-            return Err(Error::BadMac.into())
-        }
+        let th_3 = util::compute_th_3(
+            version,
+            self.0.suite.hash,
+            &self.0.th_2,
+            &self.0.plaintext_2,
+            &self.0.ciphertext_2,
+            cred_r)?;
 
         Ok(PartyI(Msg3Sender{
-            priv_st_i : self.0.priv_st_i,
-            pub_st_i : self.0.pub_st_i,
+            version,
+            auth : self.0.auth,
+            suite : self.0.suite,
             pub_ephemeral_r: self.0.pub_ephemeral_r,
             i_kid : self.0.kid,
-            msg_2 : self.0.msg_2,
-            th_2 : self.0.th_2,
-            prk_3e2m_hkdf,
-            prk_3e2m
+            th_3,
+            prk_3e2m,
         }))
     }
 }
 
 /// Contains the state to build the third message.
-pub struct Msg3Sender {
-    priv_st_i : StaticSecret,
-    pub_st_i : PublicKey,
-    pub_ephemeral_r : PublicKey, 
+pub struct Msg3Sender<K: DhSecret = StaticSecret> {
+    version: Version,
+    auth : AuthKey<K>,
+    suite : CipherSuite,
+    pub_ephemeral_r : K::Public,
     i_kid: Vec<u8>,
-    msg_2: Message2,
-    th_2: Vec<u8>,
-    prk_3e2m_hkdf :  hkdf::Hkdf<sha2::Sha256>,
-    prk_3e2m : Vec<u8>,
-
+    th_3: Vec<u8>,
+    prk_3e2m :  util::Prk,
 }
 
-impl PartyI<Msg3Sender> {
+impl<K: DhSecret> PartyI<Msg3Sender<K>> {
     /// Returns the bytes of the third message, as well as the OSCORE master
     /// secret and the OSCORE master salt.
+    ///
+    /// CRED_I is a CCS containing only the `COSE_Key` of our key.
     pub fn generate_message_3(
         self,
         ead_3: Option<Vec<u8>>,
     ) -> Result<(PartyI<Msg4ReceiveVerify>,Vec<u8>), OwnError> {
+        let cred_i = self.0.auth.credential(self.0.version, &self.0.i_kid)?;
 
-        //first making necessary copies:
-
-        // Build the COSE header map identifying the public authentication key
-        let id_cred_i = cose::build_id_cred_x(&self.0.i_kid)?;
-        // Build the COSE_Key containing our public authentication key
-        let cred_i = cose::serialize_cred_x(&self.0.pub_st_i.to_bytes(), &self.0.i_kid)?;
-
-        let shared_secret_2 = self.0.priv_st_i.diffie_hellman(&self.0.pub_ephemeral_r);
-
-        
-        // transcript hash 3
+        self.generate_message_3_cred(ead_3, &cred_i)
+    }
 
-        let th_3 = util::compute_th_3(
-            &self.0.th_2, 
-            &self.0.msg_2.ciphertext_2)?;
-            
-        let (_,prk_4x3m_hkdf) = util::extract_prk(
-            Some(&self.0.prk_3e2m),
-             shared_secret_2.as_bytes())?;
+    /// Like `generate_message_3`, for a given CRED_I containing our public
+    /// authentication key.
+    pub fn generate_message_3_cred(
+        self,
+        ead_3: Option<Vec<u8>>,
+        cred_i: &[u8],
+    ) -> Result<(PartyI<Msg4ReceiveVerify>,Vec<u8>), OwnError> {
 
+        let version = self.0.version;
+        // Build the COSE header map identifying the public authentication key
+        let id_cred_i = cose::build_id_cred_x(version, &self.0.i_kid)?;
+        let context_3 = util::MacContext {
+            version,
+            c_r: None,
+            id_cred_x: &id_cred_i,
+            th: &self.0.th_3,
+            cred_x: cred_i,
+            ead: &ead_3,
+        };
 
-        let mac_3 = util::create_mac_with_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            util::EDHOC_MAC/8, 
-            &th_3,  
-            "MAC_3",
-             id_cred_i, 
-             cred_i,
-            &ead_3)?;
+        let (prk_4e3m, mac_3) = match &self.0.auth {
+            AuthKey::StaticDh(priv_st_i, _) => {
+                let shared_secret_2 = priv_st_i.diffie_hellman(&self.0.pub_ephemeral_r);
+
+                let salt_4e3m = util::derive_salt(
+                    version,
+                    &self.0.prk_3e2m,
+                    util::SALT_4E3M,
+                    &self.0.th_3)?;
+                let (_,prk_4e3m) = util::extract_prk(
+                    self.0.suite.hash,
+                    Some(&salt_4e3m),
+                     &shared_secret_2)?;
+
+                let mac_3 = util::create_mac_with_kdf(
+                    mac_3_prk(version, &self.0.prk_3e2m, &prk_4e3m), 
+                    self.0.suite.mac_length, 
+                    util::MAC_3,
+                    &context_3)?;
+
+                (prk_4e3m, mac_3)
+            }
+            AuthKey::Signature(signing_key) => {
+                // We sign, so there is no static DH secret to add to the PRK
+                let signature_3 = util::create_signature(
+                    &self.0.prk_3e2m,
+                    util::MAC_3,
+                    &context_3,
+                    signing_key)?;
+
+                (self.0.prk_3e2m.clone(), signature_3)
+            }
+        };
 
-        
-        let k_3 = util::edhoc_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            &th_3, 
-            "K_3",
-            b"",
-            util::CCM_KEY_LEN / 8)?;
+        let k_3 = util::edhoc_kdf_th(
+            version,
+            &self.0.prk_3e2m, 
+            util::K_3,
+            &self.0.th_3, 
+            self.0.suite.aead.key_length())?;
             
-        let iv_3 = util::edhoc_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            &th_3, 
-            "IV_3",
-            b"",
-            util::CCM_NONCE_LEN / 8)?;
-        let p = util::build_plaintext(&self.0.i_kid, &mac_3,ead_3)?;
-
-        let ad = cose::build_ad(&th_3)?;
+        let iv_3 = util::edhoc_kdf_th(
+            version,
+            &self.0.prk_3e2m, 
+            util::IV_3,
+            &self.0.th_3, 
+            self.0.suite.aead.nonce_length())?;
+        let plaintext_3 = util::build_plaintext(version, &self.0.i_kid, &mac_3, &ead_3)?;
+
+        let ad = cose::build_ad(&self.0.th_3)?;
         // Constructing ciphertext:
-        let ciphertext_3 = util::aead_seal(&k_3, &iv_3, &p, &ad)?;
-        let th_4 = util::compute_th_4(&th_3, &ciphertext_3)?;
+        let ciphertext_3 = util::aead_seal(self.0.suite.aead, &k_3, &iv_3, &plaintext_3, &ad)?;
+        let th_4 = util::compute_th_4(
+            version,
+            self.0.suite.hash,
+            &self.0.th_3,
+            &plaintext_3,
+            &ciphertext_3,
+            cred_i,
+        )?;
 
         let msg_3 = Message3 {ciphertext: ciphertext_3};
         let msg_3_seq = util::serialize_message_3(&msg_3)?;
 
-
-
-        // now computing the values needed for sck and rck
-
-
-        let master_secret = util::edhoc_exporter(
-            &prk_4x3m_hkdf,
-            &th_4,
-            "OSCORE_Master_Secret",
-            b"",
-            util::CCM_KEY_LEN/8, //going from bits to bytes
-
-        )?;
-        let master_salt = util::edhoc_exporter(
-            &prk_4x3m_hkdf,
-            &th_4,
-            "OSCORE_Master_Salt",
-            b"",
-            util::SALT_LENGTH/8,//going from bits to bytes
-        )?;
+        // The session is only handed out once message_4 is verified
+        let session = Session::new(version, self.0.suite, &prk_4e3m, &th_4)?;
 
         Ok((PartyI(Msg4ReceiveVerify {
-            prk_4x3m_hkdf,
+            version,
+            suite: self.0.suite,
+            prk_4e3m,
             th_4,
-            master_salt,
-            master_secret
+            session,
         }),msg_3_seq))
     }
 }
 
 
+/// Contains the state to receive and verify the fourth message.
 pub struct Msg4ReceiveVerify {
-    prk_4x3m_hkdf : hkdf::Hkdf<sha2::Sha256>,
+    version: Version,
+    suite : CipherSuite,
+    prk_4e3m : util::Prk,
     th_4 : Vec<u8>,
-    master_secret : Vec<u8>,
-    master_salt : Vec<u8>,
+    session: Session,
 }
 
-    /// Handle message four, and return output keying material and ead, if wanted
+impl PartyI<Msg4ReceiveVerify> {
+    /// Handle message four, and return the completed session and ead.
     ///
     /// # Arguments
     /// * `msg4_seq` msg 4 as bytes
-    /// Outputs (sck,rck,rk,ead)
-
-
-impl PartyI<Msg4ReceiveVerify> {
-    pub fn handle_message_4_ead(
+    pub fn handle_message_4_session(
         self,
         msg4_seq : Vec<u8>,
-    ) -> Result<(Vec<u8>, Vec<u8>,Vec<u8>,Vec<u8>), OwnOrPeerError> {
+    ) -> Result<(Session, Vec<u8>), OwnOrPeerError> {
 
         util::fail_on_error_message(&msg4_seq)?;
         let msg4 = util::deserialize_message_4(&msg4_seq)?;
 
-        let k_4 = util::edhoc_exporter(
-            &self.0.prk_4x3m_hkdf,
+        let k_4 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_4e3m,
+            util::K_4,
             &self.0.th_4,
-            "EDHOC_K_4",
-            b"",
-            util::CCM_KEY_LEN /8, //going from bits to bytes
+            self.0.suite.aead.key_length(),
         )?;
 
-        let iv_4 = util::edhoc_exporter(
-            &self.0.prk_4x3m_hkdf,
+        let iv_4 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_4e3m,
+            util::IV_4,
             &self.0.th_4,
-            "EDHOC_IV_4",
-            b"",
-            util::CCM_NONCE_LEN/8 , //going from bits to bytes
+            self.0.suite.aead.nonce_length(),
         )?;
         let ad = cose::build_ad(&self.0.th_4)?;
 
-        let plaintext = util::aead_open(&k_4, &iv_4, &msg4.ciphertext, &ad)?;
-        let ead : Vec<u8> = Vec::new();
-        if !plaintext.is_empty(){
-            let ead = util::deserialize_ead(&plaintext)?;
-        }
-        
-
-
-        let sck = util::extract_expand(
-            &self.0.master_secret,
-            &self.0.master_salt, 
-            "DOWNLINK", 
-            32, 
-            )?;
-
-        let rck = util::extract_expand(
-            &self.0.master_secret,
-            &self.0.master_salt,
-            "UPLINK", 
-            32,  
-            )?;
+        let plaintext = util::aead_open(self.0.suite.aead, &k_4, &iv_4, &msg4.ciphertext, &ad)?;
+        let ead = if plaintext.is_empty() {
+            Vec::new()
+        } else {
+            util::deserialize_ead(&plaintext)?
+        };
 
-        let rk = util::extract_expand(
-            &self.0.master_secret,
-            &self.0.master_salt,
-            "RK0", 
-            32,  
-            )?;
+        Ok((self.0.session, ead))
+    }
 
-    
+    /// Handle message four, and return output keying material and ead, if wanted
+    ///
+    /// # Arguments
+    /// * `msg4_seq` msg 4 as bytes
+    ///
+    /// Outputs (sck,rck,rk,ead)
+    pub fn handle_message_4_ead(
+        self,
+        msg4_seq : Vec<u8>,
+    ) -> Result<(Vec<u8>, Vec<u8>,Vec<u8>,Vec<u8>), OwnOrPeerError> {
+        let (session, ead) = self.handle_message_4_session(msg4_seq)?;
+        // I sends on the downlink
+        let (rck, sck, rk) = session.link_keys()?;
 
         Ok((sck,rck,rk,ead))
     }
@@ -453,49 +757,87 @@ impl PartyI<Msg4ReceiveVerify> {
 pub struct PartyR<S: PartyRState>(pub S);
 // Necessary stuff for session types
 pub trait PartyRState {}
-impl PartyRState for Msg1Receiver {}
-impl PartyRState for Msg2Sender {}
-impl PartyRState for Msg3Receiver {}
-impl PartyRState for Msg3verifier {}
+impl<K: DhSecret> PartyRState for Msg1Receiver<K> {}
+impl<K: DhSecret> PartyRState for Msg2Sender<K> {}
+impl<K: DhSecret> PartyRState for Msg3Receiver<K> {}
+impl<K: DhSecret> PartyRState for Msg3verifier<K> {}
 impl PartyRState for Msg4Sender {}
 
 /// Contains the state to receive the first message.
-/// 
-pub struct Msg1Receiver {
-    priv_ephemeral_r: StaticSecret,
-    pub_ephemeral_r: PublicKey,
-    pub_static_r: PublicKey,
-    priv_static_r: StaticSecret,
+pub struct Msg1Receiver<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ephemeral_r: K,
+    pub_ephemeral_r: K::Public,
+    auth: AuthKey<K>,
     kid: Vec<u8>,
 }
 
-impl PartyR<Msg1Receiver> {
+impl<K: DhSecret> PartyR<Msg1Receiver<K>> {
     /// Creates a new `PartyR` ready to receive the first message.
     ///
+    /// The group of `ecdh_secret` determines the suites `PartyR` accepts.
+    /// Since `message_1` doesn't tell the version of EDHOC I speaks, a
+    /// responder serving both draft-12 and RFC 9528 initiators picks it per
+    /// protocol run, e.g. by the endpoint the message arrived on.
+    ///
     /// # Arguments
-    /// * `c_v` - The chosen connection identifier.
     /// * `ecdh_secret` - The ECDH secret to use for this protocol run.
-    /// * `auth_private` - The private ed25519 authentication key.
-    /// * `auth_public` - The public ed25519 authentication key.
+    /// * `priv_static_r` - The private static DH authentication key.
+    /// * `pub_static_r` - The public static DH authentication key.
     /// * `kid` - The key ID by which the other party is able to retrieve
-    ///   `auth_public`.
+    ///   `pub_static_r`.
+    /// * `version` - The version of EDHOC I speaks.
     pub fn new(
-        ecdh_secret: [u8; 32],
-        priv_static_r: StaticSecret,
-        pub_static_r: PublicKey,
+        ecdh_secret: K,
+        priv_static_r: K,
+        pub_static_r: K::Public,
         kid: Vec<u8>,
-    ) -> PartyR<Msg1Receiver> {
-        // From the secret bytes, create the DH secret
-        let priv_ephemeral_r = StaticSecret::from(ecdh_secret);
-        // and from that build the corresponding public key
-        let pub_ephemeral_r = PublicKey::from(&priv_ephemeral_r);
-        // Combine the authentication key pair for convenience
+        version: Version,
+    ) -> PartyR<Msg1Receiver<K>> {
+        PartyR::new_with_auth(
+            ecdh_secret,
+            AuthKey::StaticDh(priv_static_r, pub_static_r),
+            kid,
+            version,
+        )
+    }
+
+    /// Creates a new `PartyR` authenticating with a signature key.
+    ///
+    /// # Arguments
+    /// * `ecdh_secret` - The ECDH secret to use for this protocol run.
+    /// * `signing_key` - The private Ed25519 or P-256 authentication key.
+    /// * `kid` - The key ID by which the other party is able to retrieve
+    ///   the public part of `signing_key`.
+    /// * `version` - The version of EDHOC I speaks.
+    pub fn new_sig(
+        ecdh_secret: K,
+        signing_key: impl Into<SigningKey>,
+        kid: Vec<u8>,
+        version: Version,
+    ) -> PartyR<Msg1Receiver<K>> {
+        PartyR::new_with_auth(
+            ecdh_secret,
+            AuthKey::Signature(signing_key.into()),
+            kid,
+            version,
+        )
+    }
+
+    fn new_with_auth(
+        priv_ephemeral_r: K,
+        auth: AuthKey<K>,
+        kid: Vec<u8>,
+        version: Version,
+    ) -> PartyR<Msg1Receiver<K>> {
+        // Build the public key corresponding to our ephemeral secret
+        let pub_ephemeral_r = priv_ephemeral_r.public_key();
 
         PartyR(Msg1Receiver {
+            version,
             priv_ephemeral_r,
             pub_ephemeral_r,
-            priv_static_r,
-            pub_static_r,
+            auth,
             kid,
         })
     }
@@ -504,33 +846,50 @@ impl PartyR<Msg1Receiver> {
     pub fn handle_message_1_ead(
         self,
         msg_1: Vec<u8>,
-    ) -> Result<(PartyR<Msg2Sender>,Vec<u8>,Option<Vec<u8>>), OwnError> {
+    ) -> Result<(PartyR<Msg2Sender<K>>,Vec<u8>,Option<Vec<u8>>), OwnError> {
         // Alias this
         let msg_1_seq = msg_1;
         // Decode the first message
 
 
-        let msg_1 = util::deserialize_message_1(&msg_1_seq)?;
+        let msg_1 = util::deserialize_message_1(self.0.version, &msg_1_seq)?;
 
 
-        // Verify that the selected suite is supported
-        
-        if msg_1.suite != 0 {
-            return Err(Error::UnsupportedSuite.into())
+        // Verify that the selected suite, which is the last one in SUITES_I,
+        // is supported, which requires both our ephemeral and our
+        // authentication key to belong to it. For downgrade protection,
+        // also verify that I didn't skip any suite we support, which it
+        // prefers. In both cases, we tell I which suites we support.
+        let suites_r = self.0.auth.supported_suites();
+        let (selected, preferred) = msg_1
+            .suites_i
+            .split_last()
+            .ok_or(Error::UnsupportedSuite)?;
+        if !suites_r.contains(selected)
+            || preferred.iter().any(|suite| suites_r.contains(suite))
+        {
+            return Err(OwnError(util::build_suites_error_message(&suites_r)?));
+        }
+        let suite = CipherSuite::from_id(*selected)?;
+        // Verify that we can take part in the selected method, which is the
+        // case when our side of it matches our authentication key
+        let (_, r_signs) = util::method_signers(msg_1.method)?;
+        if r_signs != self.0.auth.signs() {
+            return Err(Error::UnsupportedMethod.into())
         }
         // Use U's public key to generate the ephemeral shared secret
-        let mut ed_key_bytes = [0; 32];
-        ed_key_bytes.copy_from_slice(&msg_1.pub_ek_i[..32]);
-        let pub_ek_i = x25519_dalek_ng::PublicKey::from(ed_key_bytes);
+        let pub_ek_i = K::decode_public(&msg_1.pub_ek_i)?;
 
 
         Ok((PartyR(Msg2Sender {
+            version: self.0.version,
             priv_ephemeral_r : self.0.priv_ephemeral_r,
             pub_ephemeral_r: self.0.pub_ephemeral_r,
-            pub_static_r: self.0.pub_static_r,
-            priv_static_r : self.0.priv_static_r,
+            auth: self.0.auth,
             pub_ephemeral_i : pub_ek_i,
             kid_r: self.0.kid,
+            method: msg_1.method,
+            suite,
             msg_1_seq,
         }),
         msg_1.c_i,
@@ -540,7 +899,7 @@ impl PartyR<Msg1Receiver> {
     pub fn handle_message_1(
         self,
         msg_1: Vec<u8>,
-    ) -> Result<(PartyR<Msg2Sender>,Vec<u8>), OwnError> {
+    ) -> Result<(PartyR<Msg2Sender<K>>,Vec<u8>), OwnError> {
         // simply wrapping the handling of message 1, but not returning ead, allowing R to discard ead
         let (msg2_sender, c_i, _ead) = self.handle_message_1_ead(msg_1)?;
 
@@ -554,65 +913,131 @@ impl PartyR<Msg1Receiver> {
 /// shared_secret_1 : The second shared secret, created only from I's  ephemeral key,R and static key
 /// shared_secret_2 : the third shared secret, created only from I's  static key, and R's ephemeral key
 /// (this is from the side of I)
-pub struct Msg2Sender {
-    priv_ephemeral_r: StaticSecret,
-    pub_ephemeral_r: PublicKey,
-    pub_static_r : PublicKey,
-    priv_static_r : StaticSecret,
-    pub_ephemeral_i : PublicKey,
+pub struct Msg2Sender<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ephemeral_r: K,
+    pub_ephemeral_r: K::Public,
+    auth: AuthKey<K>,
+    pub_ephemeral_i : K::Public,
     kid_r: Vec<u8>,
+    method: u8,
+    suite: CipherSuite,
     msg_1_seq: Vec<u8>,
 }
 
-impl PartyR<Msg2Sender> {
+impl<K: DhSecret> PartyR<Msg2Sender<K>> {
+    /// Returns the authentication method selected by I, telling whether the
+    /// key later passed to `verify_message_3` is a signature or a static DH
+    /// key.
+    pub fn method(&self) -> u8 {
+        self.0.method
+    }
+
     /// Returns the bytes of the second message.
+    ///
+    /// CRED_R is a CCS containing only the `COSE_Key` of our key.
     pub fn generate_message_2(
         self,
         c_r : Vec<u8>,
         ead_2 : Option<Vec<u8>>,
-    ) -> Result<(Vec<u8>, PartyR<Msg3Receiver>),OwnOrPeerError> {
-            // first we need to build the id_cred_r from the kid
-            let id_cred_r = cose::build_id_cred_x(&self.0.kid_r)?;
+    ) -> Result<(Vec<u8>, PartyR<Msg3Receiver<K>>),OwnOrPeerError> {
+        let cred_r = self.0.auth.credential(self.0.version, &self.0.kid_r)?;
 
-            // We now build the cred_x using the public key, and kid value
-            let cred_r = cose::serialize_cred_x(&self.0.pub_static_r.to_bytes(),&self.0.kid_r )?; 
+        self.generate_message_2_cred(c_r, ead_2, &cred_r)
+    }
 
-            let th_2 = util::compute_th_2(self.0.msg_1_seq, &c_r, self.0.pub_ephemeral_r)?;
+    /// Like `generate_message_2`, for a given CRED_R containing our public
+    /// authentication key.
+    pub fn generate_message_2_cred(
+        self,
+        c_r : Vec<u8>,
+        ead_2 : Option<Vec<u8>>,
+        cred_r : &[u8],
+    ) -> Result<(Vec<u8>, PartyR<Msg3Receiver<K>>),OwnOrPeerError> {
+            let version = self.0.version;
+            // first we need to build the id_cred_r from the kid
+            let id_cred_r = cose::build_id_cred_x(version, &self.0.kid_r)?;
 
-            let shared_secret_0 = self.0.priv_ephemeral_r.diffie_hellman(&self.0.pub_ephemeral_i);
+            let pub_ephemeral_r = K::encode_public(&self.0.pub_ephemeral_r);
 
-            let (prk_2e,prk_2e_hkdf) = util::extract_prk(None, shared_secret_0.as_bytes())?;
+            let th_2 = util::compute_th_2(
+                version,
+                self.0.suite.hash,
+                &pub_ephemeral_r,
+                &c_r,
+                &self.0.msg_1_seq,
+            )?;
 
-            let shared_secret_1 = self.0.priv_static_r.diffie_hellman(&self.0.pub_ephemeral_i);
+            let shared_secret_0 = self.0.priv_ephemeral_r.diffie_hellman(&self.0.pub_ephemeral_i);
 
-            let (prk_3e2m,prk_3e2m_hkdf) = util::extract_prk(Some(&prk_2e),shared_secret_1.as_bytes())?;
+            let prk_2e = util::extract_prk_2e(version, self.0.suite.hash, &th_2, &shared_secret_0)?;
 
-            let mac_2 = util::create_mac_with_kdf(
-                &prk_3e2m_hkdf, 
-                util::EDHOC_MAC/8, 
-                &th_2, 
-                "MAC_2", 
-                id_cred_r, 
-                cred_r,
-                &ead_2)?;
+            let context_2 = util::MacContext {
+                version,
+                c_r: Some(&c_r),
+                id_cred_x: &id_cred_r,
+                th: &th_2,
+                cred_x: cred_r,
+                ead: &ead_2,
+            };
 
+            let (prk_3e2m, mac_2) = match &self.0.auth {
+                AuthKey::StaticDh(priv_static_r, _) => {
+                    let shared_secret_1 = priv_static_r.diffie_hellman(&self.0.pub_ephemeral_i);
+
+                    let salt_3e2m = util::derive_salt(
+                        version,
+                        &prk_2e,
+                        util::SALT_3E2M,
+                        &th_2)?;
+                    let (_, prk_3e2m) = util::extract_prk(self.0.suite.hash, Some(&salt_3e2m),&shared_secret_1)?;
+
+                    let mac_2 = util::create_mac_with_kdf(
+                        &prk_3e2m, 
+                        self.0.suite.mac_length, 
+                        util::MAC_2, 
+                        &context_2)?;
+
+                    (prk_3e2m, mac_2)
+                }
+                AuthKey::Signature(signing_key) => {
+                    // We sign, so there is no static DH secret to add to the PRK
+                    let signature_2 = util::create_signature(
+                        &prk_2e,
+                        util::MAC_2,
+                        &context_2,
+                        signing_key)?;
+
+                    (prk_2e.clone(), signature_2)
+                }
+            };
 
-            
-            let plaintext_encoded = util::build_plaintext(&self.0.kid_r, &mac_2,ead_2)?;
+            let plaintext_2 = util::build_plaintext_2(version, &c_r, &self.0.kid_r, &mac_2, &ead_2)?;
 
-            let keystream2 = util::edhoc_kdf(
-                &prk_2e_hkdf, 
+            let keystream2 = util::edhoc_kdf_th(
+                version,
+                &prk_2e, 
+                util::KEYSTREAM_2,
                 &th_2, 
-                "KEYSTREAM_2",
-                b"",
-                plaintext_encoded.len(), 
+                plaintext_2.len(), 
                 )?;
-            let ciphertext_2 = util::xor(&keystream2, &plaintext_encoded)?;
-
+            let ciphertext_2 = util::xor(&keystream2, &plaintext_2)?;
+
+            let th_3 = util::compute_th_3(
+                version,
+                self.0.suite.hash,
+                &th_2,
+                &plaintext_2,
+                &ciphertext_2,
+                cred_r,
+            )?;
 
             let msg_2 = Message2 {
-                ephemeral_key_r : self.0.pub_ephemeral_r.as_bytes().to_vec(),
-                c_r,
+                ephemeral_key_r : pub_ephemeral_r,
+                c_r: match version {
+                    Version::Draft12 => Some(c_r),
+                    Version::Rfc9528 => None,
+                },
                 ciphertext_2,
             };
 
@@ -621,11 +1046,12 @@ impl PartyR<Msg2Sender> {
 
             Ok((msg2_seq, 
                 PartyR(Msg3Receiver {
+                    version,
                     priv_ephemeral_r: self.0.priv_ephemeral_r,
-                    prk_3e2m_hkdf,
+                    method: self.0.method,
+                    suite: self.0.suite,
                     prk_3e2m,
-                    msg_2,
-                    th_2,
+                    th_3,
                 }),
             ))
 
@@ -634,66 +1060,66 @@ impl PartyR<Msg2Sender> {
 }
 
 /// Contains the state to receive the third message.
-pub struct Msg3Receiver {
-    priv_ephemeral_r : StaticSecret,
-    prk_3e2m_hkdf  : hkdf::Hkdf<sha2::Sha256>,
-    prk_3e2m : Vec<u8>,
-    msg_2: Message2,
-    th_2: Vec<u8>,
+pub struct Msg3Receiver<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ephemeral_r : K,
+    method : u8,
+    suite : CipherSuite,
+    prk_3e2m : util::Prk,
+    th_3: Vec<u8>,
 }
 
-impl PartyR<Msg3Receiver> {
+impl<K: DhSecret> PartyR<Msg3Receiver<K>> {
     /// Returns the kid of the other party, and the state to verify
     pub fn unpack_message_3_return_kid_ead(
         self,
         msg_3_seq: Vec<u8>,
-    ) -> Result<(PartyR<Msg3verifier>, Vec<u8>,Option<Vec<u8>>), OwnOrPeerError> {
+    ) -> Result<(PartyR<Msg3verifier<K>>, Vec<u8>,Option<Vec<u8>>), OwnOrPeerError> {
         util::fail_on_error_message(&msg_3_seq)?;
         // first, relevant copies:
 
         let msg_3 = util::deserialize_message_3(&msg_3_seq)?;
 
 
-        let th_3 = util::compute_th_3(
-            &self.0.th_2, 
-            &self.0.msg_2.ciphertext_2)?;
-
-
-        let k_3 = util::edhoc_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            &th_3, 
-            "K_3",
-            b"",
-            util::CCM_KEY_LEN / 8)?;
-
-        let iv_3 = util::edhoc_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            &th_3, 
-            "IV_3",
-            b"",
-            util::CCM_NONCE_LEN / 8)?;
+        let k_3 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_3e2m, 
+            util::K_3,
+            &self.0.th_3, 
+            self.0.suite.aead.key_length())?;
+
+        let iv_3 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_3e2m, 
+            util::IV_3,
+            &self.0.th_3, 
+            self.0.suite.aead.nonce_length())?;
         
-        let ad = cose::build_ad(&th_3)?;
+        let ad = cose::build_ad(&self.0.th_3)?;
 
 
 
-        let p = util::aead_open(
+        let plaintext_3 = util::aead_open(
+            self.0.suite.aead,
             &k_3, 
             &iv_3, 
             &msg_3.ciphertext, 
             &ad)?;
         
-        let (kid_r, mac3,ead_3) = util::extract_plaintext(p)?;
+        let (kid_r, mac3,ead_3) = util::extract_plaintext(self.0.version, &plaintext_3)?;
 
         Ok((PartyR(Msg3verifier{
+            version: self.0.version,
             priv_ephemeral_r : self.0.priv_ephemeral_r,
-            prk_3e2m_hkdf : self.0.prk_3e2m_hkdf,
+            method : self.0.method,
+            suite : self.0.suite,
             prk_3e2m : self.0.prk_3e2m,
-            msg_3,
+            plaintext_3,
+            ciphertext_3: msg_3.ciphertext,
             kid : kid_r.clone(),
             mac3,
             ead_3 : ead_3.clone(),
-            th_3,
+            th_3: self.0.th_3,
         }),
         kid_r,
         ead_3))
@@ -702,7 +1128,7 @@ impl PartyR<Msg3Receiver> {
     pub fn unpack_message_3_return_kid(
         self,
         msg_3_seq: Vec<u8>,
-    ) -> Result<(PartyR<Msg3verifier>, Vec<u8>), OwnOrPeerError> {
+    ) -> Result<(PartyR<Msg3verifier<K>>, Vec<u8>), OwnOrPeerError> {
 
         let (msg_3_verifier, kid, _ead_3) = self.unpack_message_3_return_kid_ead(msg_3_seq)?;
         Ok((
@@ -712,97 +1138,122 @@ impl PartyR<Msg3Receiver> {
 }
 
 
-pub struct Msg3verifier {
-    priv_ephemeral_r : StaticSecret,
-    prk_3e2m_hkdf : hkdf::Hkdf<sha2::Sha256>,
-    prk_3e2m : Vec<u8>,
-    msg_3 : Message3,
+pub struct Msg3verifier<K: DhSecret = StaticSecret> {
+    version: Version,
+    priv_ephemeral_r : K,
+    method : u8,
+    suite : CipherSuite,
+    prk_3e2m : util::Prk,
+    plaintext_3 : Vec<u8>,
+    ciphertext_3 : Vec<u8>,
     kid : Vec<u8>,
     mac3 : Vec<u8>,
     ead_3 : Option<Vec<u8>>,
     th_3: Vec<u8>,
 }
-impl PartyR<Msg3verifier> {
-    /// Returns the key ID of the other party's public authentication key.
+impl<K: DhSecret> PartyR<Msg3verifier<K>> {
+    /// Checks the authenticity of the third message with the other party's
+    /// public authentication key, and returns the keys of both directions
+    /// and the root key.
+    ///
+    /// CRED_I is taken to be a CCS containing only the `COSE_Key` of the key.
+    /// Since that needs the sign of y, a static DH key on an EC2 curve is
+    /// expected as the compressed point, like `DhSecret::encode_point` gives.
     pub fn verify_message_3(
         self,
         i_public_static_bytes: &[u8],
     ) -> Result<(PartyR<Msg4Sender>, Vec<u8>, Vec<u8>,Vec<u8>), OwnOrPeerError> {
-        let mut statkey_i_bytes = [0; 32];
-        statkey_i_bytes.copy_from_slice(&i_public_static_bytes[..32]);
-        let i_public_static = x25519_dalek_ng::PublicKey::from(statkey_i_bytes);
-        let shared_secret_2 = self.0.priv_ephemeral_r.diffie_hellman(&i_public_static);
-    
-
-        let id_cred_i = cose::build_id_cred_x(&self.0.kid)?;
-
-
-
-        let cred_i = cose::serialize_cred_x(&i_public_static.to_bytes(), &self.0.kid)?;
-
-
+        let (i_signs, _) = util::method_signers(self.0.method)?;
+        let cred_i = peer_credential::<K>(
+            self.0.version,
+            &self.0.suite,
+            i_signs,
+            &self.0.kid,
+            i_public_static_bytes,
+        )?;
 
-        let mac_3_initiator = util::create_mac_with_kdf(
-            &self.0.prk_3e2m_hkdf, 
-            util::EDHOC_MAC/8, 
-            &self.0.th_3,  
-            "MAC_3",
-             id_cred_i, 
-             cred_i,
-            &self.0.ead_3)?;
+        self.verify_message_3_cred(i_public_static_bytes, &cred_i)
+    }
 
+    /// Like `verify_message_3`, for a given CRED_I.
+    ///
+    /// # Arguments
+    /// * `i_public_static_bytes` - The public authentication key of I.
+    /// * `cred_i` - The CBOR encoded credential containing that key.
+    pub fn verify_message_3_cred(
+        self,
+        i_public_static_bytes: &[u8],
+        cred_i: &[u8],
+    ) -> Result<(PartyR<Msg4Sender>, Vec<u8>, Vec<u8>,Vec<u8>), OwnOrPeerError> {
+        let version = self.0.version;
+        let id_cred_i = cose::build_id_cred_x(version, &self.0.kid)?;
+        let context_3 = util::MacContext {
+            version,
+            c_r: None,
+            id_cred_x: &id_cred_i,
+            th: &self.0.th_3,
+            cred_x: cred_i,
+            ead: &self.0.ead_3,
+        };
 
-        if mac_3_initiator != self.0.mac3{
-           return  Err(Error::BadMac.into())
+        let (i_signs, _) = util::method_signers(self.0.method)?;
+        let prk_4e3m = if i_signs {
+            // I signs, so there is no static DH secret to add to the PRK
+            util::verify_signature(
+                self.0.suite.signature,
+                &self.0.prk_3e2m,
+                util::MAC_3,
+                &context_3,
+                i_public_static_bytes,
+                &self.0.mac3)?;
+
+            self.0.prk_3e2m
+        } else {
+            let i_public_static = K::decode_point(i_public_static_bytes)?;
+            let shared_secret_2 = self.0.priv_ephemeral_r.diffie_hellman(&i_public_static);
+
+            let salt_4e3m = util::derive_salt(
+                version,
+                &self.0.prk_3e2m,
+                util::SALT_4E3M,
+                &self.0.th_3)?;
+            let (_,prk_4e3m) = util::extract_prk(
+                    self.0.suite.hash,
+                    Some(&salt_4e3m),
+                     &shared_secret_2)?;
+
+            let mac_3_initiator = util::create_mac_with_kdf(
+                mac_3_prk(version, &self.0.prk_3e2m, &prk_4e3m), 
+                self.0.suite.mac_length, 
+                util::MAC_3,
+                &context_3)?;
+
+            if mac_3_initiator != self.0.mac3{
+               return  Err(Error::BadMac.into())
             }
 
+            prk_4e3m
+        };
 
-        let th_4 = util::compute_th_4(&self.0.th_3, &self.0.msg_3.ciphertext)?;
+        let th_4 = util::compute_th_4(
+            version,
+            self.0.suite.hash,
+            &self.0.th_3,
+            &self.0.plaintext_3,
+            &self.0.ciphertext_3,
+            cred_i,
+        )?;
 
-        let (_,prk_4x3m_hkdf) = util::extract_prk(
-                Some(&self.0.prk_3e2m),
-                 shared_secret_2.as_bytes())?;
-    
-        let master_secret = util::edhoc_exporter(
-                     &prk_4x3m_hkdf,
-                     &th_4,
-                    "OSCORE_Master_Secret",
-                    b"",
-                    util::CCM_KEY_LEN / 8, //going from bits to bytes
-                )?;
-       let master_salt = util::edhoc_exporter(
-                    &prk_4x3m_hkdf,
-                    &th_4,
-                    "OSCORE_Master_Salt",
-                    b"",
-                    util::SALT_LENGTH / 8,//going from bits to bytes
-                    
-                )?;
-        
-        let sck = util::extract_expand(
-                    &master_secret,
-                    &master_salt, 
-                    "UPLINK", 
-                    32, 
-                    )?;
-        
-       let rck = util::extract_expand(
-                    &master_secret,
-                    &master_salt, 
-                    "DOWNLINK", 
-                    32,  
-                    )?;
-        
-        let rk = util::extract_expand(
-                    &master_secret,
-                    &master_salt, 
-                    "RK0", 
-                    32,  
-                    )?;
+        let session = Session::new(version, self.0.suite, &prk_4e3m, &th_4)?;
+        // R sends on the uplink
+        let (sck, rck, rk) = session.link_keys()?;
 
         Ok((PartyR(Msg4Sender{
-            prk_4x3m_hkdf,
+            version,
+            suite: self.0.suite,
+            prk_4e3m,
             th_4,
+            session,
             }),
         sck,
         rck,
@@ -813,65 +1264,118 @@ impl PartyR<Msg3verifier> {
 }
 /// Contains the state to verify the third message.
 pub struct Msg4Sender {
-    prk_4x3m_hkdf :hkdf::Hkdf<sha2::Sha256>,
+    version: Version,
+    suite : CipherSuite,
+    prk_4e3m :util::Prk,
     th_4 : Vec<u8>,
+    session: Session,
 }
 
 
 impl PartyR<Msg4Sender> {
-    pub fn generate_message_4(
+    /// Generates message four, and returns it with the completed session.
+    ///
+    /// # Arguments
+    /// * `ead_4` - The optional EAD to send.
+    pub fn generate_message_4_session(
         self,
         ead_4 :Option<Vec<u8>>,
-    ) -> Result< Vec<u8>, OwnOrPeerError> {
+    ) -> Result<(Vec<u8>, Session), OwnOrPeerError> {
 
 
-        let k_4 = util::edhoc_exporter(
-            &self.0.prk_4x3m_hkdf,
+        let k_4 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_4e3m,
+            util::K_4,
             &self.0.th_4,
-            "EDHOC_K_4",
-            b"",
-            util::CCM_KEY_LEN/8 , //going from bits to bytes
+            self.0.suite.aead.key_length(),
         )?;
 
-        let iv_4 = util::edhoc_exporter(
-            &self.0.prk_4x3m_hkdf,
+        let iv_4 = util::edhoc_kdf_th(
+            self.0.version,
+            &self.0.prk_4e3m,
+            util::IV_4,
             &self.0.th_4,
-            "EDHOC_IV_4",
-            b"",
-            util::CCM_NONCE_LEN /8, //going from bits to bytes
+            self.0.suite.aead.nonce_length(),
         )?;
 
         let ad = cose::build_ad(&self.0.th_4)?;
 
+        // PLAINTEXT_4 only consists of the optional EAD
+        let plaintext_4 = util::serialize_optional_ead(&ead_4)?;
 
-        let tmp : Vec<u8>;
-        let p = match ead_4 {
-            Some(x) => { 
-                tmp = util::serialize_ead(&x)?;
-                &tmp
-            },
-            None => "".as_bytes(),
-        };
-
-        let ciphertext_4 = util::aead_seal(&k_4, &iv_4, p, &ad)?;
+        let ciphertext_4 = util::aead_seal(self.0.suite.aead, &k_4, &iv_4, &plaintext_4, &ad)?;
 
         let msg4 = Message4 {
             ciphertext : ciphertext_4,
         };
         let msg4_seq = util::serialize_message_4(&msg4)?;
 
+        Ok((msg4_seq, self.0.session))
+    }
+
+    pub fn generate_message_4(
+        self,
+        ead_4 :Option<Vec<u8>>,
+    ) -> Result< Vec<u8>, OwnOrPeerError> {
+        let (msg4_seq, _) = self.generate_message_4_session(ead_4)?;
         Ok(msg4_seq)
     }
 }
 
+/// Returns CRED_x of the other party, a CCS containing only the `COSE_Key` of
+/// its public authentication key, or the credential of draft-12.
+///
+/// Whether the key is a signature key follows from the method, and which
+/// curve it belongs to from the suite.
+fn peer_credential<K: DhSecret>(
+    version: Version,
+    suite: &CipherSuite,
+    peer_signs: bool,
+    kid: &[u8],
+    public_key: &[u8],
+) -> Result<Vec<u8>, Error> {
+    let crv = if peer_signs {
+        suite.signature.cose_curve()
+    } else {
+        K::CURVE.cose_curve()
+    };
+
+    match version {
+        // draft-12 credentials only have the x-coordinate of a DH key
+        Version::Draft12 if !peer_signs => {
+            let public = K::decode_point(public_key)?;
+            cose::build_draft12_cred(kid, &K::encode_public(&public))
+        }
+        Version::Draft12 => cose::build_draft12_cred(kid, public_key),
+        Version::Rfc9528 => cose::build_ccs(kid, crv, public_key),
+    }
+}
 
+/// Returns the PRK `MAC_3` is derived from for a party authenticating with
+/// a static DH key, which draft-12 takes to be PRK_3e2m.
+fn mac_3_prk<'a>(
+    version: Version,
+    prk_3e2m: &'a util::Prk,
+    prk_4e3m: &'a util::Prk,
+) -> &'a util::Prk {
+    match version {
+        Version::Draft12 => prk_3e2m,
+        Version::Rfc9528 => prk_4e3m,
+    }
+}
 
-#[cfg(test)]
 
+#[cfg(test)]
 mod tests {
 
 use super::super::test_vectors::*;
 use super::*;
+use super::super::suite::*;
+use ed25519_dalek::SigningKey as Ed25519Key;
+use core::convert::TryFrom;
+use ed448_goldilocks_plus::SigningKey as Ed448Key;
+use x25519_dalek_ng::PublicKey;
 #[test]
 
 fn message1() {
@@ -885,15 +1389,908 @@ fn message1() {
     let msg1_sender = PartyI::new(
         C_I.to_vec(),
         None,
-        I_EPHEMEREAL_SK,
+        StaticSecret::from(I_EPHEMEREAL_SK),
         i_static_sk,
         pub_st_i,
         KID_I.to_vec(),
+        Version::Rfc9528,
     );
-    let (msg1_bytes, _msg2receiver) = msg1_sender.generate_message_1(METHOD_TYPE_I, SUITE_I).unwrap();
+    let suite = CipherSuite::from_id(SUITE_I).unwrap();
+    let (msg1_bytes, _msg2receiver) = msg1_sender.generate_message_1(METHOD_TYPE_I, &[suite]).unwrap();
 
 
     assert_eq!(msg1_bytes,MSG1.to_vec());
 }
 
+#[test]
+fn message1_method_mismatch() {
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+
+    assert_eq!(
+        msg1_sender.generate_message_1(METHOD_TYPE_I, &[SUITE_0]).err(),
+        Some(EarlyError(Error::UnsupportedMethod))
+    );
+}
+
+#[test]
+fn sig_sig_handshake() {
+    let i_signing_key = Ed25519Key::from_bytes(&SIG_I_SK);
+    let r_signing_key = Ed25519Key::from_bytes(&SIG_R_SK);
+    assert_eq!(i_signing_key.verifying_key().as_bytes(), &SIG_I_PK);
+    assert_eq!(r_signing_key.verifying_key().as_bytes(), &SIG_R_PK);
+
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        i_signing_key,
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    let (msg1_bytes, msg2_receiver) = msg1_sender
+        .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+        .unwrap();
+    assert_eq!(msg1_bytes, MSG1_SIG.to_vec());
+
+    let msg1_receiver =
+        PartyR::new_sig(StaticSecret::from(R_EPHEMERAL_SK), r_signing_key, KID_R.to_vec(), Version::Rfc9528);
+    let (msg2_sender, c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+    assert_eq!(c_i, C_I.to_vec());
+
+    let (msg2_bytes, msg3_receiver) =
+        msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+    assert_eq!(msg2_bytes, MSG2_SIG.to_vec());
+
+    let (kid_r, _c_r, msg2_verifier) =
+        msg2_receiver.unpack_message_2_return_kid(msg2_bytes).unwrap();
+    assert_eq!(kid_r, KID_R.to_vec());
+    let msg3_sender = msg2_verifier.verify_message_2(&SIG_R_PK).unwrap();
+    let (msg4_receiver, msg3_bytes) =
+        msg3_sender.generate_message_3(None).unwrap();
+    assert_eq!(msg3_bytes, MSG3_SIG.to_vec());
+
+    let (msg3_verifier, kid_i) =
+        msg3_receiver.unpack_message_3_return_kid(msg3_bytes).unwrap();
+    assert_eq!(kid_i, KID_I.to_vec());
+    let (msg4_sender, r_sck, r_rck, r_rk) =
+        msg3_verifier.verify_message_3(&SIG_I_PK).unwrap();
+    let msg4_bytes = msg4_sender.generate_message_4(None).unwrap();
+    assert_eq!(msg4_bytes, MSG4_SIG.to_vec());
+
+    let (i_sck, i_rck, i_rk) =
+        msg4_receiver.handle_message_4(msg4_bytes).unwrap();
+    assert_eq!((i_sck, i_rck, i_rk), (r_rck, r_sck, r_rk));
+}
+
+#[test]
+fn draft12_message1() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+    let msg1_sender = PartyI::new(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        i_static_sk,
+        i_static_pk,
+        KID_I.to_vec(),
+        Version::Draft12,
+    );
+    let (msg1_bytes, _msg2receiver) =
+        msg1_sender.generate_message_1(METHOD_TYPE_I, &[SUITE_0]).unwrap();
+
+    assert_eq!(msg1_bytes, D12_MSG1.to_vec());
+}
+
+#[test]
+fn draft12_sig_sig_handshake() {
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Draft12,
+    );
+    let (msg1_bytes, msg2_receiver) = msg1_sender
+        .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+        .unwrap();
+    assert_eq!(msg1_bytes, D12_MSG1_SIG.to_vec());
+
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        Ed25519Key::from_bytes(&SIG_R_SK),
+        KID_R.to_vec(),
+        Version::Draft12,
+    );
+    let (msg2_sender, c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+    assert_eq!(c_i, C_I.to_vec());
+
+    let (msg2_bytes, msg3_receiver) =
+        msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+    assert_eq!(msg2_bytes, D12_MSG2_SIG.to_vec());
+
+    // C_R is sent in the clear
+    let (kid_r, c_r, msg2_verifier) =
+        msg2_receiver.unpack_message_2_return_kid(msg2_bytes).unwrap();
+    assert_eq!((kid_r, c_r), (KID_R.to_vec(), C_R.to_vec()));
+    let msg3_sender = msg2_verifier.verify_message_2(&SIG_R_PK).unwrap();
+    let (msg4_receiver, msg3_bytes) =
+        msg3_sender.generate_message_3(None).unwrap();
+    assert_eq!(msg3_bytes, D12_MSG3_SIG.to_vec());
+
+    let (msg3_verifier, kid_i) =
+        msg3_receiver.unpack_message_3_return_kid(msg3_bytes).unwrap();
+    assert_eq!(kid_i, KID_I.to_vec());
+    let (msg4_sender, r_sck, r_rck, r_rk) =
+        msg3_verifier.verify_message_3(&SIG_I_PK).unwrap();
+    let msg4_bytes = msg4_sender.generate_message_4(None).unwrap();
+    assert_eq!(msg4_bytes, D12_MSG4_SIG.to_vec());
+
+    let (i_sck, i_rck, i_rk) =
+        msg4_receiver.handle_message_4(msg4_bytes).unwrap();
+    assert_eq!((i_sck, i_rck, i_rk), (r_rck, r_sck, r_rk));
+}
+
+#[test]
+fn draft12_handshakes() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+    let r_static_sk = StaticSecret::from(R_STATIC_SK);
+    let r_static_pk = PublicKey::from(&r_static_sk);
+
+    let (msg2_bytes, msg3_bytes) = versioned_handshake(
+        Version::Draft12,
+        util::METHOD_SIGN_STAT,
+        SUITE_0,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::Signature(Ed25519Key::from_bytes(&SIG_I_SK).into()),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    assert_eq!(msg2_bytes, D12_MSG2_SIG_STAT.to_vec());
+    assert_eq!(msg3_bytes, D12_MSG3_SIG_STAT.to_vec());
+
+    let (msg2_bytes, msg3_bytes) = versioned_handshake(
+        Version::Draft12,
+        util::METHOD_STAT_SIGN,
+        SUITE_0,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::Signature(Ed25519Key::from_bytes(&SIG_R_SK).into()),
+        ),
+    );
+    assert_eq!(msg2_bytes, D12_MSG2_STAT_SIG.to_vec());
+    assert_eq!(msg3_bytes, D12_MSG3_STAT_SIG.to_vec());
+
+    let i_static_sk = p256::SecretKey::from_slice(&P256_I_STATIC_SK).unwrap();
+    let r_static_sk = p256::SecretKey::from_slice(&P256_R_STATIC_SK).unwrap();
+    let i_static_pk = i_static_sk.public_key();
+    let r_static_pk = r_static_sk.public_key();
+    let (msg2_bytes, msg3_bytes) = versioned_handshake(
+        Version::Draft12,
+        METHOD_TYPE_I,
+        SUITE_2,
+        (
+            p256::SecretKey::from_slice(&P256_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    assert_eq!(msg2_bytes, D12_MSG2_P256_STAT.to_vec());
+    assert_eq!(msg3_bytes, D12_MSG3_P256_STAT.to_vec());
+}
+
+#[test]
+fn responder_versions() {
+    // The same responder keys serve initiators of either version, as long
+    // as R is created for the right one
+    for version in &[Version::Draft12, Version::Rfc9528] {
+        let (msg1_bytes, msg2_receiver) = PartyI::new_sig(
+            C_I.to_vec(),
+            None,
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            Ed25519Key::from_bytes(&SIG_I_SK),
+            KID_I.to_vec(),
+            *version,
+        )
+        .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+        .unwrap();
+
+        let msg1_receiver = PartyR::new_sig(
+            StaticSecret::from(R_EPHEMERAL_SK),
+            Ed25519Key::from_bytes(&SIG_R_SK),
+            KID_R.to_vec(),
+            *version,
+        );
+        let (msg2_sender, _c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+        let (msg2_bytes, _msg3_receiver) =
+            msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+        let (_, _, msg2_verifier) =
+            msg2_receiver.unpack_message_2_return_kid(msg2_bytes).unwrap();
+        assert!(msg2_verifier.verify_message_2(&SIG_R_PK).is_ok());
+    }
+
+    // while an RFC 9528 initiator can't make sense of a draft-12 message_2
+    let (_, msg2_receiver) = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    )
+    .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+    .unwrap();
+    assert!(msg2_receiver
+        .unpack_message_2_return_kid(D12_MSG2_SIG.to_vec())
+        .is_err());
+}
+
+#[test]
+fn sig_sig_wrong_key() {
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    let (_, msg2_receiver) = msg1_sender
+        .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+        .unwrap();
+    let (_, _, msg2_verifier) = msg2_receiver
+        .unpack_message_2_return_kid(MSG2_SIG.to_vec())
+        .unwrap();
+
+    // I's public key is not the one R signed with
+    assert!(msg2_verifier.verify_message_2(&SIG_I_PK).is_err());
+}
+
+/// Runs a full handshake and returns message_2 and message_3, checking that
+/// both parties end up with matching keys.
+fn mixed_handshake(
+    method: u8,
+    i_auth: AuthKey,
+    r_auth: AuthKey,
+) -> (Vec<u8>, Vec<u8>) {
+    handshake(
+        method,
+        SUITE_0,
+        (StaticSecret::from(I_EPHEMEREAL_SK), i_auth),
+        (StaticSecret::from(R_EPHEMERAL_SK), r_auth),
+    )
+}
+
+/// Like `mixed_handshake`, for any suite and group. Each party is given as
+/// its ephemeral secret and authentication key.
+fn handshake<K: DhSecret>(
+    method: u8,
+    suite: CipherSuite,
+    i: (K, AuthKey<K>),
+    r: (K, AuthKey<K>),
+) -> (Vec<u8>, Vec<u8>) {
+    versioned_handshake(Version::Rfc9528, method, suite, i, r)
+}
+
+/// Like `handshake`, in the given version of EDHOC.
+fn versioned_handshake<K: DhSecret>(
+    version: Version,
+    method: u8,
+    suite: CipherSuite,
+    (i_ephemeral, i_auth): (K, AuthKey<K>),
+    (r_ephemeral, r_auth): (K, AuthKey<K>),
+) -> (Vec<u8>, Vec<u8>) {
+    let i_public = i_auth.point_bytes();
+    let r_public = r_auth.point_bytes();
+
+    let msg1_sender = PartyI::new_with_auth(
+        C_I.to_vec(),
+        None,
+        i_ephemeral,
+        i_auth,
+        KID_I.to_vec(),
+        version,
+    );
+    let (msg1_bytes, msg2_receiver) =
+        msg1_sender.generate_message_1(method, &[suite]).unwrap();
+
+    let msg1_receiver =
+        PartyR::new_with_auth(r_ephemeral, r_auth, KID_R.to_vec(), version);
+    let (msg2_sender, _c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+    let (msg2_bytes, msg3_receiver) =
+        msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+
+    let (_kid_r, _c_r, msg2_verifier) = msg2_receiver
+        .unpack_message_2_return_kid(msg2_bytes.clone())
+        .unwrap();
+    let msg3_sender = msg2_verifier.verify_message_2(&r_public).unwrap();
+    let (msg4_receiver, msg3_bytes) =
+        msg3_sender.generate_message_3(None).unwrap();
+
+    let (msg3_verifier, _kid_i) = msg3_receiver
+        .unpack_message_3_return_kid(msg3_bytes.clone())
+        .unwrap();
+    let (msg4_sender, r_sck, r_rck, r_rk) =
+        msg3_verifier.verify_message_3(&i_public).unwrap();
+    let msg4_bytes = msg4_sender.generate_message_4(None).unwrap();
+    let (i_sck, i_rck, i_rk) =
+        msg4_receiver.handle_message_4(msg4_bytes).unwrap();
+    assert_eq!((i_sck, i_rck, i_rk), (r_rck, r_sck, r_rk));
+
+    (msg2_bytes, msg3_bytes)
+}
+
+#[test]
+fn sig_stat_handshake() {
+    let r_static_sk = StaticSecret::from(R_STATIC_SK);
+    let r_static_pk = PublicKey::from(&r_static_sk);
+
+    let (msg2_bytes, msg3_bytes) = mixed_handshake(
+        util::METHOD_SIGN_STAT,
+        AuthKey::Signature(Ed25519Key::from_bytes(&SIG_I_SK).into()),
+        AuthKey::StaticDh(r_static_sk, r_static_pk),
+    );
+
+    // R authenticates with an 8 byte MAC, I with a 64 byte signature
+    assert_eq!(msg2_bytes, MSG2_SIG_STAT.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SIG_STAT.to_vec());
+}
+
+#[test]
+fn stat_sig_handshake() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+
+    let (msg2_bytes, msg3_bytes) = mixed_handshake(
+        util::METHOD_STAT_SIGN,
+        AuthKey::StaticDh(i_static_sk, i_static_pk),
+        AuthKey::Signature(Ed25519Key::from_bytes(&SIG_R_SK).into()),
+    );
+
+    assert_eq!(msg2_bytes, MSG2_STAT_SIG.to_vec());
+    assert_eq!(msg3_bytes, MSG3_STAT_SIG.to_vec());
+}
+
+#[test]
+fn message1_unsupported_method() {
+    let r_static_sk = StaticSecret::from(R_STATIC_SK);
+    let r_static_pk = PublicKey::from(&r_static_sk);
+    let msg1_receiver =
+        PartyR::new(StaticSecret::from(R_EPHEMERAL_SK), r_static_sk, r_static_pk, KID_R.to_vec(), Version::Rfc9528);
+
+    // I wants R to sign, but R only has a static DH key
+    let error = match msg1_receiver.handle_message_1(MSG1_SIG.to_vec()) {
+        Err(OwnError(b)) => b,
+        Ok(_) => panic!("Accepted unsupported method"),
+    };
+    assert_eq!(
+        util::extract_error_message(&error).unwrap(),
+        ErrorMessage::Unspecified("Authentication method unsupported".into())
+    );
+
+    // The initiator aborts as soon as it receives the error message
+    let (_, msg2_receiver) = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    )
+    .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0])
+    .unwrap();
+    assert!(matches!(
+        msg2_receiver.unpack_message_2_return_kid(error),
+        Err(OwnOrPeerError::PeerError(_))
+    ));
+}
+
+#[test]
+fn message1_negotiated_method() {
+    let mut msg1 = MSG1.to_vec();
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        Ed25519Key::from_bytes(&SIG_R_SK),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    // Method 2, where I uses its static DH key and R signs
+    msg1[0] = util::METHOD_STAT_SIGN;
+    let (msg2_sender, _) = msg1_receiver.handle_message_1(msg1.clone()).unwrap();
+    assert_eq!(msg2_sender.method(), util::METHOD_STAT_SIGN);
+
+    // Unknown methods are rejected right away
+    msg1[0] = 4;
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        Ed25519Key::from_bytes(&SIG_R_SK),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    assert!(msg1_receiver.handle_message_1(msg1).is_err());
+}
+
+#[test]
+fn p256_stat_stat_handshake() {
+    let i_static_sk = p256::SecretKey::from_slice(&P256_I_STATIC_SK).unwrap();
+    let r_static_sk = p256::SecretKey::from_slice(&P256_R_STATIC_SK).unwrap();
+    let i_static_pk = i_static_sk.public_key();
+    let r_static_pk = r_static_sk.public_key();
+
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_I,
+        SUITE_2,
+        (
+            p256::SecretKey::from_slice(&P256_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+
+    assert_eq!(msg2_bytes, MSG2_P256_STAT.to_vec());
+    assert_eq!(msg3_bytes, MSG3_P256_STAT.to_vec());
+}
+
+#[test]
+fn rfc9529_trace() {
+    let i_static_sk = p256::SecretKey::from_slice(&P256_I_STATIC_SK).unwrap();
+    let r_static_sk = p256::SecretKey::from_slice(&P256_R_STATIC_SK).unwrap();
+    let i_static_pk = i_static_sk.public_key();
+    let r_static_pk = r_static_sk.public_key();
+    let i_public = p256::SecretKey::encode_point(&i_static_pk);
+    let r_public = p256::SecretKey::encode_point(&r_static_pk);
+
+    // I prefers suite 6, but can't select it with its P-256 keys
+    let msg1_sender = PartyI::new(
+        RFC_C_I.to_vec(),
+        None,
+        p256::SecretKey::from_slice(&P256_I_EPHEMERAL_SK).unwrap(),
+        i_static_sk,
+        i_static_pk,
+        RFC_KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    let (msg1_bytes, msg2_receiver) = msg1_sender
+        .generate_message_1(METHOD_TYPE_I, &[SUITE_6, SUITE_2])
+        .unwrap();
+    assert_eq!(msg1_bytes, RFC_MSG1.to_vec());
+
+    let msg1_receiver = PartyR::new(
+        p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+        r_static_sk,
+        r_static_pk,
+        RFC_KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    let (msg2_sender, c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+    assert_eq!(c_i, RFC_C_I.to_vec());
+    let (msg2_bytes, msg3_receiver) = msg2_sender
+        .generate_message_2_cred(RFC_C_R.to_vec(), None, &RFC_CRED_R)
+        .unwrap();
+    assert_eq!(msg2_bytes, RFC_MSG2.to_vec());
+
+    let (kid_r, c_r, msg2_verifier) =
+        msg2_receiver.unpack_message_2_return_kid(msg2_bytes).unwrap();
+    assert_eq!((kid_r, c_r), (RFC_KID_R.to_vec(), RFC_C_R.to_vec()));
+    let msg3_sender = msg2_verifier
+        .verify_message_2_cred(&r_public, &RFC_CRED_R)
+        .unwrap();
+    let (msg4_receiver, msg3_bytes) = msg3_sender
+        .generate_message_3_cred(None, &RFC_CRED_I)
+        .unwrap();
+    assert_eq!(msg3_bytes, RFC_MSG3.to_vec());
+
+    let (msg3_verifier, kid_i) =
+        msg3_receiver.unpack_message_3_return_kid(msg3_bytes).unwrap();
+    assert_eq!(kid_i, RFC_KID_I.to_vec());
+    let (msg4_sender, r_sck, r_rck, r_rk) = msg3_verifier
+        .verify_message_3_cred(&i_public, &RFC_CRED_I)
+        .unwrap();
+    let (msg4_bytes, r_session) =
+        msg4_sender.generate_message_4_session(None).unwrap();
+    assert_eq!(msg4_bytes, RFC_MSG4.to_vec());
+
+    let (i_session, ead_4) =
+        msg4_receiver.handle_message_4_session(msg4_bytes).unwrap();
+    assert!(ead_4.is_empty());
+    assert_eq!(
+        i_session.link_keys().unwrap(),
+        (r_sck.clone(), r_rck.clone(), r_rk.clone())
+    );
+    assert_eq!(
+        (r_sck, r_rck, r_rk),
+        (RFC_UPLINK.to_vec(), RFC_DOWNLINK.to_vec(), RFC_RK0.to_vec())
+    );
+    assert_eq!(
+        i_session.oscore_secrets().unwrap(),
+        (RFC_OSCORE_SECRET.to_vec(), RFC_OSCORE_SALT.to_vec())
+    );
+    assert_eq!(
+        i_session.export(32768, b"firmware", 16).unwrap(),
+        r_session.export(32768, b"firmware", 16).unwrap()
+    );
+}
+
+#[test]
+fn p256_sig_sig_handshake() {
+    let i_signing_key =
+        p256::ecdsa::SigningKey::from_slice(&P256_I_STATIC_SK).unwrap();
+    let r_signing_key =
+        p256::ecdsa::SigningKey::from_slice(&P256_R_STATIC_SK).unwrap();
+
+    // Suite 3 authenticates message_3 and message_4 with 16 byte tags
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_3,
+        (
+            p256::SecretKey::from_slice(&P256_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(i_signing_key.into()),
+        ),
+        (
+            p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(r_signing_key.into()),
+        ),
+    );
+
+    assert_eq!(msg2_bytes, MSG2_P256_SIG.to_vec());
+    assert_eq!(msg3_bytes, MSG3_P256_SIG.to_vec());
+}
+
+#[test]
+fn message1_unsupported_suite() {
+    // An X25519 initiator can't offer a P-256 suite
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    assert_eq!(
+        msg1_sender.generate_message_1(METHOD_TYPE_SIG, &[SUITE_2]).err(),
+        Some(EarlyError(Error::UnsupportedSuite))
+    );
+
+    // and a P-256 responder rejects suite 0
+    let msg1_receiver = PartyR::new_sig(
+        p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+        p256::ecdsa::SigningKey::from_slice(&P256_R_STATIC_SK).unwrap(),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    assert!(msg1_receiver.handle_message_1(MSG1_SIG.to_vec()).is_err());
+}
+
+#[test]
+fn suite_negotiation() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+    let msg1_sender = PartyI::new(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        i_static_sk,
+        i_static_pk,
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    // With ES256 on X25519, R only supports suite 6
+    let r_signing_key = || {
+        p256::ecdsa::SigningKey::from_slice(&P256_R_STATIC_SK).unwrap()
+    };
+
+    // I selects its most preferred suite 0 first
+    let (msg1_bytes, msg2_receiver) = msg1_sender
+        .generate_message_1(util::METHOD_STAT_SIGN, &[SUITE_0, SUITE_3, SUITE_6])
+        .unwrap();
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        r_signing_key(),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    let err = match msg1_receiver.handle_message_1(msg1_bytes) {
+        Err(OwnError(err)) => err,
+        Ok(_) => panic!("R accepted suite 0"),
+    };
+    assert_eq!(util::extract_suites_r(&err), Some(vec![6]));
+
+    // and retries with suite 6, which it can use with its keys
+    let (msg1_bytes, msg2_receiver) = msg2_receiver
+        .retry(&err, StaticSecret::from([0x42; 32]))
+        .unwrap();
+    let msg_1 = util::deserialize_message_1(Version::Rfc9528, &msg1_bytes).unwrap();
+    assert_eq!(msg_1.suites_i, vec![0, 3, 6]);
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        r_signing_key(),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    let (msg2_sender, _c_i) = msg1_receiver.handle_message_1(msg1_bytes).unwrap();
+    let (msg2_bytes, _msg3_receiver) =
+        msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+    let (_kid_r, _c_r, msg2_verifier) = msg2_receiver
+        .unpack_message_2_return_kid(msg2_bytes)
+        .unwrap();
+    assert!(msg2_verifier
+        .verify_message_2(&SigningKey::from(r_signing_key()).public_bytes())
+        .is_ok());
+}
+
+#[test]
+fn suite_downgrade() {
+    let msg1_sender = PartyI::new_sig(
+        C_I.to_vec(),
+        None,
+        StaticSecret::from(I_EPHEMEREAL_SK),
+        Ed25519Key::from_bytes(&SIG_I_SK),
+        KID_I.to_vec(),
+        Version::Rfc9528,
+    );
+    let (_msg1_bytes, msg2_receiver) = msg1_sender
+        .generate_message_1(METHOD_TYPE_SIG, &[SUITE_0, SUITE_1])
+        .unwrap();
+
+    // An attacker claiming R only supports suite 1 gets I to send
+    // SUITES_I = [0, 1]
+    let forged = util::build_suites_error_message(&[1]).unwrap();
+    let (msg1_bytes, msg2_receiver) = msg2_receiver
+        .retry(&forged, StaticSecret::from(I_EPHEMEREAL_SK))
+        .unwrap();
+
+    // which R rejects, since it prefers suite 0 as well
+    let msg1_receiver = PartyR::new_sig(
+        StaticSecret::from(R_EPHEMERAL_SK),
+        Ed25519Key::from_bytes(&SIG_R_SK),
+        KID_R.to_vec(),
+        Version::Rfc9528,
+    );
+    let err = match msg1_receiver.handle_message_1(msg1_bytes) {
+        Err(OwnError(err)) => err,
+        Ok(_) => panic!("R accepted a downgrade"),
+    };
+    assert_eq!(util::extract_suites_r(&err), Some(vec![0, 1, 4]));
+
+    // Without any suite in common, I gives up
+    let (_msg1_bytes, msg2_receiver) = msg2_receiver
+        .retry(&err, StaticSecret::from(I_EPHEMEREAL_SK))
+        .unwrap();
+    let none = util::build_suites_error_message(&[2, 3]).unwrap();
+    assert_eq!(
+        msg2_receiver
+            .retry(&none, StaticSecret::from(I_EPHEMEREAL_SK))
+            .err(),
+        Some(EarlyError(Error::UnsupportedSuite))
+    );
+}
+
+#[test]
+fn suite1_handshake() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let r_static_sk = StaticSecret::from(R_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+    let r_static_pk = PublicKey::from(&r_static_sk);
+
+    // Same keys as in suite 0, but MAC_2, MAC_3 and the tags are 16 bytes
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_I,
+        SUITE_1,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    assert_eq!(msg2_bytes, MSG2_SUITE1.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SUITE1.to_vec());
+
+    let (_, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_1,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::Signature(Ed25519Key::from_bytes(&SIG_I_SK).into()),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::Signature(Ed25519Key::from_bytes(&SIG_R_SK).into()),
+        ),
+    );
+    assert_eq!(msg3_bytes.len(), MSG3_SIG.len() + 8);
+}
+
+#[test]
+fn chacha_handshakes() {
+    let i_static_sk = StaticSecret::from(I_STATIC_SK);
+    let r_static_sk = StaticSecret::from(R_STATIC_SK);
+    let i_static_pk = PublicKey::from(&i_static_sk);
+    let r_static_pk = PublicKey::from(&r_static_sk);
+
+    // Suite 4 with X25519 static DH keys
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_I,
+        SUITE_4,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    assert_eq!(msg2_bytes, MSG2_SUITE4.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SUITE4.to_vec());
+
+    // Suite 5 with ES256 signature keys
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_5,
+        (
+            p256::SecretKey::from_slice(&P256_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(
+                p256::ecdsa::SigningKey::from_slice(&P256_I_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+        (
+            p256::SecretKey::from_slice(&P256_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(
+                p256::ecdsa::SigningKey::from_slice(&P256_R_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+    );
+    assert_eq!(msg2_bytes, MSG2_SUITE5.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SUITE5.to_vec());
+}
+
+#[test]
+fn gcm_handshakes() {
+    // Suite 6 combines X25519 with ES256
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_6,
+        (
+            StaticSecret::from(I_EPHEMEREAL_SK),
+            AuthKey::Signature(
+                p256::ecdsa::SigningKey::from_slice(&P256_I_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+        (
+            StaticSecret::from(R_EPHEMERAL_SK),
+            AuthKey::Signature(
+                p256::ecdsa::SigningKey::from_slice(&P256_R_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+    );
+    assert_eq!(msg2_bytes, MSG2_SUITE6.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SUITE6.to_vec());
+
+    // Suite 24 uses P-384 and SHA-384, so G_Y takes up 48 bytes
+    let i_static_sk = p384::SecretKey::from_slice(&P384_I_STATIC_SK).unwrap();
+    let r_static_sk = p384::SecretKey::from_slice(&P384_R_STATIC_SK).unwrap();
+    let i_static_pk = i_static_sk.public_key();
+    let r_static_pk = r_static_sk.public_key();
+    let (msg2_bytes, msg3_bytes) = handshake(
+        METHOD_TYPE_I,
+        SUITE_24,
+        (
+            p384::SecretKey::from_slice(&P384_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            p384::SecretKey::from_slice(&P384_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    assert_eq!(msg2_bytes, MSG2_SUITE24.to_vec());
+    assert_eq!(msg3_bytes, MSG3_SUITE24.to_vec());
+}
+
+#[test]
+fn es384_handshake() {
+    let (_, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_24,
+        (
+            p384::SecretKey::from_slice(&P384_I_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(
+                p384::ecdsa::SigningKey::from_slice(&P384_I_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+        (
+            p384::SecretKey::from_slice(&P384_R_EPHEMERAL_SK).unwrap(),
+            AuthKey::Signature(
+                p384::ecdsa::SigningKey::from_slice(&P384_R_STATIC_SK)
+                    .unwrap()
+                    .into(),
+            ),
+        ),
+    );
+    // The bstr header, the kid as an integer, a 96 byte signature and a 16
+    // byte tag
+    assert_eq!(msg3_bytes.len(), 2 + 1 + 2 + 96 + 16);
+}
+
+#[test]
+fn shake256_handshakes() {
+    // Suite 25 with X448 static DH keys, where G_Y takes up 56 bytes
+    let i_static_sk = x448::Secret::from(X448_I_STATIC_SK);
+    let r_static_sk = x448::Secret::from(X448_R_STATIC_SK);
+    let i_static_pk = i_static_sk.public_key();
+    let r_static_pk = r_static_sk.public_key();
+    let (msg2_bytes, _) = handshake(
+        METHOD_TYPE_I,
+        SUITE_25,
+        (
+            x448::Secret::from(X448_I_EPHEMERAL_SK),
+            AuthKey::StaticDh(i_static_sk, i_static_pk),
+        ),
+        (
+            x448::Secret::from(X448_R_EPHEMERAL_SK),
+            AuthKey::StaticDh(r_static_sk, r_static_pk),
+        ),
+    );
+    // The bstr header, G_Y, 4 bytes of C_R and ID_CRED_R, and a 16 byte
+    // MAC_2
+    assert_eq!(msg2_bytes.len(), 2 + 56 + 4 + 16);
+
+    // And with Ed448 signature keys
+    let (_, msg3_bytes) = handshake(
+        METHOD_TYPE_SIG,
+        SUITE_25,
+        (
+            x448::Secret::from(X448_I_EPHEMERAL_SK),
+            AuthKey::Signature(
+                Ed448Key::try_from(&ED448_I_STATIC_SK[..]).unwrap().into(),
+            ),
+        ),
+        (
+            x448::Secret::from(X448_R_EPHEMERAL_SK),
+            AuthKey::Signature(
+                Ed448Key::try_from(&ED448_R_STATIC_SK[..]).unwrap().into(),
+            ),
+        ),
+    );
+    // The bstr header, the kid as an integer, a 114 byte signature and a 16
+    // byte tag
+    assert_eq!(msg3_bytes.len(), 2 + 1 + 2 + 114 + 16);
+}
+
 }
@@ -1,72 +1,144 @@
-
-
 use alloc::vec::Vec;
 use serde_bytes::Bytes;
 
-use super::Result;
+use super::{error::Error, util::Version, Result};
 use crate::cbor;
 
-
-
-
-
-
-
-
-/// An Octet Key Pair (OKP) `COSE_Key`.
-#[derive(Debug, PartialEq)]
-pub struct CoseKey {
-    kty: usize,
-    crv: usize,
-    x: Vec<u8>,
-}
-
-
-
-/// Returns the CBOR encoded `COSE_Key` for the given data.
+/// Returns the CBOR encoded CWT Claims Set (CCS) which only contains the
+/// `COSE_Key` of the given public key in its `cnf` claim.
 ///
-/// This is specific to our use case where we only have Ed25519 public keys,
-/// which are Octet Key Pairs (OKP) in COSE and represented as a single
-/// x-coordinate.
-pub fn serialize_cred_x(x: &[u8], kid : &[u8]) -> Result<Vec<u8>> {
+/// # Arguments
+/// * `kid` - The key ID, which is also part of the `COSE_Key`.
+/// * `crv` - The COSE identifier of the curve of the key.
+/// * `public_key` - The public key of an OKP curve, or the compressed or
+///   uncompressed SEC1 point of an EC2 curve, since its `COSE_Key` needs the
+///   sign or the value of y as well.
+pub fn build_ccs(kid: &[u8], crv: i8, public_key: &[u8]) -> Result<Vec<u8>> {
     // Pack the data into a structure that nicely serializes almost into
     // what we want to have as the actual bytes for the COSE_Key.
-    // (kty key, kty value, crv key, crv value,
-    //  x-coordinate key, x-coordinate value)
+    // (kty key, kty value, kid key, kid value, crv key, crv value,
+    //  x-coordinate key, x-coordinate value[, y key, y value])
+    let mut bytes = match (crv, public_key.split_first()) {
+        // Octet Key Pairs, i.e. X25519, X448, Ed25519 and Ed448
+        (4..=7, _) => cbor::encode((1, 1, 2, Bytes::new(kid), -1, crv, -2, Bytes::new(public_key)))?,
+        // EC2 keys with the compressed point, of which y is only the sign
+        (_, Some((&prefix, x))) if prefix == 0x02 || prefix == 0x03 => cbor::encode((
+            1, 2, 2, Bytes::new(kid), -1, crv,
+            -2, Bytes::new(x),
+            -3, prefix == 0x03,
+        ))?,
+        // EC2 keys with the uncompressed point
+        (_, Some((0x04, point))) if point.len() % 2 == 0 => {
+            let (x, y) = point.split_at(point.len() / 2);
+            cbor::encode((
+                1, 2, 2, Bytes::new(kid), -1, crv,
+                -2, Bytes::new(x),
+                -3, Bytes::new(y),
+            ))?
+        }
+        // An EC2 key without y is not a valid COSE_Key
+        _ => return Err(Error::InvalidKey),
+    };
+    // This is a CBOR array, but we want a map
+    cbor::array_to_map(&mut bytes)?;
 
+    // The COSE_Key goes into the cnf claim of the CCS as {8: {1: COSE_Key}}
+    let mut ccs = vec![0xA1, 0x08, 0xA1, 0x01];
+    ccs.extend(bytes);
 
-    // cred_x is a naked cose key, and thus dressed as a ccs by prefixing (page 15 edhoc9)
-    let mut prefix = vec![0xA1, 0x08, 0xA1, 0x01];
+    Ok(ccs)
+}
 
-    let raw_key = (1,1,2, 2, -1, kid[0], -2, Bytes::new(x));
-    // Get the byte representation of it
-    let mut bytes = cbor::encode(raw_key)?;
+/// Returns the credential draft-12 peers use in place of a CCS, which is
+/// the `COSE_Key` {1: 1, 2: 2, -1: kid, -2: x} with the first byte of the
+/// `kid` as its curve, wrapped in a `cnf` claim all the same.
+pub fn build_draft12_cred(kid: &[u8], public_key: &[u8]) -> Result<Vec<u8>> {
+    let mut bytes =
+        cbor::encode((1, 1, 2, 2, -1, kid.first(), -2, Bytes::new(public_key)))?;
     // This is a CBOR array, but we want a map
     cbor::array_to_map(&mut bytes)?;
-    prefix.extend(bytes);
-    Ok(prefix)
-}
 
+    let mut cred = vec![0xA1, 0x08, 0xA1, 0x01];
+    cred.extend(bytes);
+
+    Ok(cred)
+}
 
 /// Returns the COSE header map for the given `kid`.
-pub fn build_id_cred_x(kid: &[u8]) -> Result<Vec<u8>> {
-    let map = cbor::build_map_single(kid)?;
-    Ok(map)
+///
+/// draft-12 peers send the first byte of the `kid` as an integer.
+pub fn build_id_cred_x(version: Version, kid: &[u8]) -> Result<Vec<u8>> {
+    let mut map = match version {
+        Version::Draft12 => cbor::encode((4, kid.first()))?,
+        Version::Rfc9528 => cbor::encode((4, Bytes::new(kid)))?,
+    };
+    cbor::array_to_map(&mut map)?;
 
+    Ok(map)
 }
 
 /// Returns the `Enc_structure` structure used as associated data in the AEAD.
 pub fn build_ad(th_i: &[u8]) -> Result<Vec<u8>> {
-    // Create array with placeholder
-    let mut ad_arr = cbor::encode(("Encrypt0", Bytes::new(b""), 0))?;
-    // Remove the placeholder
-    ad_arr.pop();
-    // Append the transcript hash, which is already CBOR encoded
-    ad_arr.extend(th_i);
-
-    Ok(ad_arr)
+    Ok(cbor::encode(("Encrypt0", Bytes::new(b""), Bytes::new(th_i)))?)
 }
 
+/// Returns the `Sig_structure` of the `COSE_Sign1` object that is signed in
+/// place of a MAC by a party authenticating with a signature key.
+///
+/// The protected header is `id_cred_x`, the external data is the sequence of
+/// the transcript hash, `cred_x` and the EAD, and the payload is the MAC
+/// computed over the same data.
+pub fn build_to_be_signed(
+    id_cred_x: &[u8],
+    th_i: &[u8],
+    cred_x: &[u8],
+    ead: &[u8],
+    mac: &[u8],
+) -> Result<Vec<u8>> {
+    // The external data is a CBOR sequence
+    let mut external_aad = cbor::encode(Bytes::new(th_i))?;
+    external_aad.extend(cred_x);
+    external_aad.extend(ead);
+
+    let sig_arr = cbor::encode((
+        "Signature1",
+        Bytes::new(id_cred_x),
+        Bytes::new(&external_aad),
+        Bytes::new(mac),
+    ))?;
+
+    Ok(sig_arr)
+}
 
-
-
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ec2_ccs() {
+        let x = [0xAA; 32];
+        let y = [0xBB; 32];
+        let ccs_prefix = [0xA1, 0x08, 0xA1, 0x01, 0xA5, 0x01, 0x02, 0x02, 0x41, 0x2B, 0x20, 0x01, 0x21, 0x58, 0x20];
+
+        // The compressed point has the sign of y
+        let mut compressed = vec![0x03];
+        compressed.extend(&x);
+        let ccs = build_ccs(&[0x2B], 1, &compressed).unwrap();
+        assert_eq!(ccs[..15], ccs_prefix);
+        assert_eq!(ccs[15..47], x);
+        assert_eq!(ccs[47..], [0x22, 0xF5]);
+
+        // The uncompressed point has y itself
+        let mut uncompressed = vec![0x04];
+        uncompressed.extend(&x);
+        uncompressed.extend(&y);
+        let ccs = build_ccs(&[0x2B], 1, &uncompressed).unwrap();
+        assert_eq!(ccs[15..47], x);
+        assert_eq!(ccs[47..50], [0x22, 0x58, 0x20]);
+        assert_eq!(ccs[50..], y);
+
+        // Without y, the COSE_Key would be invalid
+        assert_eq!(build_ccs(&[0x2B], 1, &x), Err(Error::InvalidKey));
+        assert_eq!(build_ccs(&[0x2B], 1, &uncompressed[..64]), Err(Error::InvalidKey));
+    }
+}
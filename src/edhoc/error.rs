@@ -1,6 +1,9 @@
 //! The errors of the `edhoc` module.
 
-use alloc::string::String;
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 use core::fmt;
 
 
@@ -12,6 +15,51 @@ static ERR_HKDF: &str = "Error using HKDF";
 static ERR_AEAD: &str = "Error using AEAD";
 static ERR_SUITE: &str = "Cipher suite unsupported";
 static ERR_BADMAC: &str = "Error processing MAC field";
+static ERR_BADSIG: &str = "Error verifying signature";
+static ERR_METHOD: &str = "Authentication method unsupported";
+static ERR_KEY: &str = "Invalid public key";
+static ERR_LABEL: &str = "Label unsupported";
+
+/// A received EDHOC error message, consisting of ERR_CODE and ERR_INFO.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorMessage {
+    /// ERR_CODE 0, which is not used by EDHOC itself, but can be used by
+    /// protocols built on it. Wraps the CBOR encoded ERR_INFO.
+    Success(Vec<u8>),
+    /// ERR_CODE 1, with a text diagnostic for debugging.
+    Unspecified(String),
+    /// ERR_CODE 2, R doesn't support the suite selected by I. Wraps
+    /// `SUITES_R`, the suites R does support.
+    WrongSelectedSuite(Vec<u8>),
+    /// Any other ERR_CODE, with the CBOR encoded ERR_INFO.
+    Other(isize, Vec<u8>),
+}
+
+impl ErrorMessage {
+    /// Returns the ERR_CODE of this error message.
+    pub fn code(&self) -> isize {
+        match self {
+            ErrorMessage::Success(_) => util::ERR_CODE_SUCCESS,
+            ErrorMessage::Unspecified(_) => util::ERR_CODE_UNSPECIFIED,
+            ErrorMessage::WrongSelectedSuite(_) => util::ERR_CODE_WRONG_SUITE,
+            ErrorMessage::Other(code, _) => *code,
+        }
+    }
+}
+
+impl fmt::Display for ErrorMessage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorMessage::Unspecified(text) => write!(f, "{}", text),
+            ErrorMessage::WrongSelectedSuite(suites_r) => {
+                write!(f, "{}, supported are {:?}", ERR_SUITE, suites_r)
+            }
+            ErrorMessage::Success(info) | ErrorMessage::Other(_, info) => {
+                write!(f, "Error code {}: {:?}", self.code(), info)
+            }
+        }
+    }
+}
 
 /// The error type for operations that process a message from the other party
 /// and may fail if the message is an error message (in which case the protocol
@@ -22,11 +70,11 @@ static ERR_BADMAC: &str = "Error processing MAC field";
 pub enum OwnOrPeerError {
     /// This variant wraps an error message that was received from the other
     /// party. On receiving this error, abort the protocol.
-    PeerError(String),
+    PeerError(ErrorMessage),
     /// This variant wraps an EDHOC error message that was generated because
     /// something went wrong on our end. These bytes need to be sent to the
     /// other party before aborting the protocol.
-    OwnError(alloc::vec::Vec<u8>),
+    OwnError(Vec<u8>),
 }
 
 impl From<Error> for OwnOrPeerError {
@@ -49,6 +97,18 @@ impl From<Error> for OwnOrPeerError {
             Error::BadMac => {
                 OwnOrPeerError::OwnError(util::build_error_message(ERR_BADMAC))
             }
+            Error::BadSignature => {
+                OwnOrPeerError::OwnError(util::build_error_message(ERR_BADSIG))
+            }
+            Error::UnsupportedMethod => {
+                OwnOrPeerError::OwnError(util::build_error_message(ERR_METHOD))
+            }
+            Error::InvalidKey => {
+                OwnOrPeerError::OwnError(util::build_error_message(ERR_KEY))
+            }
+            Error::UnsupportedLabel => {
+                OwnOrPeerError::OwnError(util::build_error_message(ERR_LABEL))
+            }
         }
     }
 }
@@ -72,7 +132,7 @@ impl fmt::Display for OwnOrPeerError {
 /// message, which needs to be sent to the other party prior to aborting the
 /// protocol.
 #[derive(Debug, PartialEq)]
-pub struct OwnError(pub alloc::vec::Vec<u8>);
+pub struct OwnError(pub Vec<u8>);
 
 impl From<Error> for OwnError {
     fn from(e: Error) -> OwnError {
@@ -82,12 +142,26 @@ impl From<Error> for OwnError {
             }
             Error::BadMac => {
                 OwnError(util::build_error_message(ERR_BADMAC))
-            } 
+            }
+            Error::BadSignature => {
+                OwnError(util::build_error_message(ERR_BADSIG))
+            }
+            Error::UnsupportedMethod => {
+                OwnError(util::build_error_message(ERR_METHOD))
+            }
+            Error::InvalidKey => OwnError(util::build_error_message(ERR_KEY)),
             Error::Cbor(_) => OwnError(util::build_error_message(ERR_CBOR)),
 
             Error::Hkdf(_) => OwnError(util::build_error_message(ERR_HKDF)),
             Error::Aead => OwnError(util::build_error_message(ERR_AEAD)),
-            _ => unreachable!(),
+            Error::UnsupportedLabel => {
+                OwnError(util::build_error_message(ERR_LABEL))
+            }
+            // An error message where we expected another message, which
+            // we answer with its diagnostic
+            Error::Edhoc(msg) => {
+                OwnError(util::build_error_message(&msg.to_string()))
+            }
         }
     }
 }
@@ -121,20 +195,26 @@ impl fmt::Display for EarlyError {
 /// various libraries.
 #[derive(Debug, PartialEq)]
 pub enum Error {
-
+    /// The received MAC does not have the expected value.
     BadMac,
+    /// The received signature could not be verified.
+    BadSignature,
     /// Using an unsupported cipher suite.
     UnsupportedSuite,
+    /// The authentication method does not fit the authentication keys.
+    UnsupportedMethod,
+    /// A public key is not a valid point of the suite's group.
+    InvalidKey,
+    /// The label has no counterpart in this version of EDHOC.
+    UnsupportedLabel,
     /// Wraps errors from the `cbor` module.
     Cbor(cbor::CborError),
-    /// Wraps errors from `ed25519_dalek`.
     /// Wraps errors from `hkdf`.
     Hkdf(hkdf::InvalidLength),
     /// Error in `aes_ccm`.
     Aead,
     /// Wraps a received EDHOC error message.
-    Edhoc(String),
-    // wraps the error that the mac does not have the correct value
+    Edhoc(ErrorMessage),
 }
 
 impl From<cbor::CborError> for Error {
@@ -151,6 +231,13 @@ impl From<hkdf::InvalidLength> for Error {
     }
 }
 
+// This is the `signature::Error` shared by Ed25519 and ECDSA
+impl From<ed25519_dalek::SignatureError> for Error {
+    fn from(_: ed25519_dalek::SignatureError) -> Error {
+        Error::BadSignature
+    }
+}
+
 impl From<ccm::aead::Error> for Error {
     fn from(_: ccm::aead::Error) -> Error {
         Error::Aead
@@ -161,7 +248,11 @@ impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Error::UnsupportedSuite => write!(f, "Cipher suite unsupported"),
+            Error::UnsupportedMethod => write!(f, "{}", ERR_METHOD),
             Error::BadMac => write!(f, "Mac tag was wrong"),
+            Error::BadSignature => write!(f, "{}", ERR_BADSIG),
+            Error::InvalidKey => write!(f, "{}", ERR_KEY),
+            Error::UnsupportedLabel => write!(f, "{}", ERR_LABEL),
             Error::Cbor(e) => e.fmt(f),
             Error::Hkdf(e) => e.fmt(f),
             Error::Aead => write!(f, "{}", ERR_AEAD),
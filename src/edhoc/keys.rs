@@ -0,0 +1,403 @@
+//! The key types used for the key exchange and for authentication.
+//!
+//! The protocol states are generic over the private key type of the
+//! Diffie-Hellman group, which is X25519 (`x25519_dalek_ng::StaticSecret`),
+//! NIST P-256 (`p256::SecretKey`), NIST P-384 (`p384::SecretKey`) or X448
+//! (`x448::Secret`), and has to match the curve of the selected cipher suite. Signature keys are
+//! picked at runtime with `SigningKey`, since the signature algorithm is a
+//! property of the negotiated suite.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::convert::TryFrom;
+use ed25519_dalek::Signer;
+use p256::{
+    ecdsa::signature::Verifier,
+    elliptic_curve::sec1::ToEncodedPoint,
+};
+
+use super::{
+    error::Error,
+    suite::{Curve, SignatureAlgorithm},
+    Result,
+};
+
+/// The private key of a Diffie-Hellman group.
+pub trait DhSecret {
+    /// The public key type belonging to this private key.
+    type Public;
+
+    /// Returns the public key belonging to this private key.
+    fn public_key(&self) -> Self::Public;
+
+    /// Returns the shared secret computed with the other party's public key.
+    fn diffie_hellman(&self, public: &Self::Public) -> Vec<u8>;
+
+    /// Returns the encoding of a public key as it is sent to the other party.
+    fn encode_public(public: &Self::Public) -> Vec<u8>;
+
+    /// Decodes a public key received from the other party.
+    fn decode_public(bytes: &[u8]) -> Result<Self::Public>;
+
+    /// Returns the encoding of a public key as it goes into a credential.
+    ///
+    /// The `COSE_Key` of an EC2 curve needs the sign of y, so those encode
+    /// the compressed SEC1 point instead of only the x-coordinate.
+    fn encode_point(public: &Self::Public) -> Vec<u8> {
+        Self::encode_public(public)
+    }
+
+    /// Decodes a public key encoded by `encode_point`.
+    fn decode_point(bytes: &[u8]) -> Result<Self::Public> {
+        Self::decode_public(bytes)
+    }
+
+    /// The curve of this group, which has to match the one of the suite.
+    const CURVE: Curve;
+}
+
+impl DhSecret for x25519_dalek_ng::StaticSecret {
+    type Public = x25519_dalek_ng::PublicKey;
+    const CURVE: Curve = Curve::X25519;
+
+    fn public_key(&self) -> Self::Public {
+        x25519_dalek_ng::PublicKey::from(self)
+    }
+
+    fn diffie_hellman(&self, public: &Self::Public) -> Vec<u8> {
+        self.diffie_hellman(public).as_bytes().to_vec()
+    }
+
+    fn encode_public(public: &Self::Public) -> Vec<u8> {
+        public.as_bytes().to_vec()
+    }
+
+    fn decode_public(bytes: &[u8]) -> Result<Self::Public> {
+        let bytes = <[u8; 32]>::try_from(bytes).map_err(|_| Error::InvalidKey)?;
+        Ok(x25519_dalek_ng::PublicKey::from(bytes))
+    }
+}
+
+impl DhSecret for p256::SecretKey {
+    type Public = p256::PublicKey;
+    const CURVE: Curve = Curve::P256;
+
+    fn public_key(&self) -> Self::Public {
+        self.public_key()
+    }
+
+    fn diffie_hellman(&self, public: &Self::Public) -> Vec<u8> {
+        // The shared secret is the x-coordinate of the resulting point
+        p256::ecdh::diffie_hellman(self.to_nonzero_scalar(), public.as_affine())
+            .raw_secret_bytes()
+            .to_vec()
+    }
+
+    fn encode_public(public: &Self::Public) -> Vec<u8> {
+        // Compact representation, which is only the x-coordinate
+        let point = public.to_encoded_point(false);
+        point.x().map(|x| x.to_vec()).unwrap_or_default()
+    }
+
+    fn decode_public(bytes: &[u8]) -> Result<Self::Public> {
+        if bytes.len() != 32 {
+            return Err(Error::InvalidKey);
+        }
+        // Either of the two points with this x-coordinate results in the
+        // same shared secret, so just pick the one with even y
+        let mut compressed = Vec::with_capacity(33);
+        compressed.push(0x02);
+        compressed.extend(bytes);
+
+        p256::PublicKey::from_sec1_bytes(&compressed).map_err(|_| Error::InvalidKey)
+    }
+
+    fn encode_point(public: &Self::Public) -> Vec<u8> {
+        public.to_encoded_point(true).as_bytes().to_vec()
+    }
+
+    fn decode_point(bytes: &[u8]) -> Result<Self::Public> {
+        if bytes.len() != 33 {
+            return Err(Error::InvalidKey);
+        }
+        p256::PublicKey::from_sec1_bytes(bytes).map_err(|_| Error::InvalidKey)
+    }
+}
+
+impl DhSecret for p384::SecretKey {
+    type Public = p384::PublicKey;
+    const CURVE: Curve = Curve::P384;
+
+    fn public_key(&self) -> Self::Public {
+        self.public_key()
+    }
+
+    fn diffie_hellman(&self, public: &Self::Public) -> Vec<u8> {
+        p384::ecdh::diffie_hellman(self.to_nonzero_scalar(), public.as_affine())
+            .raw_secret_bytes()
+            .to_vec()
+    }
+
+    fn encode_public(public: &Self::Public) -> Vec<u8> {
+        let point = public.to_encoded_point(false);
+        point.x().map(|x| x.to_vec()).unwrap_or_default()
+    }
+
+    fn decode_public(bytes: &[u8]) -> Result<Self::Public> {
+        if bytes.len() != 48 {
+            return Err(Error::InvalidKey);
+        }
+        // Same as for P-256, the sign of y doesn't matter
+        let mut compressed = Vec::with_capacity(49);
+        compressed.push(0x02);
+        compressed.extend(bytes);
+
+        p384::PublicKey::from_sec1_bytes(&compressed).map_err(|_| Error::InvalidKey)
+    }
+
+    fn encode_point(public: &Self::Public) -> Vec<u8> {
+        public.to_encoded_point(true).as_bytes().to_vec()
+    }
+
+    fn decode_point(bytes: &[u8]) -> Result<Self::Public> {
+        if bytes.len() != 49 {
+            return Err(Error::InvalidKey);
+        }
+        p384::PublicKey::from_sec1_bytes(bytes).map_err(|_| Error::InvalidKey)
+    }
+}
+
+impl DhSecret for x448::Secret {
+    type Public = x448::PublicKey;
+    const CURVE: Curve = Curve::X448;
+
+    fn public_key(&self) -> Self::Public {
+        x448::PublicKey::from(self)
+    }
+
+    fn diffie_hellman(&self, public: &Self::Public) -> Vec<u8> {
+        // Low order points are rejected on decoding, so this can't fail
+        self.as_diffie_hellman(public)
+            .map(|shared| shared.as_bytes().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn encode_public(public: &Self::Public) -> Vec<u8> {
+        public.as_bytes().to_vec()
+    }
+
+    fn decode_public(bytes: &[u8]) -> Result<Self::Public> {
+        x448::PublicKey::from_bytes(bytes).ok_or(Error::InvalidKey)
+    }
+}
+
+/// A private key to authenticate with by signing.
+pub enum SigningKey {
+    /// EdDSA with Ed25519.
+    Ed25519(ed25519_dalek::SigningKey),
+    /// ECDSA with P-256 and SHA-256 (ES256).
+    Es256(p256::ecdsa::SigningKey),
+    /// ECDSA with P-384 and SHA-384 (ES384).
+    Es384(p384::ecdsa::SigningKey),
+    /// EdDSA with Ed448, boxed since it's much larger than the others.
+    Ed448(Box<ed448_goldilocks_plus::SigningKey>),
+}
+
+impl SigningKey {
+    /// Returns the encoding of the public key, which is the 32 or 57 bytes
+    /// of an EdDSA key or the compressed SEC1 point of an ECDSA key.
+    pub fn public_bytes(&self) -> Vec<u8> {
+        match self {
+            SigningKey::Ed25519(key) => key.verifying_key().as_bytes().to_vec(),
+            SigningKey::Es256(key) => key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes()
+                .to_vec(),
+            SigningKey::Es384(key) => key
+                .verifying_key()
+                .to_encoded_point(true)
+                .as_bytes()
+                .to_vec(),
+            SigningKey::Ed448(key) => key.verifying_key().to_bytes().to_vec(),
+        }
+    }
+
+    /// Returns the signature algorithm of this key.
+    pub fn algorithm(&self) -> SignatureAlgorithm {
+        match self {
+            SigningKey::Ed25519(_) => SignatureAlgorithm::EdDsa,
+            SigningKey::Es256(_) => SignatureAlgorithm::Es256,
+            SigningKey::Es384(_) => SignatureAlgorithm::Es384,
+            SigningKey::Ed448(_) => SignatureAlgorithm::Ed448,
+        }
+    }
+
+    /// Returns the signature of `msg`.
+    pub fn sign(&self, msg: &[u8]) -> Vec<u8> {
+        match self {
+            SigningKey::Ed25519(key) => key.sign(msg).to_bytes().to_vec(),
+            SigningKey::Es256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(msg);
+                signature.to_bytes().to_vec()
+            }
+            SigningKey::Es384(key) => {
+                let signature: p384::ecdsa::Signature = key.sign(msg);
+                signature.to_bytes().to_vec()
+            }
+            SigningKey::Ed448(key) => key.sign_raw(msg).to_bytes().to_vec(),
+        }
+    }
+}
+
+impl From<ed25519_dalek::SigningKey> for SigningKey {
+    fn from(key: ed25519_dalek::SigningKey) -> SigningKey {
+        SigningKey::Ed25519(key)
+    }
+}
+
+impl From<p256::ecdsa::SigningKey> for SigningKey {
+    fn from(key: p256::ecdsa::SigningKey) -> SigningKey {
+        SigningKey::Es256(key)
+    }
+}
+
+impl From<p384::ecdsa::SigningKey> for SigningKey {
+    fn from(key: p384::ecdsa::SigningKey) -> SigningKey {
+        SigningKey::Es384(key)
+    }
+}
+
+impl From<ed448_goldilocks_plus::SigningKey> for SigningKey {
+    fn from(key: ed448_goldilocks_plus::SigningKey) -> SigningKey {
+        SigningKey::Ed448(Box::new(key))
+    }
+}
+
+/// Verifies a signature made with the given signature algorithm.
+///
+/// # Arguments
+/// * `public_key` - The encoded public key of the signing party.
+pub fn verify(
+    algorithm: SignatureAlgorithm,
+    public_key: &[u8],
+    msg: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    match algorithm {
+        SignatureAlgorithm::EdDsa => {
+            let public_key = ed25519_dalek::VerifyingKey::try_from(public_key)?;
+            let signature = ed25519_dalek::Signature::from_slice(signature)?;
+            public_key.verify_strict(msg, &signature)?;
+        }
+        SignatureAlgorithm::Es256 => {
+            let public_key =
+                p256::ecdsa::VerifyingKey::from_sec1_bytes(public_key)?;
+            let signature = p256::ecdsa::Signature::from_slice(signature)?;
+            public_key.verify(msg, &signature)?;
+        }
+        SignatureAlgorithm::Es384 => {
+            let public_key =
+                p384::ecdsa::VerifyingKey::from_sec1_bytes(public_key)?;
+            let signature = p384::ecdsa::Signature::from_slice(signature)?;
+            public_key.verify(msg, &signature)?;
+        }
+        SignatureAlgorithm::Ed448 => {
+            let public_key = <[u8; 57]>::try_from(public_key)
+                .ok()
+                .and_then(|bytes| {
+                    ed448_goldilocks_plus::VerifyingKey::from_bytes(&bytes).ok()
+                })
+                .ok_or(Error::InvalidKey)?;
+            let signature = <[u8; 114]>::try_from(signature)
+                .ok()
+                .and_then(|bytes| {
+                    ed448_goldilocks_plus::Signature::from_bytes(&bytes).ok()
+                })
+                .ok_or(Error::BadSignature)?;
+            public_key
+                .verify_raw(&signature, msg)
+                .map_err(|_| Error::BadSignature)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_vectors::*;
+    use super::*;
+
+    #[test]
+    fn p256_compact_agreement() {
+        let a = p256::SecretKey::from_slice(&[0x11; 32]).unwrap();
+        let b = p256::SecretKey::from_slice(&[0x22; 32]).unwrap();
+
+        // Decoding the x-coordinate may pick the other point, which must
+        // not change the shared secret
+        let a_public = p256::SecretKey::decode_public(
+            &p256::SecretKey::encode_public(&a.public_key()),
+        )
+        .unwrap();
+        let b_public = p256::SecretKey::decode_public(
+            &p256::SecretKey::encode_public(&b.public_key()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            DhSecret::diffie_hellman(&a, &b_public),
+            DhSecret::diffie_hellman(&b, &a_public)
+        );
+        assert!(p256::SecretKey::decode_public(&[0; 31]).is_err());
+    }
+
+    #[test]
+    fn es256_roundtrip() {
+        let key = SigningKey::from(
+            p256::ecdsa::SigningKey::from_slice(&[0x33; 32]).unwrap(),
+        );
+        let signature = key.sign(b"message");
+
+        let es256 = SignatureAlgorithm::Es256;
+        assert_eq!(key.algorithm(), es256);
+        assert!(verify(es256, &key.public_bytes(), b"message", &signature).is_ok());
+        assert!(verify(es256, &key.public_bytes(), b"massage", &signature).is_err());
+        // The key is not usable with EdDSA
+        let eddsa = SignatureAlgorithm::EdDsa;
+        assert!(verify(eddsa, &key.public_bytes(), b"message", &signature).is_err());
+    }
+
+    #[test]
+    fn x448_rfc7748() {
+        let alice = x448::Secret::from(X448_I_EPHEMERAL_SK);
+        let bob = x448::Secret::from(X448_R_EPHEMERAL_SK);
+        let bob_public = x448::Secret::decode_public(
+            &x448::Secret::encode_public(&bob.public_key()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            DhSecret::diffie_hellman(&alice, &bob_public),
+            X448_SHARED_SECRET.to_vec()
+        );
+        // The all-zero point has low order
+        assert!(x448::Secret::decode_public(&[0; 56]).is_err());
+    }
+
+    #[test]
+    fn ed448_rfc8032() {
+        let key = SigningKey::from(
+            ed448_goldilocks_plus::SigningKey::try_from(&ED448_I_STATIC_SK[..])
+                .unwrap(),
+        );
+        assert_eq!(key.public_bytes(), ED448_I_STATIC_PK.to_vec());
+        assert_eq!(key.sign(b""), ED448_EMPTY_SIGNATURE.to_vec());
+
+        let ed448 = SignatureAlgorithm::Ed448;
+        assert!(verify(ed448, &ED448_I_STATIC_PK, b"", &ED448_EMPTY_SIGNATURE).is_ok());
+        assert!(verify(ed448, &ED448_I_STATIC_PK, b"x", &ED448_EMPTY_SIGNATURE).is_err());
+        let mut signature = ED448_EMPTY_SIGNATURE;
+        signature[60] ^= 1;
+        assert!(verify(ed448, &ED448_I_STATIC_PK, b"", &signature).is_err());
+    }
+}
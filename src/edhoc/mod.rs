@@ -7,6 +7,15 @@
 //! are consumed by an action and return the next state struct together with
 //! optional data.
 //!
+//! All four authentication methods and the cipher suites of RFC 9528 are
+//! supported. Keys are identified by `kid`, and unless a credential is
+//! passed explicitly, CRED_I and CRED_R are CWT Claims Sets containing only
+//! the `COSE_Key`. Both parties take a `Version` when created, so that a
+//! responder can speak draft-ietf-lake-edhoc-12 with some initiators and
+//! RFC 9528 with others. A completed handshake gives both parties a
+//! `Session`, whose `export` and `key_update` are EDHOC_Exporter and
+//! EDHOC_KeyUpdate.
+//!
 //! Party U starts by initializing a `PartyI` and using it to generate the
 //! first message and its next state. Party V does the opposite,
 //! initializing a `PartyR` and using this to handle the message and get
@@ -67,10 +76,13 @@
 //! };
 //! ```
 
-mod cose;
+pub(crate) mod cose;
 #[cfg(test)]
-mod test_vectors;
+pub(crate) mod test_vectors;
 pub mod util;
+pub mod keys;
+pub mod suite;
+pub mod session;
 
 pub mod api;
 #[cfg_attr(tarpaulin, skip)]
@@ -80,3 +92,7 @@ pub mod error;
 type Result<T> = core::result::Result<T, error::Error>;
 
 pub use api::{PartyI,PartyR};
+pub use keys::{DhSecret, SigningKey};
+pub use session::Session;
+pub use suite::CipherSuite;
+pub use util::Version;
@@ -0,0 +1,287 @@
+//! The completed EDHOC session, from which applications export their keys.
+
+use alloc::vec::Vec;
+
+use super::{
+    error::Error,
+    suite::CipherSuite,
+    util::{self, Prk, Version},
+    Result,
+};
+
+/// An EDHOC session both parties completed, which holds `PRK_out` and
+/// `PRK_exporter`.
+///
+/// Applications derive their own keys with `export`, next to the OSCORE
+/// master secret and salt and the link keys this crate derives itself.
+#[derive(Clone)]
+pub struct Session {
+    version: Version,
+    suite: CipherSuite,
+    prk_out: Prk,
+    prk_exporter: Prk,
+    /// draft-12 exports from PRK_4x3m with TH_4 as context, and has no
+    /// PRK_out or PRK_exporter.
+    th_4: Vec<u8>,
+}
+
+impl Session {
+    /// Derives `PRK_out` and `PRK_exporter` from PRK_4e3m and TH_4.
+    pub fn new(
+        version: Version,
+        suite: CipherSuite,
+        prk_4e3m: &Prk,
+        th_4: &[u8],
+    ) -> Result<Session> {
+        if version == Version::Draft12 {
+            return Ok(Session {
+                version,
+                suite,
+                prk_out: prk_4e3m.clone(),
+                prk_exporter: prk_4e3m.clone(),
+                th_4: th_4.to_vec(),
+            });
+        }
+
+        let prk_out = util::edhoc_kdf(
+            prk_4e3m,
+            util::PRK_OUT,
+            th_4,
+            prk_4e3m.hash_length(),
+        )?;
+        let prk_out = Prk::from_bytes(suite.hash, &prk_out)?;
+        let prk_exporter = exporter_prk(&suite, &prk_out)?;
+
+        Ok(Session {
+            version,
+            suite,
+            prk_out,
+            prk_exporter,
+            th_4: th_4.to_vec(),
+        })
+    }
+
+    /// Returns the version of EDHOC the session was established with.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Returns the cipher suite the session was established with.
+    pub fn suite(&self) -> CipherSuite {
+        self.suite
+    }
+
+    /// Returns `PRK_out`, or PRK_4x3m for draft-12, which an application
+    /// can store in place of the session.
+    pub fn prk_out(&self) -> &[u8] {
+        self.prk_out.as_bytes()
+    }
+
+    /// The `EDHOC_KeyUpdate` of RFC 9528 Appendix H, which replaces
+    /// `PRK_out` with one derived from it and `context`, and derives a new
+    /// `PRK_exporter` from that.
+    ///
+    /// Everything exported afterwards, including the link keys, is fresh.
+    /// Both parties have to update with the same `context`, typically a
+    /// nonce they exchanged, to stay in sync. draft-12 instead extracts a
+    /// new PRK_4x3m with `context` as the salt.
+    pub fn key_update(&mut self, context: &[u8]) -> Result<()> {
+        if self.version == Version::Draft12 {
+            let (_, prk_4x3m) = util::extract_prk(
+                self.suite.hash,
+                Some(context),
+                self.prk_out.as_bytes(),
+            )?;
+            self.prk_exporter = prk_4x3m.clone();
+            self.prk_out = prk_4x3m;
+
+            return Ok(());
+        }
+
+        let prk_out = util::edhoc_kdf(
+            &self.prk_out,
+            util::KEY_UPDATE,
+            context,
+            self.prk_out.hash_length(),
+        )?;
+        self.prk_out = Prk::from_bytes(self.suite.hash, &prk_out)?;
+        self.prk_exporter = exporter_prk(&self.suite, &self.prk_out)?;
+
+        Ok(())
+    }
+
+    /// The `EDHOC_Exporter`, which derives `length` bytes of keying material
+    /// for `label` and `context` from `PRK_exporter`.
+    ///
+    /// draft-12 used text labels, so only the OSCORE master secret and salt
+    /// can be exported from its sessions.
+    pub fn export(
+        &self,
+        label: u16,
+        context: &[u8],
+        length: usize,
+    ) -> Result<Vec<u8>> {
+        match self.version {
+            Version::Draft12 => util::edhoc_kdf_draft12(
+                &self.prk_exporter,
+                &self.th_4,
+                draft12_exporter_label(label)?,
+                context,
+                length,
+            ),
+            Version::Rfc9528 => util::edhoc_exporter(
+                &self.prk_exporter,
+                label,
+                context,
+                length,
+            ),
+        }
+    }
+
+    /// Returns the OSCORE master secret and master salt.
+    pub fn oscore_secrets(&self) -> Result<(Vec<u8>, Vec<u8>)> {
+        let master_secret = self.export(
+            util::OSCORE_MASTER_SECRET,
+            b"",
+            self.suite.app_aead.key_length(),
+        )?;
+        let master_salt = self.export(
+            util::OSCORE_MASTER_SALT,
+            b"",
+            util::SALT_LENGTH / 8, //going from bits to bytes
+        )?;
+
+        Ok((master_secret, master_salt))
+    }
+
+    /// Returns the uplink key, the downlink key and the root key RK0 that
+    /// are derived from the OSCORE master secret and salt.
+    pub fn link_keys(&self) -> Result<(Vec<u8>, Vec<u8>, Vec<u8>)> {
+        let (master_secret, master_salt) = self.oscore_secrets()?;
+
+        let uplink =
+            util::extract_expand(&master_secret, &master_salt, "UPLINK", 32)?;
+        let downlink =
+            util::extract_expand(&master_secret, &master_salt, "DOWNLINK", 32)?;
+        let rk =
+            util::extract_expand(&master_secret, &master_salt, "RK0", 32)?;
+
+        Ok((uplink, downlink, rk))
+    }
+}
+
+/// Derives `PRK_exporter` from `PRK_out`.
+fn exporter_prk(suite: &CipherSuite, prk_out: &Prk) -> Result<Prk> {
+    let prk_exporter = util::edhoc_kdf(
+        prk_out,
+        util::PRK_EXPORTER,
+        b"",
+        prk_out.hash_length(),
+    )?;
+
+    Prk::from_bytes(suite.hash, &prk_exporter)
+}
+
+/// Returns the text label draft-12 used for an exporter label.
+fn draft12_exporter_label(label: u16) -> Result<&'static str> {
+    match label {
+        util::OSCORE_MASTER_SECRET => Ok("OSCORE_Master_Secret"),
+        util::OSCORE_MASTER_SALT => Ok("OSCORE_Master_Salt"),
+        _ => Err(Error::UnsupportedLabel),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{suite::*, test_vectors::*};
+    use super::*;
+
+    #[test]
+    fn rfc9529_export() {
+        let prk_4e3m = Prk::from_bytes(SUITE_2.hash, &RFC_PRK_4E3M).unwrap();
+        let session =
+            Session::new(Version::Rfc9528, SUITE_2, &prk_4e3m, &RFC_TH_4)
+                .unwrap();
+        assert_eq!(session.prk_out(), &RFC_PRK_OUT);
+        assert_eq!(session.prk_exporter.as_bytes(), &RFC_PRK_EXPORTER);
+
+        let (master_secret, master_salt) = session.oscore_secrets().unwrap();
+        assert_eq!(master_secret, RFC_OSCORE_SECRET);
+        assert_eq!(master_salt, RFC_OSCORE_SALT);
+
+        // Application labels are just other exporter labels
+        let firmware_key = session.export(32768, b"firmware", 16).unwrap();
+        assert_eq!(firmware_key.len(), 16);
+        assert_ne!(firmware_key, session.export(32768, b"", 16).unwrap());
+    }
+
+    #[test]
+    fn key_update() {
+        let prk_4e3m = Prk::from_bytes(SUITE_2.hash, &RFC_PRK_4E3M).unwrap();
+        let mut i_session =
+            Session::new(Version::Rfc9528, SUITE_2, &prk_4e3m, &RFC_TH_4)
+                .unwrap();
+        let mut r_session = i_session.clone();
+        let link_keys = i_session.link_keys().unwrap();
+
+        i_session.key_update(&[0xA0, 0x1B]).unwrap();
+        let prk_out =
+            util::edhoc_kdf(&prk_4e3m, util::PRK_OUT, &RFC_TH_4, 32).unwrap();
+        let prk_out = Prk::from_bytes(SUITE_2.hash, &prk_out).unwrap();
+        assert_eq!(
+            i_session.prk_out(),
+            &util::edhoc_kdf(&prk_out, util::KEY_UPDATE, &[0xA0, 0x1B], 32)
+                .unwrap()[..]
+        );
+        assert_ne!(i_session.link_keys().unwrap(), link_keys);
+        assert_ne!(i_session.oscore_secrets().unwrap().0, RFC_OSCORE_SECRET);
+
+        // The peer is out of sync until it updates with the same context
+        assert_ne!(
+            i_session.export(32768, b"", 16).unwrap(),
+            r_session.export(32768, b"", 16).unwrap()
+        );
+        r_session.key_update(&[0xA0, 0x1B]).unwrap();
+        assert_eq!(r_session.prk_out(), i_session.prk_out());
+        assert_eq!(r_session.link_keys().unwrap(), i_session.link_keys().unwrap());
+
+        // Updating twice with the same context still gives fresh keys
+        r_session.key_update(&[0xA0, 0x1B]).unwrap();
+        assert_ne!(r_session.prk_out(), i_session.prk_out());
+    }
+
+    #[test]
+    fn draft12_key_update() {
+        let prk_4x3m = Prk::from_bytes(SUITE_0.hash, &D12_PRK4XM).unwrap();
+        let mut session = Session::new(
+            Version::Draft12,
+            SUITE_0,
+            &prk_4x3m,
+            &D12_TH_4_CBOR[2..],
+        )
+        .unwrap();
+
+        session.key_update(&[0xA0, 0x1B]).unwrap();
+        let (_, updated) =
+            util::extract_prk(SUITE_0.hash, Some(&[0xA0, 0x1B]), &D12_PRK4XM)
+                .unwrap();
+        assert_eq!(session.prk_out(), updated.as_bytes());
+        assert_ne!(session.oscore_secrets().unwrap().0, D12_MASTER_SECRET);
+    }
+
+    #[test]
+    fn draft12_export() {
+        let prk_4x3m = Prk::from_bytes(SUITE_0.hash, &D12_PRK4XM).unwrap();
+        let session = Session::new(
+            Version::Draft12,
+            SUITE_0,
+            &prk_4x3m,
+            &D12_TH_4_CBOR[2..],
+        )
+        .unwrap();
+
+        let (master_secret, _) = session.oscore_secrets().unwrap();
+        assert_eq!(master_secret, D12_MASTER_SECRET);
+        assert_eq!(session.export(32768, b"", 16), Err(Error::UnsupportedLabel));
+    }
+}
@@ -0,0 +1,302 @@
+//! The cipher suites of EDHOC.
+//!
+//! A `CipherSuite` bundles the algorithms and lengths the protocol uses once
+//! a suite has been selected. The suites are looked up by their identifier
+//! from the registry in RFC 9528, all of which are supported.
+
+use super::{error::Error, Result};
+
+/// An AEAD algorithm, used by EDHOC itself or by the application.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aead {
+    /// AES-CCM-16-64-128 (COSE algorithm 10).
+    AesCcm16_64_128,
+    /// AES-CCM-16-128-128 (COSE algorithm 30).
+    AesCcm16_128_128,
+    /// AES-GCM with a 128 bit key (COSE algorithm 1).
+    A128Gcm,
+    /// AES-GCM with a 256 bit key (COSE algorithm 3).
+    A256Gcm,
+    /// ChaCha20/Poly1305 (COSE algorithm 24).
+    ChaCha20Poly1305,
+}
+
+impl Aead {
+    /// Returns the key length in bytes.
+    pub fn key_length(self) -> usize {
+        match self {
+            Aead::AesCcm16_64_128 | Aead::AesCcm16_128_128 | Aead::A128Gcm => 16,
+            Aead::A256Gcm | Aead::ChaCha20Poly1305 => 32,
+        }
+    }
+
+    /// Returns the nonce length in bytes.
+    pub fn nonce_length(self) -> usize {
+        match self {
+            Aead::AesCcm16_64_128 | Aead::AesCcm16_128_128 => 13,
+            Aead::A128Gcm | Aead::A256Gcm | Aead::ChaCha20Poly1305 => 12,
+        }
+    }
+
+    /// Returns the tag length in bytes.
+    pub fn tag_length(self) -> usize {
+        match self {
+            Aead::AesCcm16_64_128 => 8,
+            _ => 16,
+        }
+    }
+
+    /// Returns the COSE identifier of the algorithm.
+    pub fn cose_algorithm(self) -> i8 {
+        match self {
+            Aead::AesCcm16_64_128 => 10,
+            Aead::AesCcm16_128_128 => 30,
+            Aead::A128Gcm => 1,
+            Aead::A256Gcm => 3,
+            Aead::ChaCha20Poly1305 => 24,
+        }
+    }
+}
+
+/// A hash function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hash {
+    /// SHA-256 (COSE algorithm -16).
+    Sha256,
+    /// SHA-384 (COSE algorithm -43).
+    Sha384,
+    /// SHAKE256 with 512 bits of output (COSE algorithm -45), which EDHOC
+    /// pairs with KMAC256 instead of HKDF.
+    Shake256,
+}
+
+impl Hash {
+    /// Returns the output length in bytes.
+    pub fn output_length(self) -> usize {
+        match self {
+            Hash::Sha256 => 32,
+            Hash::Sha384 => 48,
+            Hash::Shake256 => 64,
+        }
+    }
+}
+
+/// The curve of the ephemeral (and static) Diffie-Hellman keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// X25519 (COSE curve 4).
+    X25519,
+    /// NIST P-256 (COSE curve 1).
+    P256,
+    /// NIST P-384 (COSE curve 2).
+    P384,
+    /// X448 (COSE curve 5).
+    X448,
+}
+
+impl Curve {
+    /// Returns the length in bytes of an encoded public key, which is only
+    /// the x-coordinate for the NIST curves.
+    pub fn public_key_length(self) -> usize {
+        match self {
+            Curve::X25519 | Curve::P256 => 32,
+            Curve::P384 => 48,
+            Curve::X448 => 56,
+        }
+    }
+
+    /// Returns the COSE identifier of the curve.
+    pub fn cose_curve(self) -> i8 {
+        match self {
+            Curve::X25519 => 4,
+            Curve::P256 => 1,
+            Curve::P384 => 2,
+            Curve::X448 => 5,
+        }
+    }
+}
+
+/// A signature algorithm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    /// EdDSA with Ed25519 (COSE algorithm -8).
+    EdDsa,
+    /// ECDSA with P-256 and SHA-256 (COSE algorithm -7).
+    Es256,
+    /// ECDSA with P-384 and SHA-384 (COSE algorithm -35).
+    Es384,
+    /// EdDSA with Ed448 (COSE algorithm -8).
+    Ed448,
+}
+
+impl SignatureAlgorithm {
+    /// Returns the COSE identifier of the curve of the signature keys.
+    pub fn cose_curve(self) -> i8 {
+        match self {
+            SignatureAlgorithm::EdDsa => 6,
+            SignatureAlgorithm::Es256 => 1,
+            SignatureAlgorithm::Es384 => 2,
+            SignatureAlgorithm::Ed448 => 7,
+        }
+    }
+
+    /// Returns the length in bytes of a signature.
+    pub fn signature_length(self) -> usize {
+        match self {
+            SignatureAlgorithm::EdDsa | SignatureAlgorithm::Es256 => 64,
+            SignatureAlgorithm::Es384 => 96,
+            SignatureAlgorithm::Ed448 => 114,
+        }
+    }
+}
+
+/// A cipher suite, consisting of the algorithms EDHOC uses and those the
+/// application is expected to use with the derived keys.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CipherSuite {
+    /// The identifier sent in `SUITES_I`.
+    pub id: u8,
+    /// The AEAD protecting `message_3` and `message_4`.
+    pub aead: Aead,
+    /// The hash function of the transcript hashes and the key derivation.
+    pub hash: Hash,
+    /// The length in bytes of `MAC_2` and `MAC_3` with static DH keys.
+    pub mac_length: usize,
+    /// The curve of the Diffie-Hellman keys.
+    pub curve: Curve,
+    /// The signature algorithm of the signature methods.
+    pub signature: SignatureAlgorithm,
+    /// The AEAD of the application, e.g. OSCORE.
+    pub app_aead: Aead,
+    /// The hash function of the application.
+    pub app_hash: Hash,
+}
+
+/// AES-CCM-16-64-128, SHA-256, 8, X25519, EdDSA.
+pub const SUITE_0: CipherSuite = CipherSuite {
+    id: 0,
+    aead: Aead::AesCcm16_64_128,
+    hash: Hash::Sha256,
+    mac_length: 8,
+    curve: Curve::X25519,
+    signature: SignatureAlgorithm::EdDsa,
+    app_aead: Aead::AesCcm16_64_128,
+    app_hash: Hash::Sha256,
+};
+
+/// AES-CCM-16-128-128, SHA-256, 16, X25519, EdDSA.
+pub const SUITE_1: CipherSuite = CipherSuite {
+    id: 1,
+    aead: Aead::AesCcm16_128_128,
+    mac_length: 16,
+    ..SUITE_0
+};
+
+/// AES-CCM-16-64-128, SHA-256, 8, P-256, ES256.
+pub const SUITE_2: CipherSuite = CipherSuite {
+    id: 2,
+    curve: Curve::P256,
+    signature: SignatureAlgorithm::Es256,
+    ..SUITE_0
+};
+
+/// AES-CCM-16-128-128, SHA-256, 16, P-256, ES256.
+pub const SUITE_3: CipherSuite = CipherSuite {
+    id: 3,
+    aead: Aead::AesCcm16_128_128,
+    mac_length: 16,
+    ..SUITE_2
+};
+
+/// ChaCha20/Poly1305, SHA-256, 16, X25519, EdDSA.
+pub const SUITE_4: CipherSuite = CipherSuite {
+    id: 4,
+    aead: Aead::ChaCha20Poly1305,
+    mac_length: 16,
+    app_aead: Aead::ChaCha20Poly1305,
+    ..SUITE_0
+};
+
+/// ChaCha20/Poly1305, SHA-256, 16, P-256, ES256.
+pub const SUITE_5: CipherSuite = CipherSuite {
+    id: 5,
+    curve: Curve::P256,
+    signature: SignatureAlgorithm::Es256,
+    ..SUITE_4
+};
+
+/// A128GCM, SHA-256, 16, X25519, ES256.
+pub const SUITE_6: CipherSuite = CipherSuite {
+    id: 6,
+    aead: Aead::A128Gcm,
+    hash: Hash::Sha256,
+    mac_length: 16,
+    curve: Curve::X25519,
+    signature: SignatureAlgorithm::Es256,
+    app_aead: Aead::A128Gcm,
+    app_hash: Hash::Sha256,
+};
+
+/// A256GCM, SHA-384, 16, P-384, ES384.
+pub const SUITE_24: CipherSuite = CipherSuite {
+    id: 24,
+    aead: Aead::A256Gcm,
+    hash: Hash::Sha384,
+    mac_length: 16,
+    curve: Curve::P384,
+    signature: SignatureAlgorithm::Es384,
+    app_aead: Aead::A256Gcm,
+    app_hash: Hash::Sha384,
+};
+
+/// ChaCha20/Poly1305, SHAKE256, 16, X448, EdDSA (Ed448).
+pub const SUITE_25: CipherSuite = CipherSuite {
+    id: 25,
+    aead: Aead::ChaCha20Poly1305,
+    hash: Hash::Shake256,
+    mac_length: 16,
+    curve: Curve::X448,
+    signature: SignatureAlgorithm::Ed448,
+    app_aead: Aead::ChaCha20Poly1305,
+    app_hash: Hash::Shake256,
+};
+
+/// All supported cipher suites.
+pub const SUITES: [CipherSuite; 9] = [
+    SUITE_0, SUITE_1, SUITE_2, SUITE_3, SUITE_4, SUITE_5, SUITE_6, SUITE_24,
+    SUITE_25,
+];
+
+impl CipherSuite {
+    /// Returns the suite with the given identifier.
+    pub fn from_id(id: u8) -> Result<CipherSuite> {
+        SUITES
+            .iter()
+            .find(|suite| suite.id == id)
+            .copied()
+            .ok_or(Error::UnsupportedSuite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry() {
+        for suite in SUITES.iter() {
+            assert_eq!(CipherSuite::from_id(suite.id).unwrap(), *suite);
+            // In all supported suites the MAC is as long as the AEAD tag
+            assert_eq!(suite.mac_length, suite.aead.tag_length());
+        }
+        assert_eq!(CipherSuite::from_id(26), Err(Error::UnsupportedSuite));
+        assert_eq!(CipherSuite::from_id(7), Err(Error::UnsupportedSuite));
+
+        assert_eq!(SUITE_3.aead.nonce_length(), 13);
+        assert_eq!(SUITE_24.hash.output_length(), 48);
+        assert_eq!(SUITE_24.curve.public_key_length(), 48);
+        assert_eq!(SUITE_25.hash.output_length(), 64);
+        assert_eq!(SUITE_25.curve.public_key_length(), 56);
+        assert_eq!(SUITE_25.signature.signature_length(), 114);
+    }
+}
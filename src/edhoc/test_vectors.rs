@@ -19,13 +19,11 @@ pub const _R_STATIC_PK : [u8;32]= [0xE6,0x6F,0x35,0x59,0x90,0x22,0x3C,0x3F,0x6C,
                                   0x62,0xE4,0x07,0xED,0xD1,0x17,0x4D,0x07,0x01,0xA0,0x9E,
                                   0xCD,0x6A,0x15,0xCE,0xE2,0xC6,0xCE,0x21,0xAA,0x50];
 
-pub const MSG1 :[u8;38] = [0x03,0x00,0x58,0x20,0x3A,0xA9,0xEB,0x32,0x01,0xB3,0x36,0x7B,0x8C,
-                            0x8B,0xE3,0x8D,0x91,0xE5,0x7A,0x2B,0x43,0x3E,0x67,0x88,0x8C,0x86,
-                            0xD2,0xAC,0x00,0x6A,0x52,0x08,0x42,0xED,0x50,0x37,0x41,0x0C];
+pub const MSG1 : [u8;37] = [0x03,0x00,0x58,0x20,0x3A,0xA9,0xEB,0x32,0x01,0xB3,0x36,0x7B,
+    0x8C,0x8B,0xE3,0x8D,0x91,0xE5,0x7A,0x2B,0x43,0x3E,0x67,0x88,
+    0x8C,0x86,0xD2,0xAC,0x00,0x6A,0x52,0x08,0x42,0xED,0x50,0x37,
+    0x0C];
 
-pub const R_EPHEMERAL_PK:  [u8;32] = [0x25,0x54,0x91,0xB0,0x5A,0x39,
-0x89,0xFF,0x2D,0x3F,0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,
-0x4E,0xD9,0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E];
 pub const C_R : [u8;1] = [0x40];
 pub const C_I : [u8;1] = [0xC];
 
@@ -37,104 +35,507 @@ pub const I_EPHEMEREAL_SK : [u8;32] = [0xB3,0x11,0x19,0x98,0xCB,0x3F,0x66,0x86,0
 
 
 
-pub const TH_2_RAW_INPUT :[u8;69] = [0x58,0x20,0x9B,0xDD,0xB0,0xCD,0x55,0x48,0x7F,0x82,0xA8,
+pub const A_3 :[u8;45] = [0x83,0x68,0x45,0x6E,0x63,0x72,0x79,0x70,0x74,0x30,0x40,0x58,0x20,0xA4,
+                          0x90,0x07,0xCE,0x54,0x76,0x2E,0x46,0x7C,0x4E,0x4A,0x44,0x69,0x2F,0x20,
+                          0x70,0xD3,0xE9,0xEB,0x00,0xF9,0x5A,0xC2,0x62,0x9B,0x2B,0xBE,0xF7,0xFB,
+                          0x24,0xA3,0x70];
+
+pub const P_3 :[u8;10] = [0x29,0x48,0xDB,0x0B,0x8F,0x75,0x27,0x09,0x53,0xDA];
+
+pub const SHARED_SECRET_0 :[u8;32]= [0x6D,0x26,0x60,0xEC,0x2B,0x30,0x15,0xD9,0x3F,
+                            0xE6,0x5D,0xAE,0xA5,0x12,0x74,0xBD,0x5B,0x1E,
+                            0xBB,0xAD,0x9B,0x62,0x4E,0x67,0x0E,0x79,0xA6,
+                            0x55,0xE3,0x0E,0xC3,0x4D];
+
+
+// Method 0 (SIG-SIG) with Ed25519 authentication keys, using the same
+// ephemeral keys and connection identifiers as the method 3 vectors above.
+// There is no published trace for method 0 with these keys, so the messages
+// are self-generated regression data, not an interoperability check.
+pub const METHOD_TYPE_SIG : u8 = 0;
+pub const KID_R : [u8;1] = [0x10];
+pub const R_EPHEMERAL_SK : [u8;32] = [0xBD,0x86,0xEA,0xF4,0x06,0x5A,0x83,0x6C,0xD2,0x9D,0x0F,0x06,
+    0x91,0xCA,0x2A,0x8E,0xC1,0x3F,0x51,0xD1,0xC4,0x5E,0x1B,0x43,
+    0x72,0xC0,0xCB,0xE4,0x93,0xCE,0xF6,0xBD];
+pub const SIG_I_SK : [u8;32] = [0x4C,0x5B,0x25,0x87,0x8F,0x50,0x7C,0x6B,0x9D,0xAE,0x68,0xFB,
+    0xD4,0xFD,0x3F,0xF9,0x97,0x53,0x3D,0xB0,0xAF,0x00,0xB2,0x5D,
+    0x32,0x4E,0xA2,0x8E,0x6C,0x21,0x3B,0xC8];
+pub const SIG_I_PK : [u8;32] = [0xED,0x06,0xA8,0xAE,0x61,0xA8,0x29,0xBA,0x5F,0xA5,0x45,0x25,
+    0xC9,0xD0,0x7F,0x48,0xDD,0x44,0xA3,0x02,0xF4,0x3E,0x0F,0x23,
+    0xD8,0xCC,0x20,0xB7,0x30,0x85,0x14,0x1E];
+pub const SIG_R_SK : [u8;32] = [0xEF,0x14,0x0F,0xF9,0x00,0xB0,0xAB,0x03,0xF0,0xC0,0x8D,0x87,
+    0x9C,0xBB,0xD4,0xB3,0x1E,0xA7,0x1E,0x6E,0x7E,0xE7,0xFF,0xCB,
+    0x7E,0x79,0x55,0x77,0x7A,0x33,0x27,0x99];
+pub const SIG_R_PK : [u8;32] = [0xA1,0xDB,0x47,0xB9,0x51,0x84,0x85,0x4A,0xD1,0x2A,0x0C,0x1A,
+    0x35,0x4E,0x41,0x8A,0xAC,0xE3,0x3A,0xA0,0xF2,0xC6,0x62,0xC0,
+    0x0B,0x3A,0xC5,0x5D,0xE9,0x2F,0x93,0x59];
+pub const MSG1_SIG : [u8;37] = [0x00,0x00,0x58,0x20,0x3A,0xA9,0xEB,0x32,0x01,0xB3,0x36,0x7B,
+    0x8C,0x8B,0xE3,0x8D,0x91,0xE5,0x7A,0x2B,0x43,0x3E,0x67,0x88,
+    0x8C,0x86,0xD2,0xAC,0x00,0x6A,0x52,0x08,0x42,0xED,0x50,0x37,
+    0x0C];
+pub const MSG2_SIG : [u8;103] = [0x58,0x65,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x8E,0x73,
+    0xED,0x20,0xEE,0x03,0x21,0x97,0xA7,0x35,0x6D,0xDB,0x9B,0xC4,
+    0xE0,0xD6,0x5A,0xB8,0xF5,0x62,0xA6,0x99,0xF8,0x99,0xAE,0x1D,
+    0xDC,0xF7,0xA2,0x9A,0x30,0x27,0x4C,0x0D,0x5F,0x1E,0xAA,0x10,
+    0x4D,0x52,0x12,0xD7,0xD5,0x37,0x81,0x7F,0x5F,0x0B,0x65,0xF7,
+    0x87,0xD8,0xFE,0x20,0x06,0x5A,0xCB,0xE1,0x16,0x97,0xE9,0x4D,
+    0xC9,0xF6,0x9B,0xA5,0xB7,0xF9,0x8C];
+pub const MSG3_SIG : [u8;77] = [0x58,0x4B,0xC8,0x60,0x4A,0x7D,0xEB,0x23,0x68,0x76,0xEF,0x39,
+    0x12,0x0C,0x1E,0xA6,0x8D,0xBD,0xF0,0x67,0x41,0x8E,0x75,0x31,
+    0x42,0x58,0x5C,0x1E,0xAE,0x70,0xAD,0xB5,0x19,0x18,0x0F,0xDB,
+    0x2A,0x42,0xF4,0x3D,0x38,0x3F,0x40,0xCF,0xE8,0xB3,0xA0,0xDF,
+    0xD0,0xBC,0x73,0x3A,0x9F,0xAA,0xFC,0xE6,0xAC,0xD5,0x8D,0xDF,
+    0x74,0x49,0xCC,0x06,0xB0,0x7A,0x72,0x84,0x5C,0x6E,0x09,0x98,
+    0x5F,0x23,0xAA,0xD2,0x73];
+pub const MSG4_SIG : [u8;9] = [0x48,0x72,0x34,0xF7,0xED,0xA5,0x0F,0xA6,0xFA];
+
+// Methods 1 (SIG-STAT) and 2 (STAT-SIG), mixing the keys above.
+pub const R_STATIC_SK : [u8;32] = [0x52,0x8B,0x49,0xC6,0x70,0xF8,0xFC,0x16,0xA2,0xAD,0x95,0xC1,
+    0x88,0x5B,0x2E,0x24,0xFB,0x15,0x76,0x22,0x72,0x79,0x2A,0xA1,
+    0xCF,0x05,0x1D,0xF5,0xD9,0x3D,0x36,0x94];
+pub const MSG2_SIG_STAT : [u8;46] = [0x58,0x2C,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x52,0xB4,
+    0xBE,0x69,0x55,0x75,0x6E,0x94,0xDD,0x50,0xD8,0x4E];
+pub const MSG3_SIG_STAT : [u8;77] = [0x58,0x4B,0xD5,0xAC,0xA7,0x1A,0x5F,0xFC,0xBD,0x57,0x91,0x12,
+    0xED,0x35,0x22,0xEC,0xF2,0x9B,0xE5,0xC2,0x4E,0xCC,0xB5,0xD4,
+    0xA5,0x4C,0xB7,0xD6,0x7E,0xAC,0x3D,0x3B,0xE1,0xB2,0xF6,0x0E,
+    0x9C,0xB8,0x5F,0xAF,0xD0,0xD1,0xDF,0x58,0x1B,0x6F,0xC4,0xA2,
+    0x47,0x1F,0xD1,0x26,0x26,0xEF,0x76,0x7C,0x9A,0x3B,0x15,0x00,
+    0x08,0xE5,0x4A,0x39,0x66,0xBC,0xDC,0x03,0xBA,0x04,0xBD,0xC9,
+    0xA6,0x0A,0x26,0x21,0xA7];
+pub const MSG2_STAT_SIG : [u8;103] = [0x58,0x65,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x99,0x98,
+    0xC4,0x2E,0xC7,0x34,0x87,0x07,0x73,0x82,0x91,0x4B,0x70,0x44,
+    0xB5,0xDE,0x2C,0xC1,0x74,0x2B,0x1D,0xD7,0xD6,0xDC,0x5F,0x4D,
+    0x3C,0xB1,0x63,0xCE,0x26,0x49,0x5D,0x01,0xC3,0xFD,0x0F,0x7E,
+    0x2E,0x24,0xA5,0x64,0xFC,0x89,0x9C,0xDA,0x8C,0x04,0x22,0x34,
+    0xAB,0x5F,0x4E,0x77,0x60,0x27,0x4B,0x91,0x3A,0x97,0x5C,0xC6,
+    0x11,0xDF,0xCA,0xEB,0xA5,0x66,0x76];
+pub const MSG3_STAT_SIG : [u8;19] = [0x52,0x2A,0x95,0xE3,0x23,0xBE,0x8E,0xFA,0x2C,0xE0,0x92,0xE1,
+    0x8A,0xDE,0x1C,0x33,0x52,0x35,0x0D];
+
+
+// P-256 keys, taken from the RFC 9529 trace of suite 2
+pub const P256_I_EPHEMERAL_SK : [u8;32] = [0x36,0x8E,0xC1,0xF6,0x9A,0xEB,0x65,0x9B,0xA3,0x7D,0x5A,0x8D,
+                                           0x45,0xB2,0x1B,0xDC,0x02,0x99,0xDC,0xEA,0xA8,0xEF,0x23,0x5F,
+                                           0x3C,0xA4,0x2C,0xE3,0x53,0x0F,0x95,0x25];
+
+pub const P256_R_EPHEMERAL_SK : [u8;32] = [0xE2,0xF4,0x12,0x67,0x77,0x20,0x5E,0x85,0x3B,0x43,0x7D,0x6E,
+                                           0xAC,0xA1,0xE1,0xF7,0x53,0xCD,0xCC,0x3E,0x2C,0x69,0xFA,0x88,
+                                           0x4B,0x0A,0x1A,0x64,0x09,0x77,0xE4,0x18];
+
+pub const P256_I_STATIC_SK : [u8;32] = [0xFB,0x13,0xAD,0xEB,0x65,0x18,0xCE,0xE5,0xF8,0x84,0x17,0x66,
+                                        0x08,0x41,0x14,0x2E,0x83,0x0A,0x81,0xFE,0x33,0x43,0x80,0xA9,
+                                        0x53,0x40,0x6A,0x13,0x05,0xE8,0x70,0x6B];
+
+pub const P256_R_STATIC_SK : [u8;32] = [0x72,0xCC,0x47,0x61,0xDB,0xD4,0xC7,0x8F,0x75,0x89,0x31,0xAA,
+                                        0x58,0x9D,0x34,0x8D,0x1E,0xF8,0x74,0xA7,0xE3,0x03,0xED,0xE2,
+                                        0xF1,0x40,0xDC,0xF3,0xE6,0xAA,0x4A,0xAC];
+
+// The trace of RFC 9529, Section 3, with method 3, SUITES_I = [6, 2] and the
+// P-256 keys above
+pub const RFC_C_I : [u8;1] = [0x37];
+pub const RFC_C_R : [u8;1] = [0x27];
+pub const RFC_KID_I : [u8;1] = [0x2B];
+pub const RFC_KID_R : [u8;1] = [0x32];
+pub const RFC_MSG1 : [u8;39] = [0x03,0x82,0x06,0x02,0x58,0x20,0x8A,0xF6,0xF4,0x30,0xEB,0xE1,
+    0x8D,0x34,0x18,0x40,0x17,0xA9,0xA1,0x1B,0xF5,0x11,0xC8,0xDF,
+    0xF8,0xF8,0x34,0x73,0x0B,0x96,0xC1,0xB7,0xC8,0xDB,0xCA,0x2F,
+    0xC3,0xB6,0x37];
+pub const RFC_G_Y : [u8;32] = [0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,0x7A,0x36,
+    0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,0x2C,0x8E,
+    0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5];
+pub const RFC_G_XY : [u8;32] = [0x2F,0x0C,0xB7,0xE8,0x60,0xBA,0x53,0x8F,0xBF,0x5C,0x8B,0xDE,
+    0xD0,0x09,0xF6,0x25,0x9B,0x4B,0x62,0x8F,0xE1,0xEB,0x7D,0xBE,
+    0x93,0x78,0xE5,0xEC,0xF7,0xA8,0x24,0xBA];
+pub const RFC_G_RX : [u8;32] = [0xF2,0xB6,0xEE,0xA0,0x22,0x20,0xB9,0x5E,0xEE,0x5A,0x0B,0xC7,
+    0x01,0xF0,0x74,0xE0,0x0A,0x84,0x3E,0xA0,0x24,0x22,0xF6,0x08,
+    0x25,0xFB,0x26,0x9B,0x3E,0x16,0x14,0x23];
+pub const RFC_TH_2 : [u8;32] = [0x35,0x6E,0xFD,0x53,0x77,0x14,0x25,0xE0,0x08,0xF3,0xFE,0x3A,
+    0x86,0xC8,0x3F,0xF4,0xC6,0xB1,0x6E,0x57,0x02,0x8F,0xF3,0x9D,
+    0x52,0x36,0xC1,0x82,0xB2,0x02,0x08,0x4B];
+pub const RFC_PRK_2E : [u8;32] = [0x5A,0xA0,0xD6,0x9F,0x3E,0x3D,0x1E,0x0C,0x47,0x9F,0x0B,0x8A,
+    0x48,0x66,0x90,0xC9,0x80,0x26,0x30,0xC3,0x46,0x6B,0x1D,0xC9,
+    0x23,0x71,0xC9,0x82,0x56,0x31,0x70,0xB5];
+pub const RFC_PRK_3E2M : [u8;32] = [0x0C,0xA3,0xD3,0x39,0x82,0x96,0xB3,0xC0,0x39,0x00,0x98,0x76,
+    0x20,0xC1,0x1F,0x6F,0xCE,0x70,0x78,0x1C,0x1D,0x12,0x19,0x72,
+    0x0F,0x9E,0xC0,0x8C,0x12,0x2D,0x84,0x34];
+pub const RFC_CRED_R : [u8;95] = [0xA2,0x02,0x6B,0x65,0x78,0x61,0x6D,0x70,0x6C,0x65,0x2E,0x65,
+    0x64,0x75,0x08,0xA1,0x01,0xA5,0x01,0x02,0x02,0x41,0x32,0x20,
+    0x01,0x21,0x58,0x20,0xBB,0xC3,0x49,0x60,0x52,0x6E,0xA4,0xD3,
+    0x2E,0x94,0x0C,0xAD,0x2A,0x23,0x41,0x48,0xDD,0xC2,0x17,0x91,
+    0xA1,0x2A,0xFB,0xCB,0xAC,0x93,0x62,0x20,0x46,0xDD,0x44,0xF0,
+    0x22,0x58,0x20,0x45,0x19,0xE2,0x57,0x23,0x6B,0x2A,0x0C,0xE2,
+    0x02,0x3F,0x09,0x31,0xF1,0xF3,0x86,0xCA,0x7A,0xFD,0xA6,0x4F,
+    0xCD,0xE0,0x10,0x8C,0x22,0x4C,0x51,0xEA,0xBF,0x60,0x72];
+pub const RFC_MAC_2 : [u8;8] = [0x09,0x43,0x30,0x5C,0x89,0x9F,0x5C,0x54];
+pub const RFC_PLAINTEXT_2 : [u8;11] = [0x27,0x32,0x48,0x09,0x43,0x30,0x5C,0x89,0x9F,0x5C,0x54];
+pub const RFC_MSG2 : [u8;45] = [0x58,0x2B,0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,
+    0x7A,0x36,0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,
+    0x2C,0x8E,0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5,0x98,0x62,
+    0xA1,0xEE,0xF9,0xE0,0xE7,0xE1,0x88,0x6F,0xCD];
+pub const RFC_CRED_I : [u8;107] = [0xA2,0x02,0x77,0x34,0x32,0x2D,0x35,0x30,0x2D,0x33,0x31,0x2D,
+    0x46,0x46,0x2D,0x45,0x46,0x2D,0x33,0x37,0x2D,0x33,0x32,0x2D,
+    0x33,0x39,0x08,0xA1,0x01,0xA5,0x01,0x02,0x02,0x41,0x2B,0x20,
+    0x01,0x21,0x58,0x20,0xAC,0x75,0xE9,0xEC,0xE3,0xE5,0x0B,0xFC,
+    0x8E,0xD6,0x03,0x99,0x88,0x95,0x22,0x40,0x5C,0x47,0xBF,0x16,
+    0xDF,0x96,0x66,0x0A,0x41,0x29,0x8C,0xB4,0x30,0x7F,0x7E,0xB6,
+    0x22,0x58,0x20,0x6E,0x5D,0xE6,0x11,0x38,0x8A,0x4B,0x8A,0x82,
+    0x11,0x33,0x4A,0xC7,0xD3,0x7E,0xCB,0x52,0xA3,0x87,0xD2,0x57,
+    0xE6,0xDB,0x3C,0x2A,0x93,0xDF,0x21,0xFF,0x3A,0xFF,0xC8];
+pub const RFC_PRK_4E3M : [u8;32] = [0x81,0xCC,0x8A,0x29,0x8E,0x35,0x70,0x44,0xE3,0xC4,0x66,0xBB,
+    0x5C,0x0A,0x1E,0x50,0x7E,0x01,0xD4,0x92,0x38,0xAE,0xBA,0x13,
+    0x8D,0xF9,0x46,0x35,0x40,0x7C,0x0F,0xF7];
+pub const RFC_MSG3 : [u8;19] = [0x52,0xE5,0x62,0x09,0x7B,0xC4,0x17,0xDD,0x59,0x19,0x48,0x5A,
+    0xC7,0x89,0x1F,0xFD,0x90,0xA9,0xFC];
+pub const RFC_TH_4 : [u8;32] = [0xC9,0x02,0xB1,0xE3,0xA4,0x32,0x6C,0x93,0xC5,0x55,0x1F,0x5F,
+    0x3A,0xA6,0xC5,0xEC,0xC0,0x24,0x68,0x06,0x76,0x56,0x12,0xE5,
+    0x2B,0x5D,0x99,0xE6,0x05,0x9D,0x6B,0x6E];
+pub const RFC_PRK_OUT : [u8;32] = [0x2C,0x71,0xAF,0xC1,0xA9,0x33,0x8A,0x94,0x0B,0xB3,0x52,0x9C,
+    0xA7,0x34,0xB8,0x86,0xF3,0x0D,0x1A,0xBA,0x0B,0x4D,0xC5,0x1B,
+    0xEE,0xAE,0xAB,0xDF,0xEA,0x9E,0xCB,0xF8];
+pub const RFC_MSG4 : [u8;9] = [0x48,0x28,0xC9,0x66,0xB7,0xCA,0x30,0x4F,0x83];
+pub const RFC_PRK_EXPORTER : [u8;32] = [0xE1,0x4D,0x06,0x69,0x9C,0xEE,0x24,0x8C,0x5A,0x04,0xBF,0x92,
+    0x27,0xBB,0xCD,0x4C,0xE3,0x94,0xDE,0x7D,0xCB,0x56,0xDB,0x43,
+    0x55,0x54,0x74,0x17,0x1E,0x64,0x46,0xDB];
+pub const RFC_OSCORE_SECRET : [u8;16] = [0xF9,0x86,0x8F,0x6A,0x3A,0xCA,0x78,0xA0,0x5D,0x14,0x85,0xB3,
+    0x50,0x30,0xB1,0x62];
+pub const RFC_OSCORE_SALT : [u8;8] = [0xAD,0xA2,0x4C,0x7D,0xBF,0xC8,0x5E,0xEB];
+
+// The uplink key, downlink key and RK0 of the RFC 9529 session, which the
+// ratchet and LoRaWAN tests start from
+pub const RFC_UPLINK : [u8;32] = [0xCA,0x9F,0x94,0xBF,0x8B,0x06,0x2C,0xE2,0x5C,0x9D,0x9E,0x55,
+    0xCE,0x02,0x64,0x7D,0x44,0x04,0x28,0x6D,0x38,0x78,0x1B,0x9C,
+    0xA6,0xC1,0x4C,0xEA,0x57,0xEA,0x47,0x95];
+
+pub const RFC_DOWNLINK : [u8;32] = [0x7F,0xF3,0xE8,0xF0,0xB9,0x97,0xCF,0x2F,0xEA,0xD0,0x9D,0x77,
+    0x8C,0xBE,0x2C,0xD1,0xE8,0xDE,0x69,0x67,0x37,0xA5,0xA8,0xE2,
+    0xF0,0x1A,0x76,0xCA,0x8B,0x94,0xB5,0xFA];
+
+pub const RFC_RK0 : [u8;32] = [0x29,0xCE,0x81,0xD4,0x36,0xC8,0x6A,0x21,0x6A,0x54,0x52,0xB9,
+    0xB6,0x62,0x71,0x54,0x38,0xAA,0x8D,0x7A,0xE4,0xF0,0x3A,0x01,
+    0x00,0xCA,0x9C,0x14,0xD3,0x5E,0x48,0xF1];
+
+pub const MSG2_P256_STAT : [u8;46] = [0x58,0x2C,0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,
+    0x7A,0x36,0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,
+    0x2C,0x8E,0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5,0xE9,0xE0,
+    0x6E,0xE1,0x96,0xDA,0x78,0x9E,0xA9,0x23,0x5B,0xD6];
+
+pub const MSG3_P256_STAT : [u8;19] = [0x52,0xDC,0x60,0x51,0xB8,0x1D,0x72,0x0E,0x44,0xF9,0x6C,0xE5,
+    0x78,0x21,0x4E,0xB4,0x00,0xF1,0xF7];
+
+pub const MSG2_P256_SIG : [u8;103] = [0x58,0x65,0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,
+    0x7A,0x36,0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,
+    0x2C,0x8E,0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5,0xFD,0x1C,
+    0xB6,0xCF,0xBD,0xBB,0xC7,0x3F,0xA0,0x98,0x25,0x0E,0x19,0x29,
+    0x05,0x2E,0xFD,0x10,0xF8,0xDF,0xB3,0x42,0x81,0x30,0xCC,0x55,
+    0x97,0x53,0xC0,0x1C,0xAD,0x76,0xC4,0xC0,0x10,0x31,0x09,0xD3,
+    0xB2,0x8E,0xEA,0xC4,0x05,0x2B,0xF0,0x08,0xE0,0xE4,0xCE,0x5F,
+    0xA2,0x3D,0x12,0x8B,0x3C,0x9F,0x3B,0xA2,0xF5,0xB5,0xEC,0xD4,
+    0xC9,0x4C,0xF6,0xBA,0x19,0xE8,0x09];
+
+pub const MSG3_P256_SIG : [u8;85] = [0x58,0x53,0xA2,0x58,0x9A,0x39,0xAB,0xBE,0x00,0x0F,0x60,0x03,
+    0x50,0xEF,0x3A,0x1A,0x1F,0x87,0x0B,0x46,0x88,0xF1,0xAE,0xD4,
+    0xBE,0x4B,0x92,0xBA,0xEF,0xBA,0xC3,0x14,0x72,0x75,0x0C,0xFB,
+    0xDA,0x1A,0x4A,0xEC,0x51,0x3D,0xBA,0x81,0xB2,0xD6,0x9D,0x63,
+    0x30,0xF5,0x83,0xD7,0x63,0x70,0x42,0xA0,0x7E,0xBF,0x4D,0xA9,
+    0xAA,0x38,0x44,0xA0,0x7A,0x35,0x18,0xD4,0xE9,0x79,0x77,0x04,
+    0xC1,0xE1,0x3E,0xA4,0x1B,0x71,0x10,0x4F,0x68,0x64,0x20,0xCC,
+    0x4F];
+
+
+// Suite 1 with method 3 and the keys of suite 0
+pub const MSG2_SUITE1 : [u8;54] = [0x58,0x34,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x98,0xAC,
+    0xAB,0xCA,0x8F,0x8F,0x91,0x15,0x3C,0xAC,0xCC,0x47,0x16,0x35,
+    0x16,0x96,0xC1,0x42,0x23,0xD3];
+
+pub const MSG3_SUITE1 : [u8;36] = [0x58,0x22,0x3D,0x9E,0x05,0x05,0xAB,0x77,0x0A,0x71,0x74,0xC8,
+    0xCD,0xD2,0x31,0x2D,0x17,0xCA,0x82,0x1B,0x75,0xD2,0x96,0x1B,
+    0xD5,0xDC,0x39,0x45,0x75,0x63,0xB8,0xAC,0x71,0xC2,0xB4,0xD4];
+
+
+// Suite 4 with method 3 and the keys of suite 0, and suite 5 with method 0
+// and the P-256 keys
+pub const MSG2_SUITE4 : [u8;54] = [0x58,0x34,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x92,0x66,
+    0xD9,0xAE,0x29,0xC7,0x03,0x26,0x82,0x3B,0x74,0x50,0x81,0x06,
+    0x1F,0xFE,0xE3,0xD1,0x60,0xF1];
+
+pub const MSG3_SUITE4 : [u8;36] = [0x58,0x22,0xBF,0x31,0x52,0x49,0x41,0x84,0xFB,0x09,0x7F,0xBA,
+    0x5A,0x8C,0x23,0x36,0x99,0x2E,0xD2,0xC3,0xC4,0x5C,0xEA,0x07,
+    0xC8,0x05,0x55,0x10,0x2E,0x9D,0x9B,0xEC,0x95,0x19,0xCE,0x8B];
+
+pub const MSG2_SUITE5 : [u8;103] = [0x58,0x65,0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,
+    0x7A,0x36,0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,
+    0x2C,0x8E,0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5,0x94,0x73,
+    0x0F,0xAF,0x41,0x01,0x1B,0x0B,0xC2,0xCF,0x6D,0x1C,0x8F,0x6D,
+    0x56,0x83,0x19,0x75,0x00,0x1B,0x7B,0x4D,0x7F,0x94,0x20,0x0C,
+    0x9A,0xF5,0xA1,0xE0,0x70,0x58,0x43,0xED,0xF0,0x78,0xB6,0xD5,
+    0x14,0xA1,0x91,0x96,0x65,0x87,0xB3,0x55,0x43,0x50,0x88,0xC4,
+    0x6B,0xD7,0xFB,0xAE,0x82,0x4E,0x65,0x36,0x7C,0x32,0x76,0x4C,
+    0x97,0x01,0xEB,0x4E,0xC3,0x4F,0x82];
+
+pub const MSG3_SUITE5 : [u8;85] = [0x58,0x53,0xC2,0x90,0x37,0xE0,0xFD,0x93,0x2F,0xD8,0x0E,0x57,
+    0xBA,0xC4,0x62,0x5C,0x4E,0x0F,0x9B,0x95,0x19,0x22,0xEC,0x4D,
+    0x4A,0xDB,0xD6,0x28,0x92,0x1F,0x23,0x55,0xD9,0x34,0xAC,0x1C,
+    0x81,0xE6,0x76,0xD4,0x06,0xE0,0xBB,0x98,0xDC,0x7C,0x2A,0x7A,
+    0x7D,0xBE,0xCD,0xEB,0x11,0x03,0x70,0x4B,0x87,0x21,0x36,0x3C,
+    0x45,0x77,0xFE,0x1A,0xB0,0xE8,0xD0,0xEC,0x3B,0xC6,0x5E,0xB4,
+    0x83,0x5D,0xF0,0x21,0x72,0x9D,0x69,0x35,0xCF,0x1D,0x88,0xF9,
+    0x5F];
+
+
+// P-384 keys for suite 24
+pub const P384_I_EPHEMERAL_SK : [u8;48] = [0x84,0xAF,0x3D,0x47,0x7B,0x27,0x1F,0x8D,0x0B,0x60,0xF0,0xBB,
+                                           0x76,0x53,0x6E,0x37,0xEE,0x57,0x90,0xCB,0xDE,0x03,0x23,0x8E,
+                                           0x0F,0x60,0x0B,0xDD,0x58,0xF6,0xAF,0x72,0x42,0x42,0x92,0x77,
+                                           0x51,0xC7,0x91,0x55,0xCE,0x7E,0xD1,0x2F,0x00,0x45,0xB9,0xE3];
+
+pub const P384_R_EPHEMERAL_SK : [u8;48] = [0xF7,0x77,0xA7,0x0A,0xC1,0xD6,0xA7,0xBD,0x53,0xC9,0x39,0x21,
+                                           0x13,0x10,0xCD,0x39,0x31,0xB6,0x8C,0x19,0xF0,0x62,0x23,0xA3,
+                                           0x67,0x0D,0x31,0x2B,0x59,0xAE,0x92,0xED,0x3A,0x9F,0xD0,0x28,
+                                           0x74,0x27,0x08,0x81,0x79,0x48,0xB3,0x60,0x80,0xBD,0x41,0x51];
+
+pub const P384_I_STATIC_SK : [u8;48] = [0x3A,0xC6,0xE2,0xF6,0x75,0x70,0xEA,0x9F,0x21,0x13,0x82,0xCE,
+                                        0x10,0x27,0xE7,0x31,0x49,0x0C,0x2D,0x44,0xCC,0x89,0x7F,0x61,
+                                        0x4E,0xFD,0xEF,0xB2,0xB2,0x7A,0xDA,0xE3,0x61,0xEA,0xD1,0x1C,
+                                        0x0F,0x01,0xE5,0x37,0xC6,0x3B,0x57,0xFE,0xE1,0xD7,0xD9,0x86];
+
+pub const P384_R_STATIC_SK : [u8;48] = [0x52,0x1A,0x6A,0xDB,0x5E,0x74,0xA8,0xBD,0x67,0x76,0x83,0x05,
+                                        0xED,0xB1,0x2E,0xD8,0xAD,0x00,0xCE,0x30,0x86,0x03,0x95,0xC1,
+                                        0x6E,0x31,0x71,0x18,0x22,0x84,0xD6,0xB1,0x3C,0xDD,0x01,0x3D,
+                                        0xE7,0xD5,0x01,0x56,0xCB,0x43,0xB9,0x92,0x74,0xD5,0xC2,0xA1];
+
+// Suite 6 with method 0, X25519 and the P-256 signature keys, and suite 24
+// with method 3
+pub const MSG2_SUITE6 : [u8;103] = [0x58,0x65,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0xF0,0x79,
+    0xC3,0x5D,0x90,0x90,0x5E,0x30,0x3E,0x9B,0x73,0xB9,0xAA,0x75,
+    0x5F,0x3A,0x90,0x2C,0xA0,0x05,0xED,0x82,0x98,0xAF,0x3C,0x68,
+    0x64,0xDF,0x41,0x5E,0x3F,0x9D,0xB7,0xF3,0xE8,0x97,0x9E,0x08,
+    0x53,0x05,0x0D,0x28,0x3E,0x98,0x9B,0xD7,0xFB,0xFC,0xE6,0x8B,
+    0xA4,0xCA,0x9E,0x6D,0xFA,0x35,0xD0,0x98,0x0A,0x5A,0x13,0x5E,
+    0xEE,0x91,0xB7,0x63,0x82,0x84,0x58];
+
+pub const MSG3_SUITE6 : [u8;85] = [0x58,0x53,0x06,0xBC,0x6B,0x3E,0xFA,0x55,0x07,0x11,0xF6,0xA8,
+    0xC7,0x63,0x4C,0xAE,0x57,0x4D,0x22,0xAF,0x84,0x79,0xA3,0x4C,
+    0x02,0x9B,0xDE,0x40,0xA1,0xFA,0x5A,0xE8,0x73,0x76,0x62,0x7C,
+    0x19,0x49,0x30,0x74,0x13,0xF3,0x3C,0x9D,0xDF,0x19,0x78,0x77,
+    0x62,0x2E,0x02,0x66,0xF4,0x7E,0x73,0xF8,0x39,0xF1,0x17,0x13,
+    0x43,0x67,0xA7,0x0C,0x18,0x65,0x3F,0x6D,0x6A,0x9A,0x05,0x06,
+    0x54,0xC3,0xD1,0xD2,0x12,0xBC,0x6F,0x58,0x69,0x54,0x93,0x43,
+    0x4B];
+
+pub const MSG2_SUITE24 : [u8;70] = [0x58,0x44,0x61,0xBA,0x12,0xDB,0x00,0x1B,0xE8,0x0C,0x97,0xD1,
+    0x37,0xBB,0xE1,0xE9,0xEC,0xE7,0x49,0x4C,0xCF,0x6C,0x92,0x43,
+    0xE0,0x0F,0xFC,0x78,0xC8,0x4B,0x9C,0xA0,0xC6,0x01,0x67,0x8F,
+    0x8E,0x88,0xCE,0x0F,0xEB,0x7E,0x24,0x8A,0x9F,0x3A,0xA5,0x38,
+    0x3B,0x7C,0xD8,0x92,0xB6,0x45,0xFC,0x0C,0x6A,0x23,0x69,0xD6,
+    0x3A,0xF5,0x7D,0x51,0x70,0xEE,0xDB,0x8C,0x6D,0xDB];
+
+pub const MSG3_SUITE24 : [u8;36] = [0x58,0x22,0x2D,0x00,0xAF,0xCE,0x41,0x27,0x28,0x12,0x8D,0x82,
+    0xB6,0x36,0xA3,0x9B,0x5B,0x5B,0x4E,0xD0,0xF4,0x6E,0xB5,0xF0,
+    0x5B,0x33,0x38,0xD9,0x19,0x31,0xF7,0x58,0x30,0x6F,0x81,0x03];
+
+
+// SHA-384("abc"), from FIPS 180-2
+pub const SHA384_ABC : [u8;48] = [0xCB,0x00,0x75,0x3F,0x45,0xA3,0x5E,0x8B,0xB5,0xA0,0x3D,0x69,
+                                  0x9A,0xC6,0x50,0x07,0x27,0x2C,0x32,0xAB,0x0E,0xDE,0xD1,0x63,
+                                  0x1A,0x8B,0x60,0x5A,0x43,0xFF,0x5B,0xED,0x80,0x86,0x07,0x2B,
+                                  0xA1,0xE7,0xCC,0x23,0x58,0xBA,0xEC,0xA1,0x34,0xC8,0x25,0xA7];
+
+// X448 keys for suite 25, the first two being Alice's and Bob's of
+// RFC 7748, Section 6.2
+pub const X448_I_EPHEMERAL_SK : [u8;56] = [0x9A,0x8F,0x49,0x25,0xD1,0x51,0x9F,0x57,0x75,0xCF,0x46,0xB0,
+    0x4B,0x58,0x00,0xD4,0xEE,0x9E,0xE8,0xBA,0xE8,0xBC,0x55,0x65,
+    0xD4,0x98,0xC2,0x8D,0xD9,0xC9,0xBA,0xF5,0x74,0xA9,0x41,0x97,
+    0x44,0x89,0x73,0x91,0x00,0x63,0x82,0xA6,0xF1,0x27,0xAB,0x1D,
+    0x9A,0xC2,0xD8,0xC0,0xA5,0x98,0x72,0x6B];
+
+pub const X448_R_EPHEMERAL_SK : [u8;56] = [0x1C,0x30,0x6A,0x7A,0xC2,0xA0,0xE2,0xE0,0x99,0x0B,0x29,0x44,
+    0x70,0xCB,0xA3,0x39,0xE6,0x45,0x37,0x72,0xB0,0x75,0x81,0x1D,
+    0x8F,0xAD,0x0D,0x1D,0x69,0x27,0xC1,0x20,0xBB,0x5E,0xE8,0x97,
+    0x2B,0x0D,0x3E,0x21,0x37,0x4C,0x9C,0x92,0x1B,0x09,0xD1,0xB0,
+    0x36,0x6F,0x10,0xB6,0x51,0x73,0x99,0x2D];
+
+pub const X448_I_STATIC_SK : [u8;56] = [0x41,0x31,0xB2,0xED,0x7C,0x69,0x10,0x59,0xFE,0xD6,0x74,0x3E,
+    0x67,0xC1,0x20,0x86,0x19,0xC7,0xF0,0x79,0x6A,0x08,0x58,0xEA,
+    0x5B,0xDE,0x60,0xED,0x4B,0x80,0xE4,0x7C,0x18,0xE1,0x12,0x83,
+    0x47,0x58,0x4F,0x29,0xF8,0x72,0x6C,0x07,0x88,0xE2,0xF8,0xA1,
+    0x54,0x49,0xDA,0xF4,0xE2,0xC2,0xAE,0x26];
+
+pub const X448_R_STATIC_SK : [u8;56] = [0x2C,0x93,0xCE,0x33,0x0F,0x2A,0xED,0xC3,0x00,0x01,0x54,0x25,
+    0x79,0xC4,0x06,0x41,0x21,0xEC,0xF7,0xE0,0xFF,0xEB,0x2E,0x0A,
+    0x14,0x95,0x27,0x60,0xD2,0x76,0xE6,0x8E,0x0C,0x9F,0x53,0xCF,
+    0x96,0x75,0xF6,0x41,0x27,0x3D,0x13,0x72,0x72,0xAC,0x45,0xFC,
+    0x55,0x9B,0x58,0xB1,0x55,0x27,0x27,0x9F];
+
+// The shared secret of the two ephemeral keys
+pub const X448_SHARED_SECRET : [u8;56] = [0x07,0xFF,0xF4,0x18,0x1A,0xC6,0xCC,0x95,0xEC,0x1C,0x16,0xA9,
+    0x4A,0x0F,0x74,0xD1,0x2D,0xA2,0x32,0xCE,0x40,0xA7,0x75,0x52,
+    0x28,0x1D,0x28,0x2B,0xB6,0x0C,0x0B,0x56,0xFD,0x24,0x64,0xC3,
+    0x35,0x54,0x39,0x36,0x52,0x1C,0x24,0x40,0x30,0x85,0xD5,0x9A,
+    0x44,0x9A,0x50,0x37,0x51,0x4A,0x87,0x9D];
+
+// Ed448 keys for suite 25. The first is the one of the empty message in
+// RFC 8032, Section 7.4, with its public key and signature.
+pub const ED448_I_STATIC_SK : [u8;57] = [0x6C,0x82,0xA5,0x62,0xCB,0x80,0x8D,0x10,0xD6,0x32,0xBE,0x89,
+    0xC8,0x51,0x3E,0xBF,0x6C,0x92,0x9F,0x34,0xDD,0xFA,0x8C,0x9F,
+    0x63,0xC9,0x96,0x0E,0xF6,0xE3,0x48,0xA3,0x52,0x8C,0x8A,0x3F,
+    0xCC,0x2F,0x04,0x4E,0x39,0xA3,0xFC,0x5B,0x94,0x49,0x2F,0x8F,
+    0x03,0x2E,0x75,0x49,0xA2,0x00,0x98,0xF9,0x5B];
+
+pub const ED448_I_STATIC_PK : [u8;57] = [0x5F,0xD7,0x44,0x9B,0x59,0xB4,0x61,0xFD,0x2C,0xE7,0x87,0xEC,
+    0x61,0x6A,0xD4,0x6A,0x1D,0xA1,0x34,0x24,0x85,0xA7,0x0E,0x1F,
+    0x8A,0x0E,0xA7,0x5D,0x80,0xE9,0x67,0x78,0xED,0xF1,0x24,0x76,
+    0x9B,0x46,0xC7,0x06,0x1B,0xD6,0x78,0x3D,0xF1,0xE5,0x0F,0x6C,
+    0xD1,0xFA,0x1A,0xBE,0xAF,0xE8,0x25,0x61,0x80];
+
+pub const ED448_EMPTY_SIGNATURE : [u8;114] = [0x53,0x3A,0x37,0xF6,0xBB,0xE4,0x57,0x25,0x1F,0x02,0x3C,0x0D,
+    0x88,0xF9,0x76,0xAE,0x2D,0xFB,0x50,0x4A,0x84,0x3E,0x34,0xD2,
+    0x07,0x4F,0xD8,0x23,0xD4,0x1A,0x59,0x1F,0x2B,0x23,0x3F,0x03,
+    0x4F,0x62,0x82,0x81,0xF2,0xFD,0x7A,0x22,0xDD,0xD4,0x7D,0x78,
+    0x28,0xC5,0x9B,0xD0,0xA2,0x1B,0xFD,0x39,0x80,0xFF,0x0D,0x20,
+    0x28,0xD4,0xB1,0x8A,0x9D,0xF6,0x3E,0x00,0x6C,0x5D,0x1C,0x2D,
+    0x34,0x5B,0x92,0x5D,0x8D,0xC0,0x0B,0x41,0x04,0x85,0x2D,0xB9,
+    0x9A,0xC5,0xC7,0xCD,0xDA,0x85,0x30,0xA1,0x13,0xA0,0xF4,0xDB,
+    0xB6,0x11,0x49,0xF0,0x5A,0x73,0x63,0x26,0x8C,0x71,0xD9,0x58,
+    0x08,0xFF,0x2E,0x65,0x26,0x00];
+
+pub const ED448_R_STATIC_SK : [u8;57] = [0x2D,0x01,0x3F,0x3D,0x86,0x0F,0x93,0xE3,0xEA,0xF9,0x36,0x4B,
+    0xE3,0x91,0x62,0x86,0x0C,0x3A,0x03,0x72,0x67,0xC8,0xB6,0x3B,
+    0x31,0x08,0x1F,0x7B,0x01,0x73,0x84,0x1A,0xB9,0x71,0x19,0xD3,
+    0x78,0x72,0x39,0x06,0x06,0xBC,0x26,0xFF,0xD9,0xE5,0xB8,0x91,
+    0xC2,0xBD,0x84,0x25,0x74,0xD6,0x4F,0xB5,0x33];
+
+// KMAC256 sample #5 of NIST SP 800-185, with the key 0x40..0x5F, the data
+// 0x00..0xC7, 512 bits of output and no customization string
+pub const KMAC256_SAMPLE5 : [u8;64] = [0x75,0x35,0x8C,0xF3,0x9E,0x41,0x49,0x4E,0x94,0x97,0x07,0x92,
+    0x7C,0xEE,0x0A,0xF2,0x0A,0x3F,0xF5,0x53,0x90,0x4C,0x86,0xB0,
+    0x8F,0x21,0xCC,0x41,0x4B,0xCF,0xD6,0x91,0x58,0x9D,0x27,0xCF,
+    0x5E,0x15,0x36,0x9C,0xBB,0xFF,0x8B,0x9A,0x4C,0x2E,0xB1,0x78,
+    0x00,0x85,0x5D,0x02,0x35,0xFF,0x63,0x5D,0xA8,0x25,0x33,0xEC,
+    0x6B,0x75,0x9B,0x69];
+
+// draft-ietf-lake-edhoc-12, with the keys and connection identifiers of
+// the vectors above. Its key schedule only uses SHARED_SECRET_0 to 2.
+pub const D12_MSG1 :[u8;38] = [0x03,0x00,0x58,0x20,0x3A,0xA9,0xEB,0x32,0x01,0xB3,0x36,0x7B,0x8C,
+                            0x8B,0xE3,0x8D,0x91,0xE5,0x7A,0x2B,0x43,0x3E,0x67,0x88,0x8C,0x86,
+                            0xD2,0xAC,0x00,0x6A,0x52,0x08,0x42,0xED,0x50,0x37,0x41,0x0C];
+pub const D12_R_EPHEMERAL_PK:  [u8;32] = [0x25,0x54,0x91,0xB0,0x5A,0x39,
+0x89,0xFF,0x2D,0x3F,0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,
+0x4E,0xD9,0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E];
+pub const D12_TH_2_RAW_INPUT :[u8;69] = [0x58,0x20,0x9B,0xDD,0xB0,0xCD,0x55,0x48,0x7F,0x82,0xA8,
                             0x6F,0xB7,0x2A,0x8B,0xB3,0x58,0x52,0x68,0x91,0xA0,0xA6,
                             0xC9,0x08,0x61,0x24,0x12,0xF5,0xAF,0x29,0x9D,0xAF,0x01,
                             0x96,0x58,0x20,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,
                             0x2D,0x3F,0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,
                             0x29,0x4E,0xD9,0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,
                             0x82,0x4E,0x40];
-
-pub const TH_2_CBOR : [u8;34] =[0x58,0x20,0x71,0xA6,0xC7,0xC5,0xBA,0x9A,0xD4,0x7F,
+pub const D12_TH_2_CBOR : [u8;34] =[0x58,0x20,0x71,0xA6,0xC7,0xC5,0xBA,0x9A,0xD4,0x7F,
                            0xE7,0x2D,0xA4,0xDC,0x35,0x9B,0xF6,0xB2,0x76,0xD3,
                            0x51,0x59,0x68,0x71,0x1B,0x9A,0x91,0x1C,0x71,0xFC,
                            0x09,0x6A,0xEE,0x0E];
-pub const _TH_2 : [u8;32] = [0x71,0xA6,0xC7,0xC5,0xBA,0x9A,0xD4,0x7F,
-                                0xE7,0x2D,0xA4,0xDC,0x35,0x9B,0xF6,0xB2,
-                                0x76,0xD3,0x51,0x59,0x68,0x71,0x1B,0x9A,
-                                0x91,0x1C,0x71,0xFC,0x09,0x6A,0xEE,0x0E];
-
-
-
-pub const TH_4_CBOR : [u8;34] = [0x58,0x20,0x4B,0x9A,0xDD,0x2A,0x9E,0xEB,
+pub const D12_TH_4_CBOR : [u8;34] = [0x58,0x20,0x4B,0x9A,0xDD,0x2A,0x9E,0xEB,
                                 0x88,0x49,0x71,0x6C,0x79,0x68,0x78,0x4F,
                                 0x55,0x40,0xDD,0x64,0xA3,0xBB,0x07,0xF8,
                                 0xD0,0x00,0xAD,0xCE,0x88,0xB6,0x30,0xD8,
                                 0x84,0xEB];
-
-
-
-
-
-pub const CRED_R : [u8;59]= [0xA2,0x02,0x6B,0x65,0x78,0x61,0x6D,0x70,0x6C,0x65,0x2E,
+pub const D12_CRED_R : [u8;59]= [0xA2,0x02,0x6B,0x65,0x78,0x61,0x6D,0x70,0x6C,0x65,0x2E,
                     0x65,0x64,0x75,0x08,0xA1,0x01,0xA4,0x01,0x01,0x02,0x05,
                     0x20,0x04,0x21,0x58,0x20,0xE6,0x6F,0x35,0x59,0x90,0x22,
                     0x3C,0x3F,0x6C,0xAF,0xF8,0x62,0xE4,0x07,0xED,0xD1,0x17,
                     0x4D,0x07,0x01,0xA0,0x9E,0xCD,0x6A,0x15,0xCE,0xE2,0xC6,
                     0xCE,0x21,0xAA,0x50];
-
-pub const CIPHERTEXT_2 : [u8;10] = [0x0F,0xF0,0x4C,0x29,0x4F,0x4A,0xC6,0x02,0xCF,0x78];
-
-pub const CIPHERTEXT_3 : [u8;18] = [0xBE,0x01,0x46,0xC1,0x36,0xAC,0x2E,0xFF,0xD4,0x53,0xA7,0x5E,0xFA,0x90,0x89,0x6F,0x65,0x3B];
-pub const ID_CRED_R : [u8;3]= [0xA1, 0x04, 0x05];
-
-pub const IV_3 : [u8;13] = [0xB3,0x8F,0xB6,0x31,0xE3,0x44,0xA8,0x10,0x52,0x56,0x32,0xED,0xF8];
-
-pub const A_3 :[u8;45] = [0x83,0x68,0x45,0x6E,0x63,0x72,0x79,0x70,0x74,0x30,0x40,0x58,0x20,0xA4,
-                          0x90,0x07,0xCE,0x54,0x76,0x2E,0x46,0x7C,0x4E,0x4A,0x44,0x69,0x2F,0x20,
-                          0x70,0xD3,0xE9,0xEB,0x00,0xF9,0x5A,0xC2,0x62,0x9B,0x2B,0xBE,0xF7,0xFB,
-                          0x24,0xA3,0x70];
-
-pub const P_3 :[u8;10] = [0x29,0x48,0xDB,0x0B,0x8F,0x75,0x27,0x09,0x53,0xDA];
-
-pub const K_3 : [u8;16] = [0x2A,0x30,0xE4,0xF6,0xBC,0x55,0x8D,0x0E,0x7A,0x8C,0x63,0xEE,0x7B,0xB5,0x45,0x7F];
-
-pub const MSG2 : [u8;46] = [0x58,0x2A,0x25,0x54,0x91,0xB0,0x5A,0x39,
+pub const D12_CIPHERTEXT_2 : [u8;10] = [0x0F,0xF0,0x4C,0x29,0x4F,0x4A,0xC6,0x02,0xCF,0x78];
+pub const D12_CIPHERTEXT_3 : [u8;18] = [0xBE,0x01,0x46,0xC1,0x36,0xAC,0x2E,0xFF,0xD4,0x53,0xA7,0x5E,0xFA,0x90,0x89,0x6F,0x65,0x3B];
+pub const D12_ID_CRED_R : [u8;3]= [0xA1, 0x04, 0x05];
+pub const D12_IV_3 : [u8;13] = [0xB3,0x8F,0xB6,0x31,0xE3,0x44,0xA8,0x10,0x52,0x56,0x32,0xED,0xF8];
+pub const D12_K_3 : [u8;16] = [0x2A,0x30,0xE4,0xF6,0xBC,0x55,0x8D,0x0E,0x7A,0x8C,0x63,0xEE,0x7B,0xB5,0x45,0x7F];
+pub const D12_MSG2 : [u8;46] = [0x58,0x2A,0x25,0x54,0x91,0xB0,0x5A,0x39,
                             0x89,0xFF,0x2D,0x3F,0xFE,0xA6,0x20,0x98,
                             0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
                             0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,
                             0x82,0x4E,0x0F,0xF0,0x4C,0x29,0x4F,0x4A,
                             0xC6,0x02,0xCF,0x78,0x41,0x40];
-
-pub const SHARED_SECRET_0 :[u8;32]= [0x6D,0x26,0x60,0xEC,0x2B,0x30,0x15,0xD9,0x3F,
-                            0xE6,0x5D,0xAE,0xA5,0x12,0x74,0xBD,0x5B,0x1E,
-                            0xBB,0xAD,0x9B,0x62,0x4E,0x67,0x0E,0x79,0xA6,
-                            0x55,0xE3,0x0E,0xC3,0x4D];
-
-pub const SHARED_SECRET_1 :[u8;32]=[0xB5,0x8B,0x40,0x34,0x26,0xC0,0x3D,0xB0,0x7B,
+pub const D12_SHARED_SECRET_1 :[u8;32]=[0xB5,0x8B,0x40,0x34,0x26,0xC0,0x3D,0xB0,0x7B,
                                     0xAA,0x93,0x44,0xD5,0x51,0xE6,0x7B,0x21,0x78,
                                     0xBF,0x05,0xEC,0x6F,0x52,0xC3,0x6A,0x2F,0xA5,
                                     0xBE,0x23,0x2D,0xD4,0x78];
-
-
-pub const SHARED_SECRET_2 :[u8;32]=[0x0A,0xF4,0x2A,0xD5,0x12,0xDC,0x3E,0x97,0x2B,
+pub const D12_SHARED_SECRET_2 :[u8;32]=[0x0A,0xF4,0x2A,0xD5,0x12,0xDC,0x3E,0x97,0x2B,
                             0x3A,0xC4,0xD4,0x7B,0xA3,0x3F,0xFC,0x21,0xF1,0xAE,0x6F,
                             0x07,0xF2,0xF8,0x94,0x85,0x4A,0x5A,0x47,0x44,0x33,0x85,0x48];
-
-pub const PRK2E : [u8;32] = [0xD1,0xD0,0x11,0xA5,0x9A,0x6D,0x10,0x57,0x5E,
+pub const D12_PRK2E : [u8;32] = [0xD1,0xD0,0x11,0xA5,0x9A,0x6D,0x10,0x57,0x5E,
                         0xB2,0x20,0xC7,0x65,0x2E,0x6F,0x98,0xC4,0x17,0xA5,
                         0x65,0xE4,0xE4,0x5C,0xF5,0xB5,0x01,0x06,0x95,0x04,
                         0x3B,0x0E,0xB7];
-pub const PRK3EM : [u8;32] = [0x76,0x8E,0x13,0x75,0x27,0x2E,0x1E,0x68,0xB4,
+pub const D12_PRK3EM : [u8;32] = [0x76,0x8E,0x13,0x75,0x27,0x2E,0x1E,0x68,0xB4,
                             0x2C,0xA3,0x24,0x84,0x80,0xD5,0xBB,0xA8,0x8B,
                             0xCB,0x55,0xF6,0x60,0xCE,0x7F,0x94,0x1E,0x67,
                             0x09,0x10,0x31,0x17,0xA1];
-pub const PRK4XM : [u8;32]=[0xB8,0xCC,0xDF,0x14,0x20,0xB5,0xB0,0xC8,0x2A,
+pub const D12_PRK4XM : [u8;32]=[0xB8,0xCC,0xDF,0x14,0x20,0xB5,0xB0,0xC8,0x2A,
                             0x58,0x7E,0x7D,0x26,0xDD,0x7B,0x70,0x48,0x57
                             ,0x4C,0x3A,0x48,0xDF,0x9F,0x6A,0x45,0xF7,0x21,
                             0xC0,0xCF,0xA4,0xB2,0x7C];
-
-
-pub const MAC_2 : [u8;8] = [0x8E,0x27,0xCB,0xD4,0x94,0xF7,0x52,0x83];
-
-
-pub const MASTER_SECRET : [u8;16] =[0xC0,0x53,0x01,0x37,0x6C,0xE9,0x5F,0x67,0xC4,0x14,0xD8,0xBB,0x5F,0x0F,0xDB,0x5E];
-
-pub const PLAINTEXT_2 : [u8;11] = [0x41,0x05,0x48,0x8E,0x27,0xCB,0xD4,0x94,0xF7,0x52,0x83];
-pub const _MASTER_SECRET_INFO :[u8;57]= [0x58,0x20,0x4B,0x9A,0xDD,0x2A,0x9E,0xEB,0x88,
-                                        0x49,0x71,0x6C,0x79,0x68,0x78,0x4F,0x55,0x40,
-                                        0xDD,0x64,0xA3,0xBB,0x07,0xF8,0xD0,0x00,0xAD,
-                                        0xCE,0x88,0xB6,0x30,0xD8,0x84,0xEB,0x74,0x4F,
-                                        0x53,0x43,0x4F,0x52,0x45,0x5F,0x4D,0x61,0x73,
-                                        0x74,0x65,0x72,0x5F,0x53,0x65,0x63,0x72,0x65,
-                                        0x74,0x40,0x10];
\ No newline at end of file
+pub const D12_MAC_2 : [u8;8] = [0x8E,0x27,0xCB,0xD4,0x94,0xF7,0x52,0x83];
+pub const D12_MASTER_SECRET : [u8;16] =[0xC0,0x53,0x01,0x37,0x6C,0xE9,0x5F,0x67,0xC4,0x14,0xD8,0xBB,0x5F,0x0F,0xDB,0x5E];
+pub const D12_PLAINTEXT_2 : [u8;11] = [0x41,0x05,0x48,0x8E,0x27,0xCB,0xD4,0x94,0xF7,0x52,0x83];
+// Method 0 and the mixed methods in the draft-12 format, self-generated
+// regression data like the method 0 vectors above.
+pub const D12_MSG1_SIG : [u8;38] = [0x00,0x00,0x58,0x20,0x3A,0xA9,0xEB,0x32,0x01,0xB3,0x36,0x7B,
+    0x8C,0x8B,0xE3,0x8D,0x91,0xE5,0x7A,0x2B,0x43,0x3E,0x67,0x88,
+    0x8C,0x86,0xD2,0xAC,0x00,0x6A,0x52,0x08,0x42,0xED,0x50,0x37,
+    0x41,0x0C];
+pub const D12_MSG2_SIG : [u8;104] = [0x58,0x64,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0xC1,0xFA,
+    0x5F,0x9F,0x02,0x99,0x9F,0x0A,0x3E,0x0A,0xB7,0x00,0x47,0x0F,
+    0x38,0x4D,0xD2,0x8A,0x18,0x30,0xE5,0xA1,0xF8,0xA9,0xB4,0xED,
+    0x79,0x2C,0xF3,0x81,0xEA,0xB8,0xAD,0x68,0xC0,0x80,0xC2,0x96,
+    0x61,0x37,0x62,0xE5,0x44,0xCF,0x61,0x5E,0x71,0x49,0x55,0xC6,
+    0xAA,0x4F,0x17,0xE7,0x25,0x1F,0x72,0x47,0x7C,0xC8,0x13,0x65,
+    0x7F,0xCB,0x33,0x27,0x41,0xF6,0x41,0x40];
+pub const D12_MSG3_SIG : [u8;78] = [0x58,0x4C,0x80,0x20,0xD5,0x4B,0xE8,0xB6,0x97,0x7E,0xCE,0x24,
+    0x96,0xAD,0x4A,0x04,0x40,0x17,0xEE,0x2D,0x71,0x7E,0x22,0x5E,
+    0x33,0x7D,0x50,0x08,0x7D,0xB2,0xD4,0x61,0x35,0xA1,0xDB,0xEF,
+    0x9F,0xBA,0x27,0xCB,0xC2,0x4F,0x33,0x59,0x0D,0xC4,0x10,0xCB,
+    0x13,0xAF,0xF5,0xB2,0x80,0xCC,0x5C,0x47,0x9F,0x67,0xDA,0xFE,
+    0xDF,0x36,0x44,0x69,0x8E,0x3C,0x37,0xD5,0xA7,0x2D,0x40,0xEF,
+    0xE1,0xC2,0xE7,0x64,0x42,0x4D];
+pub const D12_MSG4_SIG : [u8;9] = [0x48,0xAF,0x74,0xA0,0xA4,0x47,0x7F,0xED,0xA0];
+pub const D12_MSG2_SIG_STAT : [u8;47] = [0x58,0x2B,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x77,0xA4,
+    0x00,0x03,0xA6,0x35,0x9D,0xFB,0xFC,0xF5,0x4E,0x41,0x40];
+pub const D12_MSG3_SIG_STAT : [u8;78] = [0x58,0x4C,0x01,0xF9,0xE4,0x8D,0xFA,0x52,0xE8,0xC6,0x43,0x75,
+    0x7A,0x02,0x25,0x74,0x1F,0x2E,0xCE,0xBA,0x2F,0xD7,0xE7,0xE1,
+    0xF5,0xB0,0x9C,0x0C,0x11,0x84,0xAE,0xCC,0x61,0x63,0xA0,0x6D,
+    0x93,0xDC,0xA2,0x99,0x55,0x17,0x60,0x06,0xBF,0x9F,0xA4,0xBD,
+    0x4B,0x29,0x8C,0x45,0xBC,0x86,0x1B,0xDA,0x04,0x25,0x4A,0xA7,
+    0xF3,0x1A,0x8D,0x33,0x43,0x34,0x49,0xAF,0xA5,0xAD,0xE1,0x3A,
+    0xD7,0x6F,0x29,0x2F,0x84,0x5E];
+pub const D12_MSG2_STAT_SIG : [u8;104] = [0x58,0x64,0x25,0x54,0x91,0xB0,0x5A,0x39,0x89,0xFF,0x2D,0x3F,
+    0xFE,0xA6,0x20,0x98,0xAA,0xB5,0x7C,0x16,0x0F,0x29,0x4E,0xD9,
+    0x48,0x01,0x8B,0x41,0x90,0xF7,0xD1,0x61,0x82,0x4E,0x6C,0x88,
+    0x77,0xC0,0x1F,0x05,0x2D,0x2A,0x11,0xC5,0x8E,0xFE,0x94,0xC7,
+    0x96,0x14,0xDC,0x51,0xFA,0x05,0x6D,0xCC,0x56,0x7A,0xAA,0x48,
+    0x70,0x5A,0x25,0x73,0xB2,0x8E,0xA7,0xAF,0x80,0x6D,0x4E,0x3E,
+    0xC7,0xD7,0xC6,0x05,0xFF,0x8F,0xEB,0xD0,0xBF,0x9D,0x17,0xCE,
+    0x53,0x48,0x95,0x14,0x3B,0x24,0xF2,0xD0,0x23,0x83,0x1B,0x6E,
+    0x3B,0xA0,0xB6,0x53,0x43,0x5D,0x41,0x40];
+pub const D12_MSG3_STAT_SIG : [u8;20] = [0x53,0x69,0x74,0x94,0xCB,0xD2,0xCC,0xBD,0x60,0x42,0xE3,0x58,
+    0x2A,0xBA,0x4A,0x35,0xCA,0x8D,0x61,0x9F];
+pub const D12_MSG2_P256_STAT : [u8;47] = [0x58,0x2B,0x41,0x97,0x01,0xD7,0xF0,0x0A,0x26,0xC2,0xDC,0x58,
+    0x7A,0x36,0xDD,0x75,0x25,0x49,0xF3,0x37,0x63,0xC8,0x93,0x42,
+    0x2C,0x8E,0xA0,0xF9,0x55,0xA1,0x3A,0x4F,0xF5,0xD5,0x06,0x4B,
+    0x5D,0x74,0x4D,0x09,0xC2,0x44,0x28,0x26,0x4D,0x41,0x40];
+pub const D12_MSG3_P256_STAT : [u8;20] = [0x53,0xCC,0xC5,0xD1,0x69,0xA8,0xD6,0x4C,0x04,0x64,0x20,0x63,
+    0x24,0x95,0x83,0xC1,0x32,0x49,0xB9,0xD6];
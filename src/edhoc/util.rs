@@ -1,111 +1,226 @@
 use aes::Aes128;
-use x25519_dalek_ng::{PublicKey};
 
-use alloc::{string::String, vec::Vec};
+use alloc::vec::Vec;
 use ccm::{
-    aead::{generic_array::GenericArray, Aead, NewAead, Payload},
-    consts::{U13, U8},
+    aead::{Aead, KeyInit, Payload},
+    consts::{U13, U16, U8},
     Ccm,
 };
+use aes_gcm::{Aes128Gcm, Aes256Gcm};
+use chacha20poly1305::ChaCha20Poly1305;
 use digest::{FixedOutput, Input};
 use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
 use serde_bytes::{ByteBuf, Bytes};
-use sha2::Sha256;
-use super::{cose, error::Error, Result};
+use sha2::{Sha256, Sha384};
+use sha3::{
+    digest::{ExtendableOutput, Update, XofReader},
+    CShake256, CShake256Core, Shake256,
+};
+use super::{
+    cose,
+    error::{Error, ErrorMessage},
+    keys::{self, SigningKey},
+    suite::{Aead as AeadAlgorithm, Hash, SignatureAlgorithm},
+    Result,
+};
 use crate::cbor;
 
 
 // length in bits
-pub const CCM_KEY_LEN: usize = 128;
-pub const CCM_NONCE_LEN: usize = 104;
 pub const SALT_LENGTH : usize = 64;
-pub const EDHOC_MAC :usize = 64;
-pub const HASHFUNC_OUTPUT_LEN_BITS: usize = 256;
 pub const CONNECTION_IDENTIFIER_LENGTH: usize = 8;
 
+/// Method where both parties authenticate with signature keys.
+pub const METHOD_SIGN_SIGN: u8 = 0;
+/// Method where I signs and R authenticates with a static DH key.
+pub const METHOD_SIGN_STAT: u8 = 1;
+/// Method where I authenticates with a static DH key and R signs.
+pub const METHOD_STAT_SIGN: u8 = 2;
+/// Method where both parties authenticate with static DH keys.
+pub const METHOD_STAT_STAT: u8 = 3;
+
+/// Returns whether I and R, respectively, authenticate with a signature
+/// in the given method.
+pub fn method_signers(method: u8) -> Result<(bool, bool)> {
+    match method {
+        METHOD_SIGN_SIGN => Ok((true, true)),
+        METHOD_SIGN_STAT => Ok((true, false)),
+        METHOD_STAT_SIGN => Ok((false, true)),
+        METHOD_STAT_STAT => Ok((false, false)),
+        _ => Err(Error::UnsupportedMethod),
+    }
+}
 
+/// The version of EDHOC spoken on the wire.
+///
+/// Since the version can't be told from `message_1`, R has to know in
+/// advance which one an initiator speaks, e.g. from the way it is reached.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Version {
+    /// draft-ietf-lake-edhoc-12, which devices in the field still run.
+    Draft12,
+    /// The final RFC 9528.
+    Rfc9528,
+}
+
+
+
+/// The ERR_CODE reserved for protocols built on EDHOC.
+pub const ERR_CODE_SUCCESS: isize = 0;
+/// The ERR_CODE of error messages with a text diagnostic.
+pub const ERR_CODE_UNSPECIFIED: isize = 1;
+/// The ERR_CODE of the error message R sends when it doesn't support the
+/// suite selected by I.
+pub const ERR_CODE_WRONG_SUITE: isize = 2;
+
+/// A list of cipher suites as it is sent in `SUITES_I` and `SUITES_R`,
+/// which is a single integer if there is only one suite, and an array
+/// otherwise.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawSuites {
+    One(u8),
+    Many(Vec<u8>),
+}
+
+impl From<&[u8]> for RawSuites {
+    fn from(suites: &[u8]) -> RawSuites {
+        match suites {
+            [suite] => RawSuites::One(*suite),
+            _ => RawSuites::Many(suites.to_vec()),
+        }
+    }
+}
+
+impl From<RawSuites> for Vec<u8> {
+    fn from(suites: RawSuites) -> Vec<u8> {
+        match suites {
+            RawSuites::One(suite) => vec![suite],
+            RawSuites::Many(suites) => suites,
+        }
+    }
+}
+
+/// A connection identifier or key ID as it is sent on the wire.
+///
+/// Identifiers are byte strings, but the ones consisting of the one byte
+/// encoding of an integer in -24..=23 are sent as that integer instead.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawIdentifier {
+    Int(i64),
+    Bytes(ByteBuf),
+}
+
+impl From<&[u8]> for RawIdentifier {
+    fn from(id: &[u8]) -> RawIdentifier {
+        match id {
+            [byte @ 0x00..=0x17] => RawIdentifier::Int(i64::from(*byte)),
+            [byte @ 0x20..=0x37] => RawIdentifier::Int(-1 - i64::from(byte - 0x20)),
+            _ => RawIdentifier::Bytes(ByteBuf::from(id.to_vec())),
+        }
+    }
+}
+
+impl RawIdentifier {
+    /// Returns the wire format of the identifier in the given version, where
+    /// draft-12 always sends a byte string.
+    fn new(version: Version, id: &[u8]) -> RawIdentifier {
+        match version {
+            Version::Draft12 => RawIdentifier::Bytes(ByteBuf::from(id.to_vec())),
+            Version::Rfc9528 => RawIdentifier::from(id),
+        }
+    }
+
+    /// Returns the byte string of the identifier, which for an integer is
+    /// its CBOR encoding.
+    fn into_bytes(self) -> Result<Vec<u8>> {
+        match self {
+            RawIdentifier::Int(int) => Ok(cbor::encode(int)?),
+            RawIdentifier::Bytes(bytes) => Ok(bytes.into_vec()),
+        }
+    }
+}
 
 /// EDHOC `message_1`.
 #[derive(Debug, PartialEq)]
 pub struct Message1 {
     pub method: u8,
-    pub suite: u8,
+    /// `SUITES_I`, in order of I's preference and ending with the selected
+    /// suite.
+    pub suites_i: Vec<u8>,
     pub pub_ek_i: Vec<u8>,
     pub c_i : Vec<u8>,
     pub ead_1: Option<Vec<u8>>,
 }
 
 /// Serializes EDHOC `message_1`.
-pub fn serialize_message_1(msg: &Message1) -> Result<Vec<u8>> {
+pub fn serialize_message_1(version: Version, msg: &Message1) -> Result<Vec<u8>> {
     // Pack the data into a structure that nicely serializes almost into
     // what we want to have as the actual bytes for the EDHOC message
-
-    match &msg.ead_1 { 
-        Some(ead)=>  {
-            let ead_cbor = serialize_ead(ead)?;
-          //  let ead = &msg.ead.as_ref().unwrap();
-            let raw_msg  = (
-                msg.method,
-                msg.suite,
-                Bytes::new(&msg.pub_ek_i),
-                Bytes::new(&msg.c_i),
-                Bytes::new(&ead_cbor),
-            );
-    
-            Ok(cbor::encode_sequence(raw_msg)?)
-        },
-        
-        None => {
-        let raw_msg  = (
-            msg.method,
-            msg.suite,
-            Bytes::new(&msg.pub_ek_i),
-            Bytes::new(&msg.c_i),
-        );
-        Ok(cbor::encode_sequence(raw_msg)?)}
+    let raw_msg = (
+        msg.method,
+        RawSuites::from(&msg.suites_i[..]),
+        Bytes::new(&msg.pub_ek_i),
+        RawIdentifier::new(version, &msg.c_i),
+    );
+    let mut msg_1 = cbor::encode_sequence(raw_msg)?;
+    match (version, &msg.ead_1) {
+        // draft-12 wraps EAD_1 in a byte string
+        (Version::Draft12, Some(ead)) => {
+            msg_1.extend(cbor::encode(Bytes::new(&serialize_ead(ead)?))?)
+        }
+        // while it follows as items of its own after that
+        _ => msg_1.extend(serialize_optional_ead(&msg.ead_1)?),
     }
+
+    Ok(msg_1)
 }
 
 /// Deserializes EDHOC `message_1`, first it tries to serialize with ead, and then without
-pub fn deserialize_message_1(msg: &[u8]) -> Result<Message1> {
+pub fn deserialize_message_1(version: Version, msg: &[u8]) -> Result<Message1> {
     // Try to deserialize into our raw message format
     let mut temp = Vec::with_capacity(msg.len() + 1);
-        
-
-    
-        match cbor::decode_sequence(msg, 5, &mut temp) {
-            Ok(x) => {
-                let raw_msg : (u8, u8, ByteBuf, ByteBuf, ByteBuf) = x;
-                let ead_1 = deserialize_ead(&raw_msg.4.into_vec())?;
-                Ok(Message1 {
-                    method: raw_msg.0,
-                    suite: raw_msg.1,
-                    pub_ek_i: raw_msg.2.into_vec(),
-                    c_i : raw_msg.3.into_vec(),
-                    ead_1: Some(ead_1),
-                })
-            }
-            _ => {
-                let mut temp = Vec::with_capacity(msg.len() + 1);
-                let raw_msg : (u8, u8, ByteBuf, ByteBuf)= cbor::decode_sequence(msg, 4, &mut temp)?;
-
-                Ok(Message1 {
-                    method: raw_msg.0,
-                    suite: raw_msg.1,
-                    pub_ek_i: raw_msg.2.into_vec(),
-                    c_i : raw_msg.3.into_vec(),
-                    ead_1: None,
-                })
-
-            }
+    let with_ead = match version {
+        Version::Draft12 => cbor::decode_sequence(msg, 5, &mut temp).map(
+            |raw_msg: (u8, RawSuites, ByteBuf, RawIdentifier, ByteBuf)| {
+                let (method, suites_i, pub_ek_i, c_i, ead_1) = raw_msg;
+                (method, suites_i, pub_ek_i, c_i, deserialize_ead(&ead_1))
+            },
+        ),
+        Version::Rfc9528 => cbor::decode_sequence(msg, 6, &mut temp).map(
+            |raw_msg: (u8, RawSuites, ByteBuf, RawIdentifier, u8, ByteBuf)| {
+                let (method, suites_i, pub_ek_i, c_i, _, ead_1) = raw_msg;
+                (method, suites_i, pub_ek_i, c_i, Ok(ead_1.into_vec()))
+            },
+        ),
+    };
+    match with_ead {
+        Ok((method, suites_i, pub_ek_i, c_i, ead_1)) => {
+            Ok(Message1 {
+                method,
+                suites_i: suites_i.into(),
+                pub_ek_i: pub_ek_i.into_vec(),
+                c_i : c_i.into_bytes()?,
+                ead_1: Some(ead_1?),
+            })
         }
-    
-
-
-
-
+        _ => {
+            let mut temp = Vec::with_capacity(msg.len() + 1);
+            let raw_msg : (u8, RawSuites, ByteBuf, RawIdentifier) = cbor::decode_sequence(msg, 4, &mut temp)?;
+
+            Ok(Message1 {
+                method: raw_msg.0,
+                suites_i: raw_msg.1.into(),
+                pub_ek_i: raw_msg.2.into_vec(),
+                c_i : raw_msg.3.into_bytes()?,
+                ead_1: None,
+            })
+        }
+    }
 }
-/// Serializes EDHOC `message_1`.
+/// Serializes EAD, which is a single item with label 1.
 pub fn serialize_ead(ead: &[u8]) -> Result<Vec<u8>> {
     // Pack the data into a structure that nicely serializes almost into
     // what we want to have as the actual bytes for the EDHOC message
@@ -117,7 +232,16 @@ pub fn serialize_ead(ead: &[u8]) -> Result<Vec<u8>> {
     Ok(cbor::encode_sequence(ead_tup)?)
 
 }
-/// Deserializes EDHOC `message_1`.
+
+/// Serializes optional EAD, which is nothing at all if there is none.
+pub fn serialize_optional_ead(ead: &Option<Vec<u8>>) -> Result<Vec<u8>> {
+    match ead {
+        Some(ead) => serialize_ead(ead),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Deserializes EAD.
 pub fn deserialize_ead(ead: &[u8]) -> Result<Vec<u8>> {
     let mut temp = Vec::with_capacity(ead.len() + 1);
     // Try to deserialize into our raw message format
@@ -131,11 +255,13 @@ pub fn deserialize_ead(ead: &[u8]) -> Result<Vec<u8>> {
 }
 
 /// EDHOC `message_2`.
-/// * 
+/// * `ephemeral_key_r` - G_Y, which is sent in front of `CIPHERTEXT_2`.
+/// * `c_r` - C_R, which draft-12 sends after the ciphertext, while it is
+///   part of `PLAINTEXT_2` in RFC 9528.
 #[derive(Debug, PartialEq)]
 pub struct Message2 {
     pub ephemeral_key_r: Vec<u8>,
-    pub c_r: Vec<u8>,
+    pub c_r: Option<Vec<u8>>,
     pub ciphertext_2: Vec<u8>,
 }
 
@@ -143,49 +269,189 @@ pub struct Message2 {
 pub fn serialize_message_2(msg: &Message2) -> Result<Vec<u8>> {
     let pubk_and_ciphertext = [&msg.ephemeral_key_r[..], &msg.ciphertext_2[..]].concat();
 
-let encoded = (
-    Bytes::new(&pubk_and_ciphertext),
-    Bytes::new(&msg.c_r),
-
-
-);
-    Ok(cbor::encode_sequence(encoded)?)
+    match &msg.c_r {
+        Some(c_r) => Ok(cbor::encode_sequence((
+            Bytes::new(&pubk_and_ciphertext),
+            Bytes::new(c_r),
+        ))?),
+        None => Ok(cbor::encode(Bytes::new(&pubk_and_ciphertext))?),
+    }
 }
 
-/// Deserializes EDHOC `message_2`.
-pub fn deserialize_message_2(msg: &[u8]) -> Result<Message2> { //Result<Message2>
-    let mut temp = Vec::with_capacity(msg.len() + 1);
-    // First, attempt to decode the variant without c_u
-    let (key_and_cipher2,c_r, ) = cbor::decode_sequence::<(ByteBuf, ByteBuf)>(msg, 2, &mut temp)?;
-
-            
+/// Deserializes EDHOC `message_2`, where the ephemeral key of R takes up
+/// the first `key_length` bytes, as given by the curve of the suite.
+pub fn deserialize_message_2(
+    version: Version,
+    msg: &[u8],
+    key_length: usize,
+) -> Result<Message2> {
+    let (key_and_cipher2, c_r) = match version {
+        Version::Draft12 => {
+            let mut temp = Vec::with_capacity(msg.len() + 1);
+            let (key_and_cipher2, c_r) =
+                cbor::decode_sequence::<(ByteBuf, ByteBuf)>(msg, 2, &mut temp)?;
+            (key_and_cipher2, Some(c_r.into_vec()))
+        }
+        Version::Rfc9528 => (cbor::decode::<ByteBuf>(msg)?, None),
+    };
 
-    let ephemeral_key_r = &key_and_cipher2[..32];
-    let ciphertext2 = &key_and_cipher2[32..];
+    if key_and_cipher2.len() < key_length {
+        return Err(Error::InvalidKey);
+    }
 
+    let ephemeral_key_r = &key_and_cipher2[..key_length];
+    let ciphertext2 = &key_and_cipher2[key_length..];
 
     Ok(Message2 {
         ephemeral_key_r: ephemeral_key_r.to_vec(),
-        c_r: c_r.to_vec(),
+        c_r,
         ciphertext_2: ciphertext2.to_vec(),
         })
+}
 
+/// A pseudorandom key, ready for HKDF-Expand with the hash function it was
+/// extracted with.
+#[derive(Clone)]
+pub struct Prk {
+    hash: Hash,
+    prk: Vec<u8>,
+}
 
-    
+impl Prk {
+    /// Returns the PRK with the given bytes, which is the output of
+    /// `EDHOC_KDF` for `PRK_out` and `PRK_exporter`.
+    pub fn from_bytes(hash: Hash, prk: &[u8]) -> Result<Prk> {
+        // The length is the only thing that can be wrong
+        if prk.len() != hash.output_length() {
+            return Err(Error::Hkdf(hkdf::InvalidLength));
+        }
+
+        Ok(Prk { hash, prk: prk.to_vec() })
+    }
+
+    /// Expands the PRK to fill `okm`.
+    pub fn expand(&self, info: &[u8], okm: &mut [u8]) -> Result<()> {
+        // The length was checked on construction
+        let invalid = |_| Error::Hkdf(hkdf::InvalidLength);
+        match self.hash {
+            Hash::Sha256 => Hkdf::<Sha256>::from_prk(&self.prk)
+                .map_err(invalid)?
+                .expand(info, okm)?,
+            Hash::Sha384 => Hkdf::<Sha384>::from_prk(&self.prk)
+                .map_err(invalid)?
+                .expand(info, okm)?,
+            Hash::Shake256 => kmac256(&self.prk, info, okm),
+        }
+        Ok(())
+    }
+
+    /// Returns the output length in bytes of the hash function.
+    pub fn hash_length(&self) -> usize {
+        self.hash.output_length()
+    }
+
+    /// Returns the bytes of the PRK.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.prk
+    }
 }
 
 // derive_prk
 //deriving PRK's from some salt, and a key (shared key)
 pub fn extract_prk(
+    hash: Hash,
     salt: Option<&[u8]>, 
     ikm: &[u8]
-) -> Result<(Vec<u8>, Hkdf<Sha256>)> {
+) -> Result<(Vec<u8>, Prk)> {
     // This is the extract step, resulting in the pseudorandom key (PRK)
-    let (prk, hkdf) = Hkdf::<Sha256>::extract(salt, ikm);
-    let prk_array = prk.to_vec();
+    let prk = match hash {
+        Hash::Sha256 => Hkdf::<Sha256>::extract(salt, ikm).0.to_vec(),
+        Hash::Sha384 => Hkdf::<Sha384>::extract(salt, ikm).0.to_vec(),
+        Hash::Shake256 => {
+            let mut prk = vec![0; hash.output_length()];
+            kmac256(salt.unwrap_or_default(), ikm, &mut prk);
+            prk
+        }
+    };
 
-    Ok((prk_array,hkdf))
+    Ok((prk.clone(), Prk { hash, prk }))
 }
+
+/// Fills `out` with KMAC256(key, data, 8 * out.len(), ""), which is both
+/// EDHOC_Extract and EDHOC_Expand for the SHAKE256 suites (NIST SP 800-185).
+fn kmac256(key: &[u8], data: &[u8], out: &mut [u8]) {
+    // The rate of cSHAKE256 in bytes, to which the key is padded
+    const RATE: usize = 136;
+
+    let mut kmac = CShake256::from_core(
+        CShake256Core::new_with_function_name(b"KMAC", b""),
+    );
+    // bytepad(encode_string(key), RATE)
+    let mut padded = left_encode(RATE);
+    padded.extend(left_encode(8 * key.len()));
+    padded.extend(key);
+    padded.resize(padded.len().div_ceil(RATE) * RATE, 0);
+    kmac.update(&padded);
+    kmac.update(data);
+    kmac.update(&right_encode(8 * out.len()));
+
+    kmac.finalize_xof().read(out);
+}
+
+/// Returns the big-endian bytes of `x` without leading zeros, keeping at
+/// least one byte.
+fn encode_bytes(x: usize) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take_while(|b| **b == 0).count();
+    bytes[skip.min(bytes.len() - 1)..].to_vec()
+}
+
+/// Returns `left_encode(x)` of NIST SP 800-185.
+fn left_encode(x: usize) -> Vec<u8> {
+    let bytes = encode_bytes(x);
+    let mut encoded = vec![bytes.len() as u8];
+    encoded.extend(bytes);
+    encoded
+}
+
+/// Returns `right_encode(x)` of NIST SP 800-185.
+fn right_encode(x: usize) -> Vec<u8> {
+    let mut encoded = encode_bytes(x);
+    encoded.push(encoded.len() as u8);
+    encoded
+}
+
+/// Extracts PRK_2e from G_XY, which RFC 9528 salts with TH_2.
+pub fn extract_prk_2e(
+    version: Version,
+    hash: Hash,
+    th_2: &[u8],
+    g_xy: &[u8],
+) -> Result<Prk> {
+    let salt = match version {
+        Version::Draft12 => None,
+        Version::Rfc9528 => Some(th_2),
+    };
+    let (_, prk_2e) = extract_prk(hash, salt, g_xy)?;
+
+    Ok(prk_2e)
+}
+
+/// Returns the salt to extract PRK_3e2m or PRK_4e3m with, which RFC 9528
+/// derives from the previous PRK with `label`, while draft-12 uses that PRK
+/// itself.
+pub fn derive_salt(
+    version: Version,
+    prk: &Prk,
+    label: u16,
+    th: &[u8],
+) -> Result<Vec<u8>> {
+    match version {
+        Version::Draft12 => Ok(prk.as_bytes().to_vec()),
+        Version::Rfc9528 => edhoc_kdf(prk, label, th, prk.hash_length()),
+    }
+}
+
 /// EDHOC `message_3`.
 #[derive(Debug, PartialEq)]
 pub struct Message3 {
@@ -239,15 +505,15 @@ pub fn deserialize_message_4(msg: &[u8]) -> Result<Message4> {
 pub fn build_error_message(err_msg: &str) -> Vec<u8> {
 
     // Build a tuple for the sequence of items
-    // (type, err_msg)
-    let raw_msg = (-1, err_msg);
+    // (ERR_CODE, ERR_INFO)
+    let raw_msg = (ERR_CODE_UNSPECIFIED, err_msg);
 
 
     // Try to serialize the message. If we fail for some reason, return a
     // valid, pregenerated error message saying as much.
     cbor::encode_sequence(raw_msg).unwrap_or_else(|_| {
         vec![
-            0x20, 0x78, 0x22, 0x45, 0x72, 0x72, 0x6F, 0x72, 0x20, 0x77, 0x68,
+            0x01, 0x78, 0x22, 0x45, 0x72, 0x72, 0x6F, 0x72, 0x20, 0x77, 0x68,
             0x69, 0x6C, 0x65, 0x20, 0x62, 0x75, 0x69, 0x6C, 0x64, 0x69, 0x6E,
             0x67, 0x20, 0x65, 0x72, 0x72, 0x6F, 0x72, 0x20, 0x6D, 0x65, 0x73,
             0x73, 0x61, 0x67, 0x65,
@@ -255,27 +521,79 @@ pub fn build_error_message(err_msg: &str) -> Vec<u8> {
     })
 }
 
-/// Returns the extracted message from the EDHOC error message.
-pub fn extract_error_message(msg: &[u8]) -> Result<String> {
-    // Try to deserialize into our raw message format
-    let mut temp = Vec::with_capacity(msg.len() + 1);
-    let (_, err_msg): (isize, String) =
-        cbor::decode_sequence(msg, 2, &mut temp)?;
+/// Returns the error message R sends if it doesn't support the suite
+/// selected by I, which lists the suites R does support in `SUITES_R`.
+pub fn build_suites_error_message(suites_r: &[u8]) -> Result<Vec<u8>> {
+    Ok(cbor::encode_sequence((
+        ERR_CODE_WRONG_SUITE,
+        RawSuites::from(suites_r),
+    ))?)
+}
+
+/// Returns `SUITES_R`, if the given message is an error message with
+/// ERR_CODE 2.
+pub fn extract_suites_r(msg: &[u8]) -> Option<Vec<u8>> {
+    match extract_error_message(msg) {
+        Ok(ErrorMessage::WrongSelectedSuite(suites_r)) => Some(suites_r),
+        _ => None,
+    }
+}
+
+/// Returns whether the given message starts with an integer, which is the
+/// ERR_CODE of an error message, while every other message (except for
+/// `message_1`) starts with a byte string.
+fn is_error_message(msg: &[u8]) -> bool {
+    // Major type 0 or 1, i.e. an unsigned or negative integer
+    matches!(msg.first(), Some(byte) if byte >> 5 <= 1)
+}
+
+/// Returns the ERR_CODE and the CBOR encoded ERR_INFO of an error message.
+fn split_error_message(msg: &[u8]) -> Result<(isize, &[u8])> {
+    // The ERR_CODE takes up the initial byte and, depending on its
+    // additional information, up to 8 more bytes
+    let code_length = match msg.first().map(|byte| byte & 0x1F) {
+        Some(0..=23) => 1,
+        Some(24) => 2,
+        Some(25) => 3,
+        Some(26) => 5,
+        Some(27) => 9,
+        _ => 1,
+    };
+    // Decoding fails if it's cut off or not an integer at all
+    let (code, info) = msg.split_at(code_length.min(msg.len()));
+    let code = cbor::decode(code)?;
+    // ERR_INFO has to be exactly one data item
+    cbor::decode::<serde::de::IgnoredAny>(info)?;
 
-    Ok(err_msg)
+    Ok((code, info))
+}
+
+/// Returns the EDHOC error message contained in the given message.
+pub fn extract_error_message(msg: &[u8]) -> Result<ErrorMessage> {
+    let (code, info) = split_error_message(msg)?;
+
+    Ok(match code {
+        ERR_CODE_SUCCESS => ErrorMessage::Success(info.to_vec()),
+        ERR_CODE_UNSPECIFIED => ErrorMessage::Unspecified(cbor::decode(info)?),
+        ERR_CODE_WRONG_SUITE => ErrorMessage::WrongSelectedSuite(
+            cbor::decode::<RawSuites>(info)?.into(),
+        ),
+        _ => ErrorMessage::Other(code, info.to_vec()),
+    })
 }
 
 /// Returns `Error::Edhoc` variant containing the error message, if the given
 /// message was an EDHOC error message.
 ///
 /// Use it by passing a received message to it, before trying to parse it.
+/// A message that starts like an error message but is malformed results in
+/// `Error::Cbor`.
 pub fn fail_on_error_message(msg: &[u8]) -> Result<()> {
-    match extract_error_message(msg) {
-        // If we succeed, it really is an error message
-        Ok(err_msg) => Err(Error::Edhoc(err_msg)),
-        // If not, then we don't have an error message
-        Err(_) => Ok(()),
+    if !is_error_message(msg) {
+        return Ok(());
     }
+
+    Err(Error::Edhoc(extract_error_message(msg)?))
 }
 /*/// Simple prk generation function
 
@@ -290,78 +608,251 @@ pub fn HKDFextract(
 
     Ok((material,  PRK))
 }*/
-/// The `EDHOC-Key-Derivation` function.
+// The labels of `EDHOC_KDF` that EDHOC itself uses
+pub const KEYSTREAM_2: u16 = 0;
+pub const SALT_3E2M: u16 = 1;
+pub const MAC_2: u16 = 2;
+pub const K_3: u16 = 3;
+pub const IV_3: u16 = 4;
+pub const SALT_4E3M: u16 = 5;
+pub const MAC_3: u16 = 6;
+pub const PRK_OUT: u16 = 7;
+pub const K_4: u16 = 8;
+pub const IV_4: u16 = 9;
+pub const PRK_EXPORTER: u16 = 10;
+pub const KEY_UPDATE: u16 = 11;
+
+// The labels of `EDHOC_Exporter` for the OSCORE security context
+pub const OSCORE_MASTER_SECRET: u16 = 0;
+pub const OSCORE_MASTER_SALT: u16 = 1;
+
+/// The `EDHOC_KDF` function.
 ///
 /// # Arguments
-/// * `algorithm_id` - The algorithm name, e.g. "IV-GENERATION" or COSE number
-///   e.g. "10" for AES-CCM-16-64-128.
-/// * `key_data_length` - The desired key length in bits.
-/// * `other` - Typically a transcript hash.
 /// * `prk` - The prk to use as input keying material.
+/// * `label` - The integer identifying what is derived.
+/// * `context` - Typically a transcript hash.
+/// * `key_data_length` - The desired key length in bytes.
 pub fn edhoc_kdf(
-    prk: &Hkdf<Sha256>,
-    th: &[u8],
-    label: &str,
+    prk: &Prk,
+    label: u16,
     context: &[u8],
     key_data_length: usize,
-    
 ) -> Result<Vec<u8>> {
-    // We use the ECDH shared secret as input keying material
-
-    // For the Expand step, take the COSE_KDF_Context structure as info
-    let info = (
+    // For the Expand step, take the CBOR sequence of the label, the context
+    // and the length as info
+    let info = cbor::encode_sequence((
         label,
         Bytes::new(context),
         key_data_length,
-    );
-    let mut seq = th.to_vec();
-    let info_encoded =  cbor::encode_sequence(info)?;
-    seq.extend(&info_encoded);
-    
+    ))?;
 
     // Expand the PRK to the desired length output keying material (OKM)
     let mut okm = vec![0; key_data_length];
 
-    prk.expand(&seq, &mut okm)?;
+    prk.expand(&info, &mut okm)?;
+
+    Ok(okm)
+}
+
+/// The `EDHOC-KDF` function of draft-12, which takes the transcript hash
+/// apart from the context and identifies what is derived by a text label.
+///
+/// # Arguments
+/// * `prk` - The prk to use as input keying material.
+/// * `th` - The transcript hash.
+/// * `label` - The text identifying what is derived.
+/// * `context` - The MAC context, or empty.
+/// * `key_data_length` - The desired key length in bytes.
+pub fn edhoc_kdf_draft12(
+    prk: &Prk,
+    th: &[u8],
+    label: &str,
+    context: &[u8],
+    key_data_length: usize,
+) -> Result<Vec<u8>> {
+    // The info is the CBOR sequence of the transcript hash, the label, the
+    // context and the length
+    let mut info = cbor::encode(Bytes::new(th))?;
+    info.extend(cbor::encode_sequence((
+        label,
+        Bytes::new(context),
+        key_data_length,
+    ))?);
 
+    let mut okm = vec![0; key_data_length];
 
+    prk.expand(&info, &mut okm)?;
 
     Ok(okm)
 }
 
+/// Returns the draft-12 text label standing in for the given integer label.
+///
+/// The salts, `PRK_out` and `PRK_exporter` have no counterpart in draft-12,
+/// so they can't be derived with it.
+fn draft12_label(label: u16) -> Result<&'static str> {
+    match label {
+        KEYSTREAM_2 => Ok("KEYSTREAM_2"),
+        MAC_2 => Ok("MAC_2"),
+        K_3 => Ok("K_3"),
+        IV_3 => Ok("IV_3"),
+        MAC_3 => Ok("MAC_3"),
+        K_4 => Ok("EDHOC_K_4"),
+        IV_4 => Ok("EDHOC_IV_4"),
+        _ => Err(Error::UnsupportedLabel),
+    }
+}
+
+/// Derives `KEYSTREAM_2`, `K_3`/`IV_3` or `K_4`/`IV_4` from the transcript
+/// hash, with the KDF of the given version.
+pub fn edhoc_kdf_th(
+    version: Version,
+    prk: &Prk,
+    label: u16,
+    th: &[u8],
+    key_data_length: usize,
+) -> Result<Vec<u8>> {
+    match version {
+        Version::Draft12 => edhoc_kdf_draft12(
+            prk,
+            th,
+            draft12_label(label)?,
+            b"",
+            key_data_length,
+        ),
+        Version::Rfc9528 => edhoc_kdf(prk, label, th, key_data_length),
+    }
+}
+
+/// The data authenticated by `MAC_2` and `MAC_3`.
+pub struct MacContext<'a> {
+    pub version: Version,
+    /// `C_R`, which is only part of `context_2` in RFC 9528.
+    pub c_r: Option<&'a [u8]>,
+    pub id_cred_x: &'a [u8],
+    pub th: &'a [u8],
+    pub cred_x: &'a [u8],
+    pub ead: &'a Option<Vec<u8>>,
+}
+
+impl MacContext<'_> {
+    /// Returns `context_2` or `context_3`, the CBOR sequence of all items.
+    ///
+    /// draft-12 passes the transcript hash to the KDF on its own, and leaves
+    /// out `C_R`.
+    fn encode(&self) -> Result<Vec<u8>> {
+        let mut context = Vec::new();
+        match self.version {
+            Version::Draft12 => {
+                context.extend(self.id_cred_x);
+                context.extend(self.cred_x);
+            }
+            Version::Rfc9528 => {
+                if let Some(c_r) = self.c_r {
+                    context.extend(cbor::encode(RawIdentifier::from(c_r))?);
+                }
+                context.extend(self.id_cred_x);
+                context.extend(cbor::encode(Bytes::new(self.th))?);
+                context.extend(self.cred_x);
+            }
+        }
+        context.extend(self.ead_bytes()?);
+
+        Ok(context)
+    }
+
+    /// Returns the EAD as it is authenticated, which are its raw bytes in
+    /// draft-12 and its serialized items in RFC 9528.
+    fn ead_bytes(&self) -> Result<Vec<u8>> {
+        match (self.version, self.ead) {
+            (Version::Draft12, Some(ead)) => Ok(ead.clone()),
+            _ => serialize_optional_ead(self.ead),
+        }
+    }
+}
 
 ///Function for creating MAC tags for messages
 ///
 /// # Arguments
-/// * `PRK` - the prk used to create tag
+/// * `prk` - PRK_3e2m for `MAC_2`, PRK_4e3m for `MAC_3`
 /// * `maclength`  mac length given by cipher suite
-/// * `th` transcript hash
-/// * `id_cred_x` 
-/// * `cred_x` 
-/// 
-
+/// * `label` - `MAC_2` or `MAC_3`
+/// * `context` - the authenticated data
 pub fn create_mac_with_kdf(
-    prk: &Hkdf<Sha256>,
+    prk: &Prk,
     maclength: usize,
-    th: &[u8],
-    mac_identifier : &str,
-    id_cred_x : Vec<u8>,
-    cred_x : Vec<u8>,
-    ead : &Option<Vec<u8>>,
+    label: u16,
+    context: &MacContext,
 ) -> Result<Vec<u8>> {
-
-    // prepare context
-    let mut context = Vec::new();
-    context.extend(id_cred_x);
-    context.extend(cred_x);
-    match ead {
-       Some(data) => context.extend(data),
-       None => ()
+    match context.version {
+        Version::Draft12 => edhoc_kdf_draft12(
+            prk,
+            context.th,
+            draft12_label(label)?,
+            &context.encode()?,
+            maclength,
+        ),
+        Version::Rfc9528 => {
+            edhoc_kdf(prk, label, &context.encode()?, maclength)
+        }
     }
-    edhoc_kdf(prk, th, mac_identifier,&context, maclength)
+}
 
+
+/// Creates `Signature_or_MAC_i` for a party authenticating with a signature.
+///
+/// The MAC is computed with the length of the hash function output, and then
+/// signed as the payload of a `COSE_Sign1` object.
+///
+/// # Arguments
+/// * `prk` - the prk used to create the inner MAC
+/// * `label` - `MAC_2` or `MAC_3`
+/// * `context` - the authenticated data
+/// * `signing_key` - the key of the signing party
+pub fn create_signature(
+    prk: &Prk,
+    label: u16,
+    context: &MacContext,
+    signing_key: &SigningKey,
+) -> Result<Vec<u8>> {
+    let mac = create_mac_with_kdf(prk, prk.hash_length(), label, context)?;
+    let to_be_signed = cose::build_to_be_signed(
+        context.id_cred_x,
+        context.th,
+        context.cred_x,
+        &context.ead_bytes()?,
+        &mac,
+    )?;
+
+    Ok(signing_key.sign(&to_be_signed))
 }
 
+/// Verifies `Signature_or_MAC_i` of a party authenticating with a signature.
+///
+/// # Arguments
+/// * `algorithm` - the signature algorithm of the suite
+/// * `public_key` - the encoded public key of the signing party
+/// * `signature` - the received `Signature_or_MAC_i`
+pub fn verify_signature(
+    algorithm: SignatureAlgorithm,
+    prk: &Prk,
+    label: u16,
+    context: &MacContext,
+    public_key: &[u8],
+    signature: &[u8],
+) -> Result<()> {
+    let mac = create_mac_with_kdf(prk, prk.hash_length(), label, context)?;
+    let to_be_signed = cose::build_to_be_signed(
+        context.id_cred_x,
+        context.th,
+        context.cred_x,
+        &context.ead_bytes()?,
+        &mac,
+    )?;
+
+    keys::verify(algorithm, public_key, &to_be_signed, signature)
+}
 
 
 pub fn extract_expand(
@@ -389,261 +880,528 @@ pub fn xor(a : &[u8], b:&[u8]) -> Result<Vec<u8>>{
  
       Ok(c)
  }
-/// The `EDHOC-Exporter` interface.
+/// The `EDHOC_Exporter` interface.
 ///
 /// # Arguments
+/// * `prk_exporter` - PRK_exporter.
 /// * `label` - Chosen by the application.
+/// * `context` - Chosen by the application.
 /// * `length` - The length in bytes (chosen by the application).
-/// * `th_4` - TH_4.
-/// * `secret` - The ECDH shared secret to use as input keying material.
 pub fn edhoc_exporter(
-    prk_4: &Hkdf<Sha256>,
-    th_4: &[u8],
-    label: &str,
+    prk_exporter: &Prk,
+    label: u16,
     context : &[u8],
     length: usize,
-    
 ) -> Result<Vec<u8>> {
-    edhoc_kdf(prk_4,th_4,label,context,length )
+    edhoc_kdf(prk_exporter, label, context, length)
 }
 
-/// Calculates the transcript hash of the second message.
+/// Calculates the transcript hash of the second message, which is
+/// TH_2 = H(G_Y, H(message_1)) in RFC 9528, and
+/// TH_2 = H(H(message_1), G_Y, C_R) in draft-12.
 pub fn compute_th_2(
-    message_1: Vec<u8>,
+    version: Version,
+    hash: Hash,
+    responder_ephemeral_pk: &[u8],
     c_r: &[u8],
-    responder_ephemeral_pk: PublicKey,
+    message_1: &[u8],
 ) -> Result<Vec<u8>> {
+    let msg_1_hash = cbor::encode(Bytes::new(&h(hash, message_1)?))?;
+    let seq = match version {
+        Version::Draft12 => {
+            let mut seq = msg_1_hash;
+            seq.extend(cbor::encode_sequence((
+                Bytes::new(responder_ephemeral_pk),
+                Bytes::new(c_r),
+            ))?);
+            seq
+        }
+        Version::Rfc9528 => {
+            let mut seq = cbor::encode(Bytes::new(responder_ephemeral_pk))?;
+            seq.extend(msg_1_hash);
+            seq
+        }
+    };
 
-    let mut msg_1_hash = h(&message_1)?;
-    let pk_bytes = &responder_ephemeral_pk.to_bytes();
-
-    let hash_data = cbor::encode_sequence((
-        Bytes::new(pk_bytes),
-        Bytes::new(c_r),
-    ))?;
-    msg_1_hash.extend(&hash_data);
-
-
-    // Create a sequence of CBOR items from the data
-    // Return the hash of this
-    h(&msg_1_hash)
+    h(hash, &seq)
 }
 
-/// Calculates the transcript hash of the third message.
+/// Calculates the transcript hash of the third message, which is
+/// TH_3 = H(TH_2, PLAINTEXT_2, CRED_R) in RFC 9528, and
+/// TH_3 = H(TH_2, CIPHERTEXT_2) in draft-12.
 pub fn compute_th_3(
+    version: Version,
+    hash: Hash,
     th_2: &[u8],
+    plaintext_2: &[u8],
     ciphertext_2: &[u8],
+    cred_r: &[u8],
 ) -> Result<Vec<u8>> {
-    // Create a sequence of CBOR items
-    let mut seq = Vec::new();
-    // Add the items that are always present
-    seq.extend(th_2);
-    seq.extend(cbor::encode(Bytes::new(ciphertext_2))?);
+    compute_next_th(version, hash, th_2, plaintext_2, ciphertext_2, cred_r)
+}
 
-    // Return the hash of this
-    h(&seq)
+/// Calculates the final transcript hash, which is
+/// TH_4 = H(TH_3, PLAINTEXT_3, CRED_I) in RFC 9528, and
+/// TH_4 = H(TH_3, CIPHERTEXT_3) in draft-12.
+pub fn compute_th_4(
+    version: Version,
+    hash: Hash,
+    th_3: &[u8],
+    plaintext_3: &[u8],
+    ciphertext_3: &[u8],
+    cred_i: &[u8],
+) -> Result<Vec<u8>> {
+    compute_next_th(version, hash, th_3, plaintext_3, ciphertext_3, cred_i)
 }
 
-/// Calculates the final transcript hash used for the `EDHOC-Exporter`.
-pub fn compute_th_4(th_3: &[u8], ciphertext_3: &[u8]) -> Result<Vec<u8>> {
+/// Calculates the transcript hash following `th`, over the plaintext and
+/// the credential of the message in RFC 9528, and over its ciphertext in
+/// draft-12.
+fn compute_next_th(
+    version: Version,
+    hash: Hash,
+    th: &[u8],
+    plaintext: &[u8],
+    ciphertext: &[u8],
+    cred: &[u8],
+) -> Result<Vec<u8>> {
     // Create a sequence of CBOR items
-    let mut seq = Vec::new();
-    seq.extend(th_3);
-    seq.extend(cbor::encode(Bytes::new(ciphertext_3))?);
+    let mut seq = cbor::encode(Bytes::new(th))?;
+    match version {
+        Version::Draft12 => seq.extend(cbor::encode(Bytes::new(ciphertext))?),
+        Version::Rfc9528 => {
+            seq.extend(plaintext);
+            seq.extend(cred);
+        }
+    }
 
     // Return the hash of this
-    h(&seq)
+    h(hash, &seq)
 }
 
-/// Returns a CBOR bstr containing the hash of the input CBOR sequence.
-fn h(seq: &[u8]) -> Result<Vec<u8>> {
-    let mut sha256 = Sha256::default();
-    sha256.input(seq);
-    let hash: [u8; 32] = sha256.fixed_result().into();
+/// Returns the hash of the input CBOR sequence, using the hash function of
+/// the suite.
+fn h(hash: Hash, seq: &[u8]) -> Result<Vec<u8>> {
+    let hash = match hash {
+        Hash::Sha256 => {
+            let mut sha256 = Sha256::default();
+            sha256.input(seq);
+            sha256.fixed_result().to_vec()
+        }
+        Hash::Sha384 => {
+            let mut sha384 = Sha384::default();
+            sha384.input(seq);
+            sha384.fixed_result().to_vec()
+        }
+        Hash::Shake256 => {
+            let mut shake256 = Shake256::default();
+            shake256.update(seq);
+            let mut output = vec![0; hash.output_length()];
+            shake256.finalize_xof().read(&mut output);
+            output
+        }
+    };
 
-    // Return the bstr encoding
-    Ok(cbor::encode(Bytes::new(&hash))?)
+    Ok(hash)
 }
 
-/// Returns the CBOR bstr making up the plaintext of `message_i`.
-pub fn build_plaintext(kid: &[u8], mac: &[u8],ead :Option<Vec<u8>>) -> Result<Vec<u8>> {
-    match ead {
-        Some(ead) => {
-            Ok(cbor::encode_sequence((
-                Bytes::new(kid),
-                Bytes::new(mac),
-                Bytes::new(&ead),
-            ))?)
-        },
-        None =>  {
-            Ok(cbor::encode_sequence((
-                Bytes::new(kid),
-                Bytes::new(mac),
-            ))?)
+/// Returns the CBOR sequence making up `PLAINTEXT_3`, which is the `kid`
+/// standing in for ID_CRED_I, `Signature_or_MAC_3` and optionally EAD.
+///
+/// draft-12 sends all of them as byte strings, with the raw EAD.
+pub fn build_plaintext(
+    version: Version,
+    kid: &[u8],
+    mac: &[u8],
+    ead: &Option<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let mut plaintext = cbor::encode_sequence((
+        RawIdentifier::new(version, kid),
+        Bytes::new(mac),
+    ))?;
+    match (version, ead) {
+        (Version::Draft12, Some(ead)) => {
+            plaintext.extend(cbor::encode(Bytes::new(ead))?)
         }
+        _ => plaintext.extend(serialize_optional_ead(ead)?),
     }
 
+    Ok(plaintext)
+}
+
+/// Serializes a connection identifier as it is sent on the wire, e.g. in
+/// front of `message_3` when EDHOC is carried over CoAP.
+pub fn serialize_identifier(version: Version, id: &[u8]) -> Result<Vec<u8>> {
+    Ok(cbor::encode(RawIdentifier::new(version, id))?)
+}
+
+/// Returns the CBOR sequence making up `PLAINTEXT_2`, which is `C_R`
+/// followed by the same items as `PLAINTEXT_3`.
+///
+/// draft-12 sends `C_R` outside of the ciphertext instead.
+pub fn build_plaintext_2(
+    version: Version,
+    c_r: &[u8],
+    kid: &[u8],
+    mac: &[u8],
+    ead: &Option<Vec<u8>>,
+) -> Result<Vec<u8>> {
+    let mut plaintext = match version {
+        Version::Draft12 => Vec::new(),
+        Version::Rfc9528 => cbor::encode(RawIdentifier::from(c_r))?,
+    };
+    plaintext.extend(build_plaintext(version, kid, mac, ead)?);
+
+    Ok(plaintext)
 }
 
 /// Extracts and returns the `kid` and signature from the plaintext of
-/// `message_i`, which consists of the kid value, a mac, and optionally external auth data
-pub fn extract_plaintext(plaintext: Vec<u8>) -> Result<(Vec<u8>, Vec<u8>, Option<Vec<u8>>)> {
+/// `message_3`, which consists of the kid value, a mac, and optionally external auth data
+#[allow(clippy::type_complexity)]
+pub fn extract_plaintext(
+    version: Version,
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>, Option<Vec<u8>>)> {
 
     let mut temp = Vec::with_capacity(plaintext.len() + 1);
-
-    
-    match cbor::decode_sequence(&plaintext, 2, &mut temp) {
+    match cbor::decode_sequence(plaintext, 2, &mut temp) {
         Ok(tup) => {
-            let (kid,mac) : (ByteBuf, ByteBuf) = tup;
-            Ok((kid.to_vec(), mac.into_vec(), None))
+            let (kid, mac) : (RawIdentifier, ByteBuf) = tup;
+            Ok((kid.into_bytes()?, mac.into_vec(), None))
         },
+        _ if version == Version::Draft12 => {
+            let mut temp = Vec::with_capacity(plaintext.len() + 1);
+            let (kid, mac, ead) : (ByteBuf, ByteBuf, ByteBuf) = cbor::decode_sequence(plaintext, 3, &mut temp)?;
+            Ok((kid.into_vec(), mac.into_vec(), Some(ead.into_vec())))
+        }
         _=> {
-            let (kid,mac,ead) : (ByteBuf, ByteBuf, ByteBuf)= cbor::decode_sequence(&plaintext, 3, &mut temp)?;
-            Ok((kid.to_vec(), mac.into_vec(), Some(ead.into_vec())))
-
+            let mut temp = Vec::with_capacity(plaintext.len() + 1);
+            let (kid, mac, _, ead) : (RawIdentifier, ByteBuf, u8, ByteBuf) = cbor::decode_sequence(plaintext, 4, &mut temp)?;
+            Ok((kid.into_bytes()?, mac.into_vec(), Some(ead.into_vec())))
         }
     }
+}
 
-    
+/// Extracts and returns `C_R`, the `kid`, the signature or MAC and the
+/// optional EAD from `PLAINTEXT_2`.
+///
+/// `C_R` is only part of it in RFC 9528.
+#[allow(clippy::type_complexity)]
+pub fn extract_plaintext_2(
+    version: Version,
+    plaintext: &[u8],
+) -> Result<(Option<Vec<u8>>, Vec<u8>, Vec<u8>, Option<Vec<u8>>)> {
+    if version == Version::Draft12 {
+        let (kid, mac, ead) = extract_plaintext(version, plaintext)?;
+        return Ok((None, kid, mac, ead));
+    }
+
+    let mut temp = Vec::with_capacity(plaintext.len() + 1);
+    match cbor::decode_sequence(plaintext, 3, &mut temp) {
+        Ok(tup) => {
+            let (c_r, kid, mac) : (RawIdentifier, RawIdentifier, ByteBuf) = tup;
+            Ok((Some(c_r.into_bytes()?), kid.into_bytes()?, mac.into_vec(), None))
+        },
+        _=> {
+            let mut temp = Vec::with_capacity(plaintext.len() + 1);
+            let (c_r, kid, mac, _, ead) : (RawIdentifier, RawIdentifier, ByteBuf, u8, ByteBuf) = cbor::decode_sequence(plaintext, 5, &mut temp)?;
+            Ok((Some(c_r.into_bytes()?), kid.into_bytes()?, mac.into_vec(), Some(ead.into_vec())))
+        }
+    }
 }
 
-/// Encrypts and authenticates with AES-CCM-16-64-128.
+/// Encrypts and authenticates with the given AEAD algorithm.
 ///
 /// DO NOT reuse the nonce with the same key.
 pub fn aead_seal(
+    algorithm: AeadAlgorithm,
     key: &[u8],
     nonce: &[u8],
     plaintext: &[u8],
     ad: &[u8],
 ) -> Result<Vec<u8>> {
-    // Initialize CCM mode
-    let ccm: Ccm<Aes128, U8, U13> = Ccm::new(GenericArray::from_slice(key));
-
-    
+    let payload = Payload {
+        aad: ad,
+        msg: plaintext,
+    };
     // Encrypt and place ciphertext & tag in dst_out_ct
-    let dst_out_ct = ccm.encrypt(
-        GenericArray::from_slice(nonce),
-        Payload {
-            aad: ad,
-            msg: plaintext,
-        },
-    )?;
+    let dst_out_ct = match algorithm {
+        AeadAlgorithm::AesCcm16_64_128 => Ccm::<Aes128, U8, U13>::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .encrypt(nonce.into(), payload)?,
+        AeadAlgorithm::AesCcm16_128_128 => Ccm::<Aes128, U16, U13>::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .encrypt(nonce.into(), payload)?,
+        AeadAlgorithm::A128Gcm => Aes128Gcm::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .encrypt(nonce.into(), payload)?,
+        AeadAlgorithm::A256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .encrypt(nonce.into(), payload)?,
+        AeadAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .encrypt(nonce.into(), payload)?,
+    };
     Ok(dst_out_ct)
 }
 
-/// Decrypts and verifies with AES-CCM-16-64-128.
+/// Decrypts and verifies with the given AEAD algorithm.
 pub fn aead_open(
+    algorithm: AeadAlgorithm,
     key: &[u8],
     nonce: &[u8],
     ciphertext: &[u8],
     ad: &[u8],
 ) -> Result<Vec<u8>> {
-    // Initialize CCM mode
-    let ccm: Ccm<Aes128, U8, U13> = Ccm::new(GenericArray::from_slice(key));
+    let payload = Payload {
+        aad: ad,
+        msg: ciphertext,
+    };
     // Verify tag, if correct then decrypt and place plaintext in dst_out_pt
-    let dst_out_pt = ccm.decrypt(
-        GenericArray::from_slice(nonce),
-        Payload {
-            aad: ad,
-            msg: ciphertext,
-        },
-    )?;
+    let dst_out_pt = match algorithm {
+        AeadAlgorithm::AesCcm16_64_128 => Ccm::<Aes128, U8, U13>::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .decrypt(nonce.into(), payload)?,
+        AeadAlgorithm::AesCcm16_128_128 => Ccm::<Aes128, U16, U13>::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .decrypt(nonce.into(), payload)?,
+        AeadAlgorithm::A128Gcm => Aes128Gcm::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .decrypt(nonce.into(), payload)?,
+        AeadAlgorithm::A256Gcm => Aes256Gcm::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .decrypt(nonce.into(), payload)?,
+        AeadAlgorithm::ChaCha20Poly1305 => ChaCha20Poly1305::new_from_slice(key)
+            .map_err(|_| Error::Aead)?
+            .decrypt(nonce.into(), payload)?,
+    };
 
     Ok(dst_out_pt)
 }
 
 
 #[cfg(test)]
-
 mod tests {
 
 use super::super::test_vectors::*;
 use super::*;
+use super::super::suite::*;
+use super::super::error::OwnError;
 #[test]
 
 
 
 fn test_serialize_message_2() {
+    let msg2 = deserialize_message_2(Version::Rfc9528, &RFC_MSG2, 32).unwrap();
+    assert_eq!(msg2.ephemeral_key_r, RFC_G_Y.to_vec());
+    assert_eq!(msg2.c_r, None);
+    assert_eq!(msg2.ciphertext_2.len(), RFC_PLAINTEXT_2.len());
 
+    let serial = serialize_message_2(&msg2).unwrap();
 
+    assert_eq!(serial,RFC_MSG2.to_vec());
+}
+#[test]
+
+fn draft12_serialize_message_2() {
     let msg2 = Message2 {
-        ephemeral_key_r : R_EPHEMERAL_PK.to_vec(),
-        c_r : C_R.to_vec(),
-        ciphertext_2 : CIPHERTEXT_2.to_vec(),
+        ephemeral_key_r : D12_R_EPHEMERAL_PK.to_vec(),
+        c_r : Some(C_R.to_vec()),
+        ciphertext_2 : D12_CIPHERTEXT_2.to_vec(),
     };
-    
+
     let serial = serialize_message_2(&msg2).unwrap();
 
-    assert_eq!(serial,MSG2.to_vec());
+    assert_eq!(serial,D12_MSG2.to_vec());
+    assert_eq!(deserialize_message_2(Version::Draft12, &serial, 32).unwrap(), msg2);
+    // C_R can't be left out
+    assert!(deserialize_message_2(Version::Draft12, &serial[..44], 32).is_err());
 }
 #[test]
 
 fn prk_generation() {
-    let (prk_2e,_) = extract_prk(None, &SHARED_SECRET_0).unwrap();
-    assert_eq!(prk_2e,PRK2E.to_vec());
+    let th_2 = compute_th_2(Version::Rfc9528, SUITE_2.hash, &RFC_G_Y, &RFC_C_R, &RFC_MSG1).unwrap();
+    assert_eq!(th_2, RFC_TH_2.to_vec());
 
-    let (prk3e2m,_) = extract_prk(Some(&prk_2e), &SHARED_SECRET_1.to_vec()).unwrap();
+    let prk_2e = extract_prk_2e(Version::Rfc9528, SUITE_2.hash, &th_2, &RFC_G_XY).unwrap();
+    assert_eq!(prk_2e.as_bytes(),RFC_PRK_2E);
 
-    assert_eq!(prk3e2m, PRK3EM.to_vec());
+    let salt_3e2m = derive_salt(Version::Rfc9528, &prk_2e, SALT_3E2M, &th_2).unwrap();
+    let (prk_3e2m,_) = extract_prk(SUITE_2.hash, Some(&salt_3e2m), &RFC_G_RX).unwrap();
 
-    let (prk_4x3m,_) = extract_prk(Some(&prk3e2m), &SHARED_SECRET_2).unwrap();
+    assert_eq!(prk_3e2m, RFC_PRK_3E2M.to_vec());
+}
+#[test]
 
-    assert_eq!(prk_4x3m, PRK4XM.to_vec());
+fn draft12_prk_generation() {
+    // TH_2 doesn't salt PRK_2e, and each PRK salts the next one
+    let prk_2e = extract_prk_2e(Version::Draft12, SUITE_0.hash, &[], &SHARED_SECRET_0).unwrap();
+    assert_eq!(prk_2e.as_bytes(),D12_PRK2E);
+
+    let salt_3e2m = derive_salt(Version::Draft12, &prk_2e, SALT_3E2M, &[]).unwrap();
+    let (prk_3e2m,prk_3e2m_hkdf) = extract_prk(SUITE_0.hash, Some(&salt_3e2m), &D12_SHARED_SECRET_1).unwrap();
+    assert_eq!(prk_3e2m, D12_PRK3EM.to_vec());
+
+    let salt_4e3m = derive_salt(Version::Draft12, &prk_3e2m_hkdf, SALT_4E3M, &[]).unwrap();
+    let (prk_4x3m,_) = extract_prk(SUITE_0.hash, Some(&salt_4e3m), &D12_SHARED_SECRET_2).unwrap();
+    assert_eq!(prk_4x3m, D12_PRK4XM.to_vec());
 }
 
 #[test]
 
 fn mac_2() {
-    let (prk_2e,_) = extract_prk(None, &SHARED_SECRET_0).unwrap();
-
-    let (_,prk_3e2m_hkdf) = extract_prk(Some(&prk_2e), &SHARED_SECRET_1.to_vec()).unwrap();
-    let id_cred_x = cose::build_id_cred_x(&[5]).unwrap();
+    let prk_3e2m = Prk::from_bytes(SUITE_2.hash, &RFC_PRK_3E2M).unwrap();
+    let id_cred_x = cose::build_id_cred_x(Version::Rfc9528, &RFC_KID_R).unwrap();
+
+    assert_eq!(id_cred_x, [0xA1, 0x04, 0x41, 0x32]);
+
+    let context_2 = MacContext {
+        version: Version::Rfc9528,
+        c_r: Some(&RFC_C_R),
+        id_cred_x: &id_cred_x,
+        th: &RFC_TH_2,
+        cred_x: &RFC_CRED_R,
+        ead: &None,
+    };
+    let mac_2 = create_mac_with_kdf(&prk_3e2m, 
+        SUITE_2.mac_length, 
+        MAC_2, 
+        &context_2).unwrap();
 
 
-    
-    assert_eq!(id_cred_x, ID_CRED_R);
+    assert_eq!(mac_2, &RFC_MAC_2)
+}
+#[test]
 
+fn draft12_mac_2() {
+    let prk_3e2m = Prk::from_bytes(SUITE_0.hash, &D12_PRK3EM).unwrap();
+    let id_cred_x = cose::build_id_cred_x(Version::Draft12, &[5]).unwrap();
 
-    let th_2 = h(&TH_2_RAW_INPUT).unwrap();
+    assert_eq!(id_cred_x, D12_ID_CRED_R);
 
+    let th_2 = h(SUITE_0.hash, &D12_TH_2_RAW_INPUT).unwrap();
 
-    assert_eq!(&th_2, &TH_2_CBOR);
-    let mac_2 = create_mac_with_kdf(&prk_3e2m_hkdf, 
-        EDHOC_MAC /8, 
-        &th_2, 
-        "MAC_2", 
-        id_cred_x, 
-        CRED_R.to_vec(),
-        &None).unwrap();
+    assert_eq!(&th_2, &D12_TH_2_CBOR[2..]);
+    let context_2 = MacContext {
+        version: Version::Draft12,
+        c_r: Some(&C_R),
+        id_cred_x: &id_cred_x,
+        th: &th_2,
+        cred_x: &D12_CRED_R,
+        ead: &None,
+    };
+    let mac_2 = create_mac_with_kdf(&prk_3e2m,
+        SUITE_0.mac_length,
+        MAC_2,
+        &context_2).unwrap();
 
 
-    assert_eq!(mac_2, &MAC_2)
+    assert_eq!(mac_2, &D12_MAC_2)
 }
 #[test]
 
 fn plaintext() {
-    let plain = build_plaintext(&[5], &MAC_2,None).unwrap();
-    assert_eq!(plain,PLAINTEXT_2);
+    // Identifiers with a one byte CBOR integer encoding are sent as such
+    let plain = build_plaintext_2(Version::Rfc9528, &RFC_C_R, &RFC_KID_R, &RFC_MAC_2, &None).unwrap();
+    assert_eq!(plain,RFC_PLAINTEXT_2);
+    assert_eq!(
+        extract_plaintext_2(Version::Rfc9528, &plain).unwrap(),
+        (Some(RFC_C_R.to_vec()), RFC_KID_R.to_vec(), RFC_MAC_2.to_vec(), None)
+    );
+
+    // and all others as byte strings
+    let plain = build_plaintext(Version::Rfc9528, &[0x40], &RFC_MAC_2, &Some(vec![1])).unwrap();
+    assert_eq!(&plain[..2], &[0x41, 0x40]);
+    assert_eq!(
+        extract_plaintext(Version::Rfc9528, &plain).unwrap(),
+        (vec![0x40], RFC_MAC_2.to_vec(), Some(vec![1]))
+    );
 }
 #[test]
 
-fn master_secret() {
-    let (prk_2e,_) = extract_prk(None, &SHARED_SECRET_0).unwrap();
+fn draft12_plaintext() {
+    // Without C_R, and the kid is always a byte string
+    let plain = build_plaintext_2(Version::Draft12, &C_R, &[5], &D12_MAC_2, &None).unwrap();
+    assert_eq!(plain,D12_PLAINTEXT_2);
+    assert_eq!(
+        extract_plaintext_2(Version::Draft12, &plain).unwrap(),
+        (None, vec![5], D12_MAC_2.to_vec(), None)
+    );
 
-    let (prk3e2m,_) = extract_prk(Some(&prk_2e), &SHARED_SECRET_1.to_vec()).unwrap();
+    // The EAD is a byte string of its own
+    let plain = build_plaintext(Version::Draft12, &[5], &D12_MAC_2, &Some(vec![1])).unwrap();
+    assert_eq!(&plain[D12_PLAINTEXT_2.len()..], &[0x41, 0x01]);
+    assert_eq!(
+        extract_plaintext(Version::Draft12, &plain).unwrap(),
+        (vec![5], D12_MAC_2.to_vec(), Some(vec![1]))
+    );
+}
+#[test]
 
+fn master_secret() {
+    let prk_4e3m = Prk::from_bytes(SUITE_2.hash, &RFC_PRK_4E3M).unwrap();
+    let prk_out = edhoc_kdf(&prk_4e3m, PRK_OUT, &RFC_TH_4, 32).unwrap();
+    assert_eq!(prk_out, RFC_PRK_OUT.to_vec());
 
-    let (_,prk_4x3m_hkdf) = extract_prk(Some(&prk3e2m), &SHARED_SECRET_2).unwrap();
+    let prk_out = Prk::from_bytes(SUITE_2.hash, &prk_out).unwrap();
+    let prk_exporter = edhoc_kdf(&prk_out, PRK_EXPORTER, b"", 32).unwrap();
+    assert_eq!(prk_exporter, RFC_PRK_EXPORTER.to_vec());
 
+    let prk_exporter = Prk::from_bytes(SUITE_2.hash, &prk_exporter).unwrap();
     let master_secret = edhoc_exporter(
-        &prk_4x3m_hkdf,
-        &TH_4_CBOR,
+        &prk_exporter,
+        OSCORE_MASTER_SECRET,
+        b"",
+        SUITE_2.app_aead.key_length(),
+    ).unwrap();
+    let master_salt = edhoc_exporter(
+        &prk_exporter,
+        OSCORE_MASTER_SALT,
+        b"",
+        SALT_LENGTH / 8,
+    ).unwrap();
+
+    assert_eq!(master_secret,RFC_OSCORE_SECRET);
+    assert_eq!(master_salt,RFC_OSCORE_SALT);
+}
+#[test]
+
+fn draft12_master_secret() {
+    let prk_4x3m = Prk::from_bytes(SUITE_0.hash, &D12_PRK4XM).unwrap();
+
+    let master_secret = edhoc_kdf_draft12(
+        &prk_4x3m,
+        &D12_TH_4_CBOR[2..],
         "OSCORE_Master_Secret",
         b"",
-        CCM_KEY_LEN/8, //going from bits to bytes
+        SUITE_0.app_aead.key_length(),
     ).unwrap();
 
-    assert_eq!(master_secret,MASTER_SECRET);
+    assert_eq!(master_secret,D12_MASTER_SECRET);
+}
+#[test]
+
+fn draft12_cipher3() {
+    let cipher3 = aead_seal(SUITE_0.aead, &D12_K_3, &D12_IV_3, &P_3, &A_3).unwrap();
+
+
+    assert_eq!(cipher3,D12_CIPHERTEXT_3.to_vec());
+}
+#[test]
+
+fn draft12_unsupported_labels() {
+    let (_, prk) = extract_prk(Hash::Sha256, None, &SHARED_SECRET_0).unwrap();
+    for label in &[SALT_3E2M, SALT_4E3M, PRK_OUT] {
+        assert_eq!(
+            edhoc_kdf_th(Version::Draft12, &prk, *label, &[0; 32], 16),
+            Err(Error::UnsupportedLabel)
+        );
+    }
+    // In an OwnError, this becomes an error message for the other party
+    assert_eq!(
+        OwnError::from(Error::UnsupportedLabel),
+        OwnError(build_error_message("Label unsupported"))
+    );
 }
 #[test]
 
@@ -660,11 +1418,130 @@ fn test_ead() {
 }
 #[test]
 
-fn cipher3() {
-    let cipher3 = aead_seal(&K_3, &IV_3, &P_3, &A_3).unwrap();
+fn chacha_seal_open() {
+    let aead = SUITE_4.aead;
+    let key = vec![7; aead.key_length()];
+    let nonce = vec![9; aead.nonce_length()];
+
+    let mut ciphertext = aead_seal(aead, &key, &nonce, &P_3, &A_3).unwrap();
+    assert_eq!(ciphertext.len(), P_3.len() + aead.tag_length());
+    assert_eq!(aead_open(aead, &key, &nonce, &ciphertext, &A_3).unwrap(), P_3.to_vec());
+
+    ciphertext[0] ^= 1;
+    assert!(aead_open(aead, &key, &nonce, &ciphertext, &A_3).is_err());
+}
+#[test]
+
+fn sha384_transcript_hash() {
+    let th = h(Hash::Sha384, b"abc").unwrap();
+    assert_eq!(th, SHA384_ABC.to_vec());
+
+    let (prk, prk_hkdf) = extract_prk(SUITE_24.hash, None, &SHARED_SECRET_0).unwrap();
+    assert_eq!(prk.len(), 48);
+    assert_eq!(prk_hkdf.hash_length(), 48);
+}
+
+#[test]
+fn shake256_kmac() {
+    let key: Vec<u8> = (0x40..0x60).collect();
+    let data: Vec<u8> = (0x00..0xC8).collect();
+    let mut output = [0; 64];
+    kmac256(&key, &data, &mut output);
+    assert_eq!(output, KMAC256_SAMPLE5);
+
+    // KMAC is both the extract and the expand step of suite 25
+    let (prk, _) = extract_prk(SUITE_25.hash, Some(&key), &data).unwrap();
+    assert_eq!(prk, KMAC256_SAMPLE5.to_vec());
+    assert!(Prk::from_bytes(SUITE_25.hash, &key).is_err());
+    assert_eq!(h(Hash::Shake256, b"").unwrap().len(), 64);
+}
+
+#[test]
+
+fn gcm_seal_open() {
+    for aead in &[SUITE_6.aead, SUITE_24.aead] {
+        let key = vec![7; aead.key_length()];
+        let nonce = vec![9; aead.nonce_length()];
+
+        let mut ciphertext = aead_seal(*aead, &key, &nonce, &P_3, &A_3).unwrap();
+        assert_eq!(aead_open(*aead, &key, &nonce, &ciphertext, &A_3).unwrap(), P_3.to_vec());
+
+        ciphertext[0] ^= 1;
+        assert!(aead_open(*aead, &key, &nonce, &ciphertext, &A_3).is_err());
+    }
+}
+
+#[test]
+
+fn suites_encoding() {
+    // A single suite is sent as an integer, several as an array
+    let mut msg1 = deserialize_message_1(Version::Rfc9528, &MSG1).unwrap();
+    assert_eq!(msg1.suites_i, vec![SUITE_I]);
+
+    msg1.suites_i = vec![6, 2];
+    let serial = serialize_message_1(Version::Rfc9528, &msg1).unwrap();
+    assert_eq!(&serial[..4], &[msg1.method, 0x82, 0x06, 0x02]);
+    assert_eq!(deserialize_message_1(Version::Rfc9528, &serial).unwrap(), msg1);
+
+    let err = build_suites_error_message(&[2]).unwrap();
+    assert_eq!(err, [0x02, 0x02]);
+    assert_eq!(extract_suites_r(&err), Some(vec![2]));
+    assert_eq!(
+        fail_on_error_message(&err),
+        Err(Error::Edhoc(ErrorMessage::WrongSelectedSuite(vec![2])))
+    );
+    // A text error message isn't mistaken for one
+    assert_eq!(extract_suites_r(&build_error_message("2")), None);
+}
+
+#[test]
+
+fn draft12_message_1() {
+    let msg1 = deserialize_message_1(Version::Draft12, &D12_MSG1).unwrap();
+    assert_eq!(msg1.c_i, C_I.to_vec());
+    assert_eq!(serialize_message_1(Version::Draft12, &msg1).unwrap(), D12_MSG1.to_vec());
+    // RFC 9528 sends the same C_I as an integer
+    assert_eq!(serialize_message_1(Version::Rfc9528, &msg1).unwrap()[36..], [0x0C]);
+
+    // The EAD is wrapped in a byte string
+    let msg1 = Message1 { ead_1: Some(vec![1, 2]), ..msg1 };
+    let serial = serialize_message_1(Version::Draft12, &msg1).unwrap();
+    assert_eq!(serial[D12_MSG1.len()..], [0x44, 0x01, 0x42, 0x01, 0x02]);
+    assert_eq!(deserialize_message_1(Version::Draft12, &serial).unwrap(), msg1);
+}
+
+#[test]
+
+fn error_messages() {
+    // (1, "Oops")
+    let err = build_error_message("Oops");
+    assert_eq!(err, [0x01, 0x64, 0x4F, 0x6F, 0x70, 0x73]);
+    assert_eq!(
+        extract_error_message(&err).unwrap(),
+        ErrorMessage::Unspecified("Oops".into())
+    );
 
+    // Other codes keep the encoded ERR_INFO, here (0, true), (3, true) and
+    // (-1000, [1, 2])
+    assert_eq!(
+        extract_error_message(&[0x00, 0xF5]).unwrap(),
+        ErrorMessage::Success(vec![0xF5])
+    );
+    assert_eq!(extract_error_message(&[0x03, 0xF5]).unwrap().code(), 3);
+    assert_eq!(
+        extract_error_message(&[0x39, 0x03, 0xE7, 0x82, 0x01, 0x02]).unwrap(),
+        ErrorMessage::Other(-1000, vec![0x82, 0x01, 0x02])
+    );
 
-    assert_eq!(cipher3,CIPHERTEXT_3.to_vec());
+    // Regular messages, and error messages with a wrong or missing ERR_INFO
+    // aren't accepted
+    assert!(fail_on_error_message(&RFC_MSG2).is_ok());
+    assert!(extract_error_message(&RFC_MSG2).is_err());
+    assert!(extract_error_message(&[0x01, 0x01]).is_err());
+    assert!(extract_error_message(&[0x01]).is_err());
+    assert!(extract_error_message(&[0x18]).is_err());
+    assert!(extract_error_message(&[0x01, 0x61, 0x41, 0x01]).is_err());
+    assert!(fail_on_error_message(&[0x01, 0x01]).is_err());
 }
 
-}
\ No newline at end of file
+}
@@ -1,7 +1,7 @@
 //! An experimental
 //! [OSCORE](https://tools.ietf.org/html/rfc8613)
 //! implementation with
-//! [EDHOC](https://datatracker.ietf.org/doc/draft-ietf-lake-edhoc)
+//! [EDHOC](https://www.rfc-editor.org/rfc/rfc9528)
 //! key exchange, intended for embedded devices.
 //!
 //! The EDHOC implementation is based on the older
@@ -29,4 +29,14 @@ extern crate alloc;
 #[allow(clippy::unusual_byte_groupings)]
 pub mod cbor;
 
+pub mod counter;
+
 pub mod edhoc;
+
+pub mod oscore;
+
+pub mod ratchet;
+
+pub mod lorawan;
+
+pub mod schc;
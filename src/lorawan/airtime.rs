@@ -0,0 +1,359 @@
+//! The sizes of the handshake messages and the time they spend on air, for
+//! checking the airtime and duty cycle budgets of a configuration.
+//!
+//! The sizes come from the encoders that build the messages, fed with
+//! placeholders of the right lengths, so they are exact. The time on air
+//! follows the LoRa modem design guide (Semtech AN1200.13).
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use super::{error::Error, Result};
+use crate::edhoc::{
+    suite::CipherSuite,
+    util::{self, Message1, Message2, Message3, Message4, Version},
+};
+
+/// The bytes a LoRaWAN frame adds to its FRMPayload without FOpts: the
+/// MHDR, the FHDR, the FPort and the MIC.
+pub const FRAME_OVERHEAD: usize = 13;
+
+/// The preamble length of LoRaWAN, in symbols.
+pub const PREAMBLE_SYMBOLS: u64 = 8;
+
+/// The parts of a handshake configuration the message sizes depend on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HandshakeProfile {
+    pub version: Version,
+    pub method: u8,
+    /// The selected suite.
+    pub suite: CipherSuite,
+    /// The suites I prefers over the selected one, which it sends before
+    /// it in `SUITES_I`.
+    pub preferred_suites: Vec<CipherSuite>,
+    pub c_i: Vec<u8>,
+    pub c_r: Vec<u8>,
+    /// The `kid` of I, since the size of its encoding depends on the value.
+    pub kid_i: Vec<u8>,
+    /// The `kid` of R.
+    pub kid_r: Vec<u8>,
+    /// The lengths of `ead_1` to `ead_4`, if they are sent.
+    pub ead_lengths: [Option<usize>; 4],
+}
+
+impl HandshakeProfile {
+    /// Returns the sizes of the serialized messages of the handshake.
+    pub fn message_sizes(&self) -> Result<MessageSizes> {
+        let (i_signs, r_signs) = util::method_signers(self.method)?;
+        let ead = |i: usize| self.ead_lengths[i].map(|length| vec![0; length]);
+        let signature_or_mac = |signs| {
+            if signs {
+                vec![0; self.suite.signature.signature_length()]
+            } else {
+                vec![0; self.suite.mac_length]
+            }
+        };
+
+        let mut suites_i: Vec<u8> =
+            self.preferred_suites.iter().map(|suite| suite.id).collect();
+        suites_i.push(self.suite.id);
+        let message_1 = util::serialize_message_1(
+            self.version,
+            &Message1 {
+                method: self.method,
+                suites_i,
+                pub_ek_i: vec![0; self.suite.curve.public_key_length()],
+                c_i: self.c_i.clone(),
+                ead_1: ead(0),
+            },
+        )?;
+
+        // CIPHERTEXT_2 is as long as PLAINTEXT_2
+        let plaintext_2 = util::build_plaintext_2(
+            self.version,
+            &self.c_r,
+            &self.kid_r,
+            &signature_or_mac(r_signs),
+            &ead(1),
+        )?;
+        let message_2 = util::serialize_message_2(&Message2 {
+            ephemeral_key_r: vec![0; self.suite.curve.public_key_length()],
+            c_r: match self.version {
+                Version::Draft12 => Some(self.c_r.clone()),
+                Version::Rfc9528 => None,
+            },
+            ciphertext_2: plaintext_2,
+        })?;
+
+        let tag = vec![0; self.suite.aead.tag_length()];
+        let plaintext_3 = util::build_plaintext(
+            self.version,
+            &self.kid_i,
+            &signature_or_mac(i_signs),
+            &ead(2),
+        )?;
+        let message_3 = util::serialize_message_3(&Message3 {
+            ciphertext: [&plaintext_3[..], &tag[..]].concat(),
+        })?;
+
+        let plaintext_4 = util::serialize_optional_ead(&ead(3))?;
+        let message_4 = util::serialize_message_4(&Message4 {
+            ciphertext: [&plaintext_4[..], &tag[..]].concat(),
+        })?;
+
+        Ok(MessageSizes {
+            message_1: message_1.len(),
+            message_2: message_2.len(),
+            message_3: message_3.len(),
+            message_4: message_4.len(),
+        })
+    }
+}
+
+/// The sizes in bytes of the four messages of a handshake.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MessageSizes {
+    pub message_1: usize,
+    pub message_2: usize,
+    pub message_3: usize,
+    pub message_4: usize,
+}
+
+impl MessageSizes {
+    /// Returns the time on air of the uplinks of I, `message_1` and
+    /// `message_3`, each sent in a frame of its own.
+    pub fn uplink_time_on_air(&self, modulation: &Modulation) -> Duration {
+        modulation.frame_time_on_air(self.message_1, true)
+            + modulation.frame_time_on_air(self.message_3, true)
+    }
+
+    /// Returns the time on air of the downlinks of R, `message_2` and
+    /// `message_4`, which are sent without a payload CRC.
+    pub fn downlink_time_on_air(&self, modulation: &Modulation) -> Duration {
+        modulation.frame_time_on_air(self.message_2, false)
+            + modulation.frame_time_on_air(self.message_4, false)
+    }
+
+    /// Returns the size of the largest message, which has to fit the
+    /// largest payload of the data rate unless it's fragmented.
+    pub fn largest(&self) -> usize {
+        self.message_1
+            .max(self.message_2)
+            .max(self.message_3)
+            .max(self.message_4)
+    }
+}
+
+/// The LoRa modulation a frame is sent with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Modulation {
+    spreading_factor: u32,
+    bandwidth: u32,
+    coding_rate: u32,
+}
+
+impl Modulation {
+    /// Returns the modulation with the given parameters.
+    ///
+    /// # Arguments
+    /// * `spreading_factor` - The spreading factor, from 7 to 12.
+    /// * `bandwidth` - The bandwidth in Hz, e.g. 125000.
+    /// * `coding_rate` - The coding rate 4/(4 + `coding_rate`), from 1 for
+    ///   4/5 to 4 for 4/8.
+    pub fn new(
+        spreading_factor: u32,
+        bandwidth: u32,
+        coding_rate: u32,
+    ) -> Result<Modulation> {
+        if !(7..=12).contains(&spreading_factor)
+            || bandwidth == 0
+            || !(1..=4).contains(&coding_rate)
+        {
+            return Err(Error::InvalidModulation);
+        }
+
+        Ok(Modulation {
+            spreading_factor,
+            bandwidth,
+            coding_rate,
+        })
+    }
+
+    /// Returns the time on air of a LoRaWAN frame with an FRMPayload of
+    /// `payload_length` bytes.
+    pub fn frame_time_on_air(
+        &self,
+        payload_length: usize,
+        crc: bool,
+    ) -> Duration {
+        self.time_on_air(FRAME_OVERHEAD + payload_length, crc)
+    }
+
+    /// Returns the time on air of a packet of `length` bytes, with the
+    /// preamble and explicit header of LoRaWAN.
+    pub fn time_on_air(&self, length: usize, crc: bool) -> Duration {
+        let sf = i64::from(self.spreading_factor);
+        // Low data rate optimization is used once symbols take 16 ms
+        let low_data_rate = (1000 << sf) >= 16 * i64::from(self.bandwidth);
+        let bits = 8 * length as i64 - 4 * sf + 28 + if crc { 16 } else { 0 };
+        let bits_per_block = 4 * (sf - if low_data_rate { 2 } else { 0 });
+        let blocks = ((bits + bits_per_block - 1) / bits_per_block).max(0);
+        let payload_symbols =
+            8 + blocks as u64 * u64::from(self.coding_rate + 4);
+
+        // In quarter symbols, to keep the 4.25 symbols of the sync word
+        // exact
+        let quarter_symbols = 4 * (PREAMBLE_SYMBOLS + payload_symbols) + 17;
+        Duration::from_nanos(
+            quarter_symbols * (1_000_000_000 << sf)
+                / (4 * u64::from(self.bandwidth)),
+        )
+    }
+}
+
+/// Returns how long a device has to stay silent after sending for
+/// `time_on_air` under a duty cycle of `1 / inverse_duty_cycle`, e.g. 100
+/// for 1%.
+pub fn off_time(time_on_air: Duration, inverse_duty_cycle: u32) -> Duration {
+    time_on_air * inverse_duty_cycle.saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::frame::DR0_MAX_PAYLOAD;
+    use super::*;
+    use crate::edhoc::suite::{SUITE_0, SUITE_2, SUITE_6};
+
+    /// The handshake of the trace of RFC 9529 Chapter 3.
+    fn rfc9529_profile() -> HandshakeProfile {
+        HandshakeProfile {
+            version: Version::Rfc9528,
+            method: util::METHOD_STAT_STAT,
+            suite: SUITE_2,
+            preferred_suites: vec![SUITE_6],
+            c_i: vec![0x37],
+            c_r: vec![0x27],
+            kid_i: vec![0x2B],
+            kid_r: vec![0x32],
+            ead_lengths: [None; 4],
+        }
+    }
+
+    #[test]
+    fn rfc9529_sizes() {
+        // The lengths of the messages in the trace
+        assert_eq!(
+            rfc9529_profile().message_sizes().unwrap(),
+            MessageSizes {
+                message_1: 39,
+                message_2: 45,
+                message_3: 19,
+                message_4: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn draft12_sizes() {
+        // The lengths of the messages of the draft-12 signature handshake
+        let profile = HandshakeProfile {
+            version: Version::Draft12,
+            method: util::METHOD_SIGN_SIGN,
+            suite: SUITE_0,
+            preferred_suites: vec![],
+            c_i: vec![0x0C],
+            c_r: vec![0x40],
+            kid_i: vec![5],
+            kid_r: vec![0x10],
+            ead_lengths: [None; 4],
+        };
+        assert_eq!(
+            profile.message_sizes().unwrap(),
+            MessageSizes {
+                message_1: 38,
+                message_2: 104,
+                message_3: 78,
+                message_4: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn ead_sizes() {
+        let plain = rfc9529_profile().message_sizes().unwrap();
+        let sizes = HandshakeProfile {
+            ead_lengths: [Some(8), Some(30), None, Some(0)],
+            ..rfc9529_profile()
+        }
+        .message_sizes()
+        .unwrap();
+
+        // The label and the byte string header come on top of the EAD
+        assert_eq!(sizes.message_1, plain.message_1 + 1 + 1 + 8);
+        assert_eq!(sizes.message_2, plain.message_2 + 1 + 2 + 30);
+        assert_eq!(sizes.message_3, plain.message_3);
+        assert_eq!(sizes.message_4, plain.message_4 + 1 + 1);
+        // message_2 now needs fragments at DR0
+        assert!(sizes.largest() > DR0_MAX_PAYLOAD);
+
+        let profile = HandshakeProfile {
+            method: 4,
+            ..rfc9529_profile()
+        };
+        assert_eq!(profile.message_sizes(), Err(Error::UnsupportedMethod));
+    }
+
+    #[test]
+    fn time_on_air() {
+        // A full frame at DR5 and DR0 of EU868
+        let dr5 = Modulation::new(7, 125_000, 1).unwrap();
+        let dr0 = Modulation::new(12, 125_000, 1).unwrap();
+        assert_eq!(
+            dr5.frame_time_on_air(DR0_MAX_PAYLOAD, true),
+            Duration::from_micros(118_016)
+        );
+        assert_eq!(
+            dr0.frame_time_on_air(DR0_MAX_PAYLOAD, true),
+            Duration::from_micros(2_793_472)
+        );
+        // An empty packet without CRC still takes the minimum of symbols
+        assert_eq!(
+            Modulation::new(7, 500_000, 4)
+                .unwrap()
+                .time_on_air(0, false),
+            Duration::from_micros(5_184)
+        );
+
+        assert_eq!(
+            Modulation::new(6, 125_000, 1),
+            Err(Error::InvalidModulation)
+        );
+        assert_eq!(
+            Modulation::new(7, 125_000, 5),
+            Err(Error::InvalidModulation)
+        );
+    }
+
+    #[test]
+    fn duty_cycle() {
+        let sizes = rfc9529_profile().message_sizes().unwrap();
+        let dr0 = Modulation::new(12, 125_000, 1).unwrap();
+
+        // A 1% duty cycle allows 36 s per hour, which is enough for the
+        // uplinks of 8 handshakes at DR0
+        let uplink = sizes.uplink_time_on_air(&dr0);
+        assert_eq!(uplink, Duration::from_micros(4_276_224));
+        assert_eq!(
+            Duration::from_secs(36).as_micros() / uplink.as_micros(),
+            8
+        );
+        assert_eq!(
+            sizes.downlink_time_on_air(&dr0),
+            dr0.frame_time_on_air(45, false) + dr0.frame_time_on_air(9, false)
+        );
+        assert_eq!(
+            off_time(Duration::from_millis(1_500), 100),
+            Duration::from_millis(148_500)
+        );
+    }
+}
@@ -0,0 +1,612 @@
+//! A driver that runs the handshake as the initiator on a Class A end
+//! device.
+//!
+//! A Class A device only listens for downlinks in the two receive windows
+//! after each of its uplinks, RX1 opening one second and RX2 two seconds
+//! after the end of the uplink, so message_2 and message_4 can only reach
+//! it as answers to message_1 and message_3. If neither window brings an
+//! answer, the driver sends the uplink again after a backoff that doubles
+//! with every attempt, and never before the duty cycle allows. R has to
+//! answer a repeated uplink with the message it sent before, since its
+//! answer may have been lost rather than the uplink.
+//!
+//! If R rejects the suite selected in message_1, the driver sends message_1
+//! again with the most preferred suite R supports, as `PartyI::retry` does.
+//!
+//! The radio and the clock are traits, so that the same driver runs on any
+//! hardware and in simulations, such as those of the `sim` module.
+
+use alloc::vec::Vec;
+use core::time::Duration;
+
+use super::{
+    airtime::{self, Modulation},
+    error::Error,
+    Result,
+};
+use crate::edhoc::{
+    api::Msg1Sender,
+    error::{EarlyError, OwnError, OwnOrPeerError},
+    util, CipherSuite, DhSecret, PartyI, Session,
+};
+
+/// The delay from the end of an uplink to the opening of RX1.
+pub const RECEIVE_DELAY_1: Duration = Duration::from_secs(1);
+
+/// The delay from the end of an uplink to the opening of RX2.
+pub const RECEIVE_DELAY_2: Duration = Duration::from_secs(2);
+
+/// The receive windows following an uplink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    Rx1,
+    Rx2,
+}
+
+/// A monotonic clock.
+pub trait Clock {
+    /// Returns the time elapsed since some fixed point in the past.
+    fn now(&self) -> Duration;
+
+    /// Returns once `instant` is reached, or right away if it has passed.
+    fn wait_until(&mut self, instant: Duration);
+}
+
+/// The radio of a Class A device.
+pub trait Radio {
+    /// Sends `payload` as the FRMPayload of an uplink, starting now.
+    fn transmit(&mut self, payload: &[u8]);
+
+    /// Listens in a receive window opening now, and returns the FRMPayload
+    /// of the downlink received in it, if any.
+    fn receive(&mut self, window: Window) -> Option<Vec<u8>>;
+}
+
+/// The parameters of the uplinks the driver sends.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The modulation of the uplinks, which determines their time on air.
+    pub modulation: Modulation,
+    /// The inverse of the duty cycle of the sub-band, e.g. 100 for 1%.
+    pub inverse_duty_cycle: u32,
+    /// The most times each of message_1 and message_3 is sent.
+    pub max_attempts: u32,
+    /// The wait after RX2 before the first retry, which doubles with every
+    /// further retry.
+    pub backoff: Duration,
+}
+
+impl Config {
+    /// Returns the configuration for uplinks with the given modulation, in
+    /// a sub-band with a 1% duty cycle, sending each message up to 4 times
+    /// with a backoff starting at 10 seconds.
+    pub fn new(modulation: Modulation) -> Config {
+        Config {
+            modulation,
+            inverse_duty_cycle: 100,
+            max_attempts: 4,
+            backoff: Duration::from_secs(10),
+        }
+    }
+}
+
+/// Runs handshakes over a Class A radio, keeping account of the airtime
+/// used.
+pub struct Driver<C: Clock, R: Radio> {
+    clock: C,
+    radio: R,
+    config: Config,
+    /// The earliest time the duty cycle allows the next uplink at.
+    next_uplink: Duration,
+    /// The total time on air of the uplinks sent.
+    airtime: Duration,
+    /// The number of uplinks sent.
+    uplinks: u32,
+}
+
+impl<C: Clock, R: Radio> Driver<C, R> {
+    /// Returns a driver that hasn't sent anything yet.
+    pub fn new(clock: C, radio: R, config: Config) -> Driver<C, R> {
+        Driver {
+            clock,
+            radio,
+            config,
+            next_uplink: Duration::from_secs(0),
+            airtime: Duration::from_secs(0),
+            uplinks: 0,
+        }
+    }
+
+    /// Returns the total time on air of the uplinks sent.
+    pub fn airtime(&self) -> Duration {
+        self.airtime
+    }
+
+    /// Returns the number of uplinks sent, including retries.
+    pub fn uplinks(&self) -> u32 {
+        self.uplinks
+    }
+
+    /// Returns the earliest time the duty cycle allows the next uplink at.
+    pub fn next_uplink(&self) -> Duration {
+        self.next_uplink
+    }
+
+    /// Returns the clock and the radio.
+    pub fn into_parts(self) -> (C, R) {
+        (self.clock, self.radio)
+    }
+
+    /// Runs a handshake as I, and returns the completed session.
+    ///
+    /// If R rejects the selected suite, message_1 is sent once more with
+    /// another suite. If we reject a message of R, the error message is sent
+    /// to R as an uplink before `Error::OwnError` is returned.
+    ///
+    /// # Arguments
+    /// * `initiator` - The state of I before sending message_1.
+    /// * `ephemeral` - Returns a fresh ephemeral key for the second
+    ///   message_1, and is only called if R rejects the selected suite.
+    /// * `method` - The EDHOC method to use.
+    /// * `suites` - The supported suites, most preferred first.
+    /// * `pub_static_r` - The public authentication key of R.
+    /// * `ead_3` - The optional EAD to send in message_3.
+    pub fn handshake<K: DhSecret, F: FnOnce() -> K>(
+        &mut self,
+        initiator: PartyI<Msg1Sender<K>>,
+        ephemeral: F,
+        method: u8,
+        suites: &[CipherSuite],
+        pub_static_r: &[u8],
+        ead_3: Option<Vec<u8>>,
+    ) -> Result<Session> {
+        let (msg_1, mut msg2_receiver) = initiator
+            .generate_message_1(method, suites)
+            .map_err(|EarlyError(e)| Error::Edhoc(e))?;
+        let mut msg_2 = self.exchange(&msg_1)?;
+        if util::extract_suites_r(&msg_2).is_some() {
+            let (msg_1, retry_receiver) = msg2_receiver
+                .retry(&msg_2, ephemeral())
+                .map_err(|EarlyError(e)| Error::Edhoc(e))?;
+            msg2_receiver = retry_receiver;
+            msg_2 = self.exchange(&msg_1)?;
+        }
+
+        let (_kid_r, _c_r, msg2_verifier) = msg2_receiver
+            .unpack_message_2_return_kid(msg_2)
+            .map_err(|e| self.abort(e))?;
+        let msg3_sender = msg2_verifier
+            .verify_message_2(pub_static_r)
+            .map_err(|OwnError(b)| self.abort(OwnOrPeerError::OwnError(b)))?;
+        let (msg4_receiver, msg_3) = msg3_sender
+            .generate_message_3(ead_3)
+            .map_err(|OwnError(b)| self.abort(OwnOrPeerError::OwnError(b)))?;
+        let msg_4 = self.exchange(&msg_3)?;
+
+        let (session, _ead_4) = msg4_receiver
+            .handle_message_4_session(msg_4)
+            .map_err(|e| self.abort(e))?;
+
+        Ok(session)
+    }
+
+    /// Sends an uplink until a downlink answers it in RX1 or RX2, and
+    /// returns that downlink.
+    fn exchange(&mut self, uplink: &[u8]) -> Result<Vec<u8>> {
+        let mut backoff = self.config.backoff;
+        for attempt in 0..self.config.max_attempts {
+            if attempt > 0 {
+                let retry = self.clock.now() + backoff;
+                self.clock.wait_until(retry);
+                backoff = backoff.saturating_mul(2);
+            }
+            let end = self.send(uplink);
+            for (window, delay) in [
+                (Window::Rx1, RECEIVE_DELAY_1),
+                (Window::Rx2, RECEIVE_DELAY_2),
+            ] {
+                self.clock.wait_until(end + delay);
+                if let Some(downlink) = self.radio.receive(window) {
+                    return Ok(downlink);
+                }
+            }
+        }
+
+        Err(Error::NoResponse)
+    }
+
+    /// Sends an uplink as soon as the duty cycle allows, and returns the
+    /// time its transmission ends at.
+    fn send(&mut self, uplink: &[u8]) -> Duration {
+        self.clock.wait_until(self.next_uplink);
+        let time_on_air =
+            self.config.modulation.frame_time_on_air(uplink.len(), true);
+        let end = self.clock.now() + time_on_air;
+        self.radio.transmit(uplink);
+        self.clock.wait_until(end);
+
+        self.airtime += time_on_air;
+        self.uplinks += 1;
+        self.next_uplink = end
+            + airtime::off_time(time_on_air, self.config.inverse_duty_cycle);
+        end
+    }
+
+    /// Sends our error message to R if there is one, and returns the error
+    /// ending the handshake.
+    fn abort(&mut self, error: OwnOrPeerError) -> Error {
+        match error {
+            OwnOrPeerError::OwnError(b) => {
+                self.send(&b);
+                Error::OwnError(b)
+            }
+            OwnOrPeerError::PeerError(e) => Error::PeerError(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x25519_dalek_ng::{PublicKey, StaticSecret};
+
+    use super::super::sim::{Delivery, Network, SimClock, SimRadio};
+    use super::*;
+    use crate::edhoc::{
+        api::{Msg1Receiver, Msg3Receiver},
+        error::{Error as EdhocError, ErrorMessage},
+        keys::SigningKey,
+        suite::{SUITE_0, SUITE_6},
+        PartyR, Version,
+    };
+
+    const C_I: [u8; 1] = [0x37];
+    const C_R: [u8; 1] = [0x27];
+    /// The ES256 key R signs with when it only supports suite 6.
+    const R_SIGNING_KEY: [u8; 32] = [0x66; 32];
+
+    /// R behind a network server, answering duplicates of the last uplink
+    /// with the same downlink.
+    struct Server {
+        msg1_receiver: Option<PartyR<Msg1Receiver>>,
+        msg3_receiver: Option<PartyR<Msg3Receiver>>,
+        session: Option<Session>,
+        last: Option<(Vec<u8>, Vec<u8>)>,
+        /// Whether R signs with ES256 on X25519, which only suite 6 has.
+        signs: bool,
+        /// Whether R answers everything with an error message.
+        reject: bool,
+    }
+
+    impl Server {
+        fn new(signs: bool) -> Server {
+            Server {
+                msg1_receiver: Some(Server::responder(signs)),
+                msg3_receiver: None,
+                session: None,
+                last: None,
+                signs,
+                reject: false,
+            }
+        }
+
+        fn responder(signs: bool) -> PartyR<Msg1Receiver> {
+            let r_ephemeral = StaticSecret::from([0x44; 32]);
+            if signs {
+                return PartyR::new_sig(
+                    r_ephemeral,
+                    p256::ecdsa::SigningKey::from_slice(&R_SIGNING_KEY)
+                        .unwrap(),
+                    vec![0x32],
+                    Version::Rfc9528,
+                );
+            }
+            let r_static = StaticSecret::from([0x22; 32]);
+            let r_public = PublicKey::from(&r_static);
+            PartyR::new(
+                r_ephemeral,
+                r_static,
+                r_public,
+                vec![0x32],
+                Version::Rfc9528,
+            )
+        }
+    }
+
+    impl Network for Server {
+        fn handle(&mut self, uplink: &[u8]) -> Option<Vec<u8>> {
+            if self.reject {
+                return Some(util::build_error_message("Busy"));
+            }
+            if let Some((last, answer)) = &self.last {
+                if last == uplink {
+                    return Some(answer.clone());
+                }
+            }
+            let answer = if let Some(msg1_receiver) = self.msg1_receiver.take()
+            {
+                match msg1_receiver.handle_message_1(uplink.to_vec()) {
+                    Ok((msg2_sender, _)) => {
+                        let (msg_2, msg3_receiver) = msg2_sender
+                            .generate_message_2(C_R.to_vec(), None)
+                            .unwrap();
+                        self.msg3_receiver = Some(msg3_receiver);
+                        msg_2
+                    }
+                    // A rejected message_1 is followed by another one
+                    Err(OwnError(err)) => {
+                        self.msg1_receiver = Some(Server::responder(self.signs));
+                        err
+                    }
+                }
+            } else if let Some(msg3_receiver) = self.msg3_receiver.take() {
+                let (msg3_verifier, _) = msg3_receiver
+                    .unpack_message_3_return_kid(uplink.to_vec())
+                    .ok()?;
+                let i_public =
+                    PublicKey::from(&StaticSecret::from([0x11; 32]));
+                let (msg4_sender, _, _, _) = msg3_verifier
+                    .verify_message_3(i_public.as_bytes())
+                    .unwrap();
+                let (msg_4, session) =
+                    msg4_sender.generate_message_4_session(None).unwrap();
+                self.session = Some(session);
+                msg_4
+            } else {
+                return None;
+            };
+            self.last = Some((uplink.to_vec(), answer.clone()));
+
+            Some(answer)
+        }
+    }
+
+    fn initiator() -> PartyI<Msg1Sender> {
+        let i_static = StaticSecret::from([0x11; 32]);
+        let i_public = PublicKey::from(&i_static);
+        PartyI::new(
+            C_I.to_vec(),
+            None,
+            StaticSecret::from([0x33; 32]),
+            i_static,
+            i_public,
+            vec![0x2B],
+            Version::Rfc9528,
+        )
+    }
+
+    fn ephemeral() -> StaticSecret {
+        StaticSecret::from([0x35; 32])
+    }
+
+    fn sim(
+        script: &[Delivery],
+        config: Config,
+    ) -> Driver<SimClock, SimRadio<Server>> {
+        let clock = SimClock::new();
+        let radio = SimRadio::new(&clock, Server::new(false), script);
+        Driver::new(clock, radio, config)
+    }
+
+    fn r_public() -> PublicKey {
+        PublicKey::from(&StaticSecret::from([0x22; 32]))
+    }
+
+    fn dr5() -> Config {
+        Config::new(Modulation::new(7, 125_000, 1).unwrap())
+    }
+
+    /// Returns the exporter output of the server's session, to compare with
+    /// the device's.
+    fn server_export(radio: &SimRadio<Server>) -> Vec<u8> {
+        let session = radio.network().session.as_ref().unwrap();
+        session.export(32768, b"", 16).unwrap()
+    }
+
+    #[test]
+    fn handshake() {
+        let config = dr5();
+        let mut driver = sim(&[], config);
+        let session = driver
+            .handshake(
+                initiator(),
+                ephemeral,
+                util::METHOD_STAT_STAT,
+                &[SUITE_0],
+                r_public().as_bytes(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(driver.uplinks(), 2);
+        let airtime = driver.airtime();
+        let next_uplink = driver.next_uplink();
+        let (_, radio) = driver.into_parts();
+        assert_eq!(
+            session.export(32768, b"", 16).unwrap(),
+            server_export(&radio)
+        );
+
+        let (start_1, msg_1) = &radio.uplinks()[0];
+        let (start_3, msg_3) = &radio.uplinks()[1];
+        let toa_1 = config.modulation.frame_time_on_air(msg_1.len(), true);
+        let toa_3 = config.modulation.frame_time_on_air(msg_3.len(), true);
+        assert_eq!(airtime, toa_1 + toa_3);
+        assert_eq!(
+            radio.windows()[0],
+            (Window::Rx1, toa_1 + RECEIVE_DELAY_1)
+        );
+        // message_3 waits for the duty cycle, not for RX2
+        assert_eq!(radio.windows().len(), 2);
+        assert_eq!(*start_3, *start_1 + 100 * toa_1);
+        assert_eq!(
+            radio.windows()[1],
+            (Window::Rx1, *start_3 + toa_3 + RECEIVE_DELAY_1)
+        );
+        assert_eq!(next_uplink, *start_3 + 100 * toa_3);
+    }
+
+    #[test]
+    fn retries() {
+        let config = dr5();
+        let mut driver = sim(
+            &[
+                Delivery::Lost,
+                Delivery::Dropped,
+                Delivery::In(Window::Rx2),
+                Delivery::Dropped,
+                Delivery::In(Window::Rx1),
+            ],
+            config,
+        );
+        let session = driver
+            .handshake(
+                initiator(),
+                ephemeral,
+                util::METHOD_STAT_STAT,
+                &[SUITE_0],
+                r_public().as_bytes(),
+                None,
+            )
+            .unwrap();
+        assert_eq!(driver.uplinks(), 5);
+        let (_, radio) = driver.into_parts();
+        assert_eq!(
+            session.export(32768, b"", 16).unwrap(),
+            server_export(&radio)
+        );
+
+        // message_1 three times, then message_3 twice
+        let uplinks = radio.uplinks();
+        assert!(uplinks[..3].iter().all(|(_, u)| *u == uplinks[0].1));
+        assert_eq!(uplinks[3].1, uplinks[4].1);
+        // The backoff doubles after every attempt without an answer
+        let rx2 = |i: usize| radio.windows()[2 * i + 1].1;
+        assert_eq!(uplinks[1].0, rx2(0) + config.backoff);
+        assert_eq!(uplinks[2].0, rx2(1) + 2 * config.backoff);
+        assert_eq!(radio.windows()[5], (Window::Rx2, rx2(2)));
+        assert_eq!(uplinks[4].0, rx2(3) + config.backoff);
+    }
+
+    #[test]
+    fn wrong_selected_suite() {
+        let clock = SimClock::new();
+        let radio = SimRadio::new(&clock, Server::new(true), &[]);
+        let mut driver = Driver::new(clock, radio, dr5());
+        let r_public = SigningKey::from(
+            p256::ecdsa::SigningKey::from_slice(&R_SIGNING_KEY).unwrap(),
+        )
+        .public_bytes();
+
+        // R rejects suite 0, and the driver retries with suite 6
+        let session = driver
+            .handshake(
+                initiator(),
+                ephemeral,
+                util::METHOD_STAT_SIGN,
+                &[SUITE_0, SUITE_6],
+                &r_public,
+                None,
+            )
+            .unwrap();
+        assert_eq!(driver.uplinks(), 3);
+        let (_, radio) = driver.into_parts();
+        assert_eq!(
+            session.export(32768, b"", 16).unwrap(),
+            server_export(&radio)
+        );
+        let msg_1 = util::deserialize_message_1(
+            Version::Rfc9528,
+            &radio.uplinks()[1].1,
+        )
+        .unwrap();
+        assert_eq!(msg_1.suites_i, vec![0, 6]);
+
+        // Without a suite in common, the handshake ends after the rejection
+        let clock = SimClock::new();
+        let radio = SimRadio::new(&clock, Server::new(true), &[]);
+        let mut driver = Driver::new(clock, radio, dr5());
+        assert_eq!(
+            driver
+                .handshake(
+                    initiator(),
+                    ephemeral,
+                    util::METHOD_STAT_SIGN,
+                    &[SUITE_0],
+                    &r_public,
+                    None,
+                )
+                .err(),
+            Some(Error::Edhoc(EdhocError::UnsupportedSuite))
+        );
+        assert_eq!(driver.uplinks(), 1);
+    }
+
+    #[test]
+    fn no_response() {
+        let config = Config {
+            max_attempts: 3,
+            ..Config::new(Modulation::new(12, 125_000, 1).unwrap())
+        };
+        let mut driver = sim(&[Delivery::Lost; 3], config);
+        assert_eq!(
+            driver
+                .handshake(
+                    initiator(),
+                    ephemeral,
+                    util::METHOD_STAT_STAT,
+                    &[SUITE_0],
+                    r_public().as_bytes(),
+                    None,
+                )
+                .err(),
+            Some(Error::NoResponse)
+        );
+        assert_eq!(driver.uplinks(), 3);
+        let (_, radio) = driver.into_parts();
+        // At SF12, the duty cycle is longer than the backoff
+        let toa = config
+            .modulation
+            .frame_time_on_air(radio.uplinks()[0].1.len(), true);
+        assert!(99 * toa > 4 * config.backoff + RECEIVE_DELAY_2);
+        for pair in radio.uplinks().windows(2) {
+            assert_eq!(pair[1].0, pair[0].0 + 100 * toa);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        // An unknown key of R makes us send an error message
+        let mut driver = sim(&[], dr5());
+        let result = driver.handshake(
+            initiator(),
+            ephemeral,
+            util::METHOD_STAT_STAT,
+            &[SUITE_0],
+            PublicKey::from([0x55; 32]).as_bytes(),
+            None,
+        );
+        let (_, radio) = driver.into_parts();
+        match result {
+            Err(Error::OwnError(b)) => assert_eq!(radio.uplinks()[1].1, b),
+            _ => panic!("Expected an own error"),
+        }
+        assert!(radio.network().session.is_none());
+
+        // An error message of R ends the handshake without an answer
+        let mut driver = sim(&[], dr5());
+        driver.radio.network_mut().reject = true;
+        assert_eq!(
+            driver
+                .handshake(
+                    initiator(),
+                    ephemeral,
+                    util::METHOD_STAT_STAT,
+                    &[SUITE_0],
+                    r_public().as_bytes(),
+                    None,
+                )
+                .err(),
+            Some(Error::PeerError(ErrorMessage::Unspecified("Busy".into())))
+        );
+        assert_eq!(driver.uplinks(), 1);
+    }
+}
@@ -0,0 +1,123 @@
+//! The errors of the `lorawan` module.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    cbor,
+    counter::CounterError,
+    edhoc::error::{Error as EdhocError, ErrorMessage},
+};
+
+/// The catch-all error type for this module.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Wraps errors from the `cbor` module.
+    Cbor(cbor::CborError),
+    /// Wraps errors from `hkdf`.
+    Hkdf(hkdf::InvalidLength),
+    /// An exporter label the session doesn't support.
+    UnsupportedLabel,
+    /// Error using the AEAD, which for a received frame means it wasn't
+    /// authentic.
+    Aead,
+    /// A key of the wrong length.
+    InvalidKey,
+    /// A received frame counter that isn't higher than the last one.
+    CounterReuse,
+    /// All frame counters were used.
+    CounterExhausted,
+    /// All reserved frame counters were used.
+    FrameCountersNotReserved,
+    /// An MTU too small for the header of a fragment.
+    InvalidMtu,
+    /// A message that needs more fragments than can be numbered.
+    MessageTooLong,
+    /// A fragment with a malformed header, or one that doesn't match the
+    /// others of its message.
+    InvalidFragment,
+    /// A message longer than the reassembly buffer.
+    BufferFull,
+    /// An EDHOC method that doesn't exist.
+    UnsupportedMethod,
+    /// LoRa modulation parameters out of range.
+    InvalidModulation,
+    /// Wraps any other error from the `edhoc` module, such as an
+    /// unsupported suite before the handshake started.
+    Edhoc(EdhocError),
+    /// Wraps the error message we sent, after rejecting a message of the
+    /// other party.
+    OwnError(Vec<u8>),
+    /// Wraps the error message the other party sent.
+    PeerError(ErrorMessage),
+    /// No downlink answered an uplink in any attempt.
+    NoResponse,
+}
+
+impl From<ccm::aead::Error> for Error {
+    fn from(_: ccm::aead::Error) -> Error {
+        Error::Aead
+    }
+}
+
+impl From<cbor::CborError> for Error {
+    fn from(e: cbor::CborError) -> Error {
+        Error::Cbor(e)
+    }
+}
+
+impl From<hkdf::InvalidLength> for Error {
+    fn from(e: hkdf::InvalidLength) -> Error {
+        Error::Hkdf(e)
+    }
+}
+
+impl From<CounterError> for Error {
+    fn from(e: CounterError) -> Error {
+        match e {
+            CounterError::Exhausted => Error::CounterExhausted,
+            CounterError::NotReserved => Error::FrameCountersNotReserved,
+        }
+    }
+}
+
+impl From<EdhocError> for Error {
+    fn from(e: EdhocError) -> Error {
+        match e {
+            EdhocError::Cbor(e) => Error::Cbor(e),
+            EdhocError::Hkdf(e) => Error::Hkdf(e),
+            EdhocError::UnsupportedMethod => Error::UnsupportedMethod,
+            EdhocError::UnsupportedLabel => Error::UnsupportedLabel,
+            e => Error::Edhoc(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Cbor(e) => e.fmt(f),
+            Error::Hkdf(e) => e.fmt(f),
+            Error::UnsupportedLabel => write!(f, "Exporter label unsupported"),
+            Error::Aead => write!(f, "Error using AEAD"),
+            Error::InvalidKey => write!(f, "Invalid key length"),
+            Error::CounterReuse => write!(f, "Frame counter reused"),
+            Error::CounterExhausted => write!(f, "Frame counter exhausted"),
+            Error::FrameCountersNotReserved => {
+                write!(f, "No frame counters reserved")
+            }
+            Error::InvalidMtu => write!(f, "MTU too small for fragments"),
+            Error::MessageTooLong => {
+                write!(f, "Message needs too many fragments")
+            }
+            Error::InvalidFragment => write!(f, "Invalid fragment"),
+            Error::BufferFull => write!(f, "Reassembly buffer full"),
+            Error::UnsupportedMethod => write!(f, "Method unsupported"),
+            Error::InvalidModulation => write!(f, "Invalid LoRa modulation"),
+            Error::Edhoc(e) => e.fmt(f),
+            Error::OwnError(_) => write!(f, "Sent an EDHOC error message"),
+            Error::PeerError(e) => e.fmt(f),
+            Error::NoResponse => write!(f, "No downlink received"),
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Fragmentation of messages that don't fit a single frame, such as
+//! message_2 with EAD at DR0.
+//!
+//! Every fragment starts with a 2 byte header, the message tag, which
+//! tells apart the fragments of consecutive messages, and a byte with the
+//! index of the fragment in the high nibble and the index of the last one
+//! in the low nibble. Nothing else about the transport is assumed.
+
+use alloc::vec::Vec;
+
+use super::{error::Error, Result};
+
+/// The length in bytes of the header of a fragment.
+pub const HEADER_LENGTH: usize = 2;
+
+/// The most fragments a message can be split into.
+pub const MAX_FRAGMENTS: usize = 16;
+
+/// Splits a message into fragments of at most `mtu` bytes.
+///
+/// # Arguments
+/// * `tag` - The message tag, which has to differ from the one of the
+///   message before.
+/// * `message` - The message, e.g. a serialized EDHOC message.
+/// * `mtu` - The largest payload of a frame.
+pub fn fragment(tag: u8, message: &[u8], mtu: usize) -> Result<Vec<Vec<u8>>> {
+    if mtu <= HEADER_LENGTH {
+        return Err(Error::InvalidMtu);
+    }
+    let chunks: Vec<&[u8]> = if message.is_empty() {
+        vec![message]
+    } else {
+        message.chunks(mtu - HEADER_LENGTH).collect()
+    };
+    if chunks.len() > MAX_FRAGMENTS {
+        return Err(Error::MessageTooLong);
+    }
+
+    let last = chunks.len() as u8 - 1;
+    Ok(chunks
+        .iter()
+        .enumerate()
+        .map(|(index, chunk)| {
+            let mut fragment = Vec::with_capacity(HEADER_LENGTH + chunk.len());
+            fragment.push(tag);
+            fragment.push((index as u8) << 4 | last);
+            fragment.extend_from_slice(chunk);
+            fragment
+        })
+        .collect())
+}
+
+/// Reassembles the messages of a peer from their fragments, which may
+/// arrive out of order and more than once.
+///
+/// Only one message is reassembled at a time, and a fragment with another
+/// tag drops the fragments of the message before it.
+pub struct Reassembler {
+    /// The most bytes of fragment payloads buffered.
+    max_length: usize,
+    /// The tag of the message being reassembled.
+    tag: Option<u8>,
+    /// The payloads received, by index.
+    fragments: Vec<Option<Vec<u8>>>,
+    /// The number of bytes buffered.
+    length: usize,
+}
+
+impl Reassembler {
+    /// Returns a reassembler that buffers up to `max_length` bytes of a
+    /// message.
+    pub fn new(max_length: usize) -> Reassembler {
+        Reassembler {
+            max_length,
+            tag: None,
+            fragments: Vec::new(),
+            length: 0,
+        }
+    }
+
+    /// Takes in a fragment, returning the message once all of its fragments
+    /// were received.
+    ///
+    /// Duplicates of fragments already received are ignored, as are
+    /// fragments of the message that was completed last. A message that
+    /// doesn't fit the buffer is dropped, and so are its later fragments.
+    pub fn receive(&mut self, fragment: &[u8]) -> Result<Option<Vec<u8>>> {
+        if fragment.len() < HEADER_LENGTH {
+            return Err(Error::InvalidFragment);
+        }
+        let (tag, index, count) = (
+            fragment[0],
+            usize::from(fragment[1] >> 4),
+            usize::from(fragment[1] & 0x0F) + 1,
+        );
+        if index >= count {
+            return Err(Error::InvalidFragment);
+        }
+
+        if self.tag != Some(tag) {
+            self.tag = Some(tag);
+            self.fragments = vec![None; count];
+            self.length = 0;
+        } else if self.fragments.is_empty() {
+            // The message was already completed, or dropped
+            return Ok(None);
+        } else if self.fragments.len() != count {
+            return Err(Error::InvalidFragment);
+        } else if self.fragments[index].is_some() {
+            return Ok(None);
+        }
+
+        let payload = &fragment[HEADER_LENGTH..];
+        if self.length + payload.len() > self.max_length {
+            self.fragments.clear();
+            return Err(Error::BufferFull);
+        }
+        self.length += payload.len();
+        self.fragments[index] = Some(payload.to_vec());
+
+        if self.fragments.iter().any(Option::is_none) {
+            return Ok(None);
+        }
+        let message = self.fragments.drain(..).flatten().flatten().collect();
+        self.length = 0;
+
+        Ok(Some(message))
+    }
+
+    /// Returns the indexes of the fragments still missing from the message
+    /// being reassembled, for the peer to send them again.
+    pub fn missing(&self) -> Vec<u8> {
+        self.fragments
+            .iter()
+            .enumerate()
+            .filter(|(_, fragment)| fragment.is_none())
+            .map(|(index, _)| index as u8)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::frame::DR0_MAX_PAYLOAD;
+    use super::*;
+
+    fn message() -> Vec<u8> {
+        (0..120).collect()
+    }
+
+    #[test]
+    fn fragments() {
+        let fragments = fragment(7, &message(), DR0_MAX_PAYLOAD).unwrap();
+        assert_eq!(fragments.len(), 3);
+        assert!(fragments.iter().all(|f| f.len() <= DR0_MAX_PAYLOAD));
+        assert_eq!(fragments[0][..HEADER_LENGTH], [7, 0x02]);
+        assert_eq!(fragments[2][..HEADER_LENGTH], [7, 0x22]);
+
+        let mut reassembler = Reassembler::new(256);
+        assert_eq!(reassembler.receive(&fragments[0]), Ok(None));
+        assert_eq!(reassembler.receive(&fragments[1]), Ok(None));
+        assert_eq!(
+            reassembler.receive(&fragments[2]).unwrap(),
+            Some(message())
+        );
+
+        let fragments = fragment(8, b"", DR0_MAX_PAYLOAD).unwrap();
+        assert_eq!(fragments, vec![vec![8, 0x00]]);
+        assert_eq!(reassembler.receive(&fragments[0]).unwrap(), Some(vec![]));
+    }
+
+    #[test]
+    fn out_of_order() {
+        let fragments = fragment(1, &message(), 20).unwrap();
+        assert_eq!(fragments.len(), 7);
+        let mut reassembler = Reassembler::new(256);
+
+        for index in &[6, 2, 2, 0, 4] {
+            assert_eq!(reassembler.receive(&fragments[*index]), Ok(None));
+        }
+        assert_eq!(reassembler.missing(), vec![1, 3, 5]);
+        for index in &[5, 3, 0] {
+            assert_eq!(reassembler.receive(&fragments[*index]), Ok(None));
+        }
+        assert_eq!(reassembler.missing(), vec![1]);
+        assert_eq!(
+            reassembler.receive(&fragments[1]).unwrap(),
+            Some(message())
+        );
+
+        // Late duplicates of the completed message are ignored
+        assert_eq!(reassembler.receive(&fragments[4]), Ok(None));
+        assert!(reassembler.missing().is_empty());
+    }
+
+    #[test]
+    fn new_message() {
+        let first = fragment(1, &message(), 40).unwrap();
+        let second = fragment(2, &[0xAB; 50], 40).unwrap();
+        let mut reassembler = Reassembler::new(256);
+
+        reassembler.receive(&first[0]).unwrap();
+        reassembler.receive(&second[1]).unwrap();
+        assert_eq!(reassembler.missing(), vec![0]);
+        assert_eq!(
+            reassembler.receive(&second[0]).unwrap(),
+            Some(vec![0xAB; 50])
+        );
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(fragment(0, b"abc", HEADER_LENGTH), Err(Error::InvalidMtu));
+        assert_eq!(
+            fragment(0, &[0; 17], HEADER_LENGTH + 1),
+            Err(Error::MessageTooLong)
+        );
+        assert_eq!(fragment(0, &[0; 16], HEADER_LENGTH + 1).unwrap().len(), 16);
+
+        let fragments = fragment(3, &message(), 51).unwrap();
+        let mut reassembler = Reassembler::new(100);
+        reassembler.receive(&fragments[0]).unwrap();
+        reassembler.receive(&fragments[1]).unwrap();
+        assert_eq!(reassembler.receive(&fragments[2]), Err(Error::BufferFull));
+
+        let mut reassembler = Reassembler::new(100);
+        assert_eq!(reassembler.receive(&[3]), Err(Error::InvalidFragment));
+        // The index of the fragment past the last one
+        assert_eq!(
+            reassembler.receive(&[3, 0x21, 0]),
+            Err(Error::InvalidFragment)
+        );
+        reassembler.receive(&[3, 0x02, 0]).unwrap();
+        // Another number of fragments for the same message
+        assert_eq!(
+            reassembler.receive(&[3, 0x13, 0]),
+            Err(Error::InvalidFragment)
+        );
+        assert_eq!(
+            reassembler.receive(&[3, 0x33, 0]),
+            Err(Error::InvalidFragment)
+        );
+    }
+}
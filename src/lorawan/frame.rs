@@ -0,0 +1,343 @@
+//! The protection of frame payloads.
+
+use aes::Aes256;
+use alloc::vec::Vec;
+use ccm::{
+    aead::{Aead, KeyInit, Payload},
+    consts::{U13, U4},
+    Ccm,
+};
+use core::convert::TryFrom;
+
+use super::{error::Error, Result};
+use crate::{counter::Counter, ratchet::Direction};
+
+/// AES-256-CCM with 4 byte tags and 13 byte nonces.
+type FrameCipher = Ccm<Aes256, U4, U13>;
+
+/// The length in bytes of `sck` and `rck`.
+pub const KEY_LENGTH: usize = 32;
+
+/// The length in bytes of the tag of a protected payload.
+pub const TAG_LENGTH: usize = 4;
+
+/// The largest application payload at DR0, in bytes.
+pub const DR0_MAX_PAYLOAD: usize = 51;
+
+/// The number of frame counters, which is one past the last.
+const FCNT_COUNT: u64 = 1 << 32;
+
+/// The frame protection of one party.
+///
+/// The frame counter of a frame is sent in its header, which is passed as
+/// associated data, so that it's authenticated along with the payload.
+///
+/// The frame counter of the frames sent is a [`Counter`], which says how to
+/// keep it across reboots with `reserve_frame_counters` and
+/// `restore_frame_counter`. The last received frame counter is restored with
+/// `set_last_received_fcnt`.
+pub struct FrameProtection {
+    dev_addr: [u8; 4],
+    sending: Direction,
+    sck: Vec<u8>,
+    rck: Vec<u8>,
+    /// The frame counter of the frames sent.
+    fcnt: Counter,
+    /// The frame counter of the last frame received.
+    last_received_fcnt: Option<u32>,
+}
+
+impl FrameProtection {
+    /// Sets up frame protection with the keys of a party.
+    ///
+    /// # Arguments
+    /// * `dev_addr` - The DevAddr of the device.
+    /// * `sending` - The direction this party sends on, the uplink for the
+    ///   device and the downlink for the server.
+    /// * `sck` - The key the frames sent are encrypted with.
+    /// * `rck` - The key the frames received are encrypted with.
+    pub fn new(
+        dev_addr: [u8; 4],
+        sending: Direction,
+        sck: &[u8],
+        rck: &[u8],
+    ) -> Result<FrameProtection> {
+        if sck.len() != KEY_LENGTH || rck.len() != KEY_LENGTH {
+            return Err(Error::InvalidKey);
+        }
+
+        Ok(FrameProtection {
+            dev_addr,
+            sending,
+            sck: sck.to_vec(),
+            rck: rck.to_vec(),
+            fcnt: Counter::new(FCNT_COUNT),
+            last_received_fcnt: None,
+        })
+    }
+
+    /// Returns the frame counter of the next frame sent.
+    pub fn next_fcnt(&self) -> Option<u32> {
+        u32::try_from(self.fcnt.value()).ok()
+    }
+
+    /// Returns the frame counter of the last frame received.
+    pub fn last_received_fcnt(&self) -> Option<u32> {
+        self.last_received_fcnt
+    }
+
+    /// Sets the frame counter of the last frame received, when restoring
+    /// the frame protection.
+    pub fn set_last_received_fcnt(&mut self, fcnt: Option<u32>) {
+        self.last_received_fcnt = fcnt;
+    }
+
+    /// Reserves the next `count` frame counters, as `Counter::reserve`
+    /// does.
+    pub fn reserve_frame_counters(&mut self, count: u32) -> u64 {
+        self.fcnt.reserve(count.into())
+    }
+
+    /// Restores the frame counter from a stored bound, as
+    /// `Counter::restore` does.
+    pub fn restore_frame_counter(&mut self, bound: u64) {
+        self.fcnt.restore(bound);
+    }
+
+    /// Protects the payload of the next frame, and returns its frame
+    /// counter together with the ciphertext and tag.
+    ///
+    /// # Arguments
+    /// * `header` - The frame header, which is authenticated.
+    /// * `payload` - The frame payload, which is encrypted.
+    pub fn protect(
+        &mut self,
+        header: &[u8],
+        payload: &[u8],
+    ) -> Result<(u32, Vec<u8>)> {
+        let fcnt = u32::try_from(self.fcnt.take()?)
+            .map_err(|_| Error::CounterExhausted)?;
+        let nonce = self.nonce(self.sending, fcnt);
+        let protected = FrameCipher::new_from_slice(&self.sck)
+            .map_err(|_| Error::InvalidKey)?
+            .encrypt(
+                (&nonce[..]).into(),
+                Payload {
+                    aad: header,
+                    msg: payload,
+                },
+            )?;
+        Ok((fcnt, protected))
+    }
+
+    /// Verifies and decrypts the payload of a received frame.
+    ///
+    /// # Arguments
+    /// * `fcnt` - The frame counter of the frame.
+    /// * `header` - The frame header.
+    /// * `protected` - The ciphertext and tag.
+    pub fn unprotect(
+        &mut self,
+        fcnt: u32,
+        header: &[u8],
+        protected: &[u8],
+    ) -> Result<Vec<u8>> {
+        if matches!(self.last_received_fcnt, Some(last) if fcnt <= last) {
+            return Err(Error::CounterReuse);
+        }
+        let nonce = self.nonce(self.sending.reverse(), fcnt);
+        let payload = FrameCipher::new_from_slice(&self.rck)
+            .map_err(|_| Error::InvalidKey)?
+            .decrypt(
+                (&nonce[..]).into(),
+                Payload {
+                    aad: header,
+                    msg: protected,
+                },
+            )?;
+        // Only an authentic frame moves the counter on
+        self.last_received_fcnt = Some(fcnt);
+
+        Ok(payload)
+    }
+
+    /// Returns the nonce of a frame, which is laid out like the blocks
+    /// LoRaWAN encrypts with: 4 zero bytes, the direction, the DevAddr and
+    /// the frame counter.
+    fn nonce(&self, direction: Direction, fcnt: u32) -> [u8; 13] {
+        let mut nonce = [0; 13];
+        nonce[4] = match direction {
+            Direction::Uplink => 0,
+            Direction::Downlink => 1,
+        };
+        nonce[5..9].copy_from_slice(&self.dev_addr);
+        nonce[9..].copy_from_slice(&fcnt.to_le_bytes());
+
+        nonce
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edhoc::test_vectors::{RFC_DOWNLINK, RFC_UPLINK};
+
+    const DEV_ADDR: [u8; 4] = [0x26, 0x01, 0x1B, 0xDA];
+    /// The header of an unconfirmed uplink with FCtrl and FCnt zeroed.
+    const HEADER: [u8; 8] = [0x40, 0xDA, 0x1B, 0x01, 0x26, 0x00, 0x00, 0x00];
+
+    /// Returns the frame protection of the device and the network server,
+    /// keyed with the uplink and downlink keys of the RFC 9529 session.
+    fn link() -> (FrameProtection, FrameProtection) {
+        (
+            FrameProtection::new(
+                DEV_ADDR,
+                Direction::Uplink,
+                &RFC_UPLINK,
+                &RFC_DOWNLINK,
+            )
+            .unwrap(),
+            FrameProtection::new(
+                DEV_ADDR,
+                Direction::Downlink,
+                &RFC_DOWNLINK,
+                &RFC_UPLINK,
+            )
+            .unwrap(),
+        )
+    }
+
+    #[test]
+    fn frames() {
+        let (mut device, mut server) = link();
+
+        let (fcnt, protected) =
+            device.protect(&HEADER, b"temperature").unwrap();
+        assert_eq!(fcnt, 0);
+        assert_eq!(protected.len(), b"temperature".len() + TAG_LENGTH);
+        assert_eq!(
+            server.unprotect(fcnt, &HEADER, &protected).unwrap(),
+            b"temperature".to_vec()
+        );
+
+        let (fcnt, protected) = server.protect(&HEADER, b"ack").unwrap();
+        assert_eq!(
+            device.unprotect(fcnt, &HEADER, &protected).unwrap(),
+            b"ack".to_vec()
+        );
+
+        // The same payload looks different in the next frame
+        let (_, first) = device.protect(&HEADER, b"temperature").unwrap();
+        let (fcnt, second) = device.protect(&HEADER, b"temperature").unwrap();
+        assert_eq!(fcnt, 2);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn dr0_payload() {
+        let (mut device, mut server) = link();
+        let payload = [0xA5; DR0_MAX_PAYLOAD - TAG_LENGTH];
+
+        let (fcnt, protected) = device.protect(&HEADER, &payload).unwrap();
+        assert_eq!(protected.len(), DR0_MAX_PAYLOAD);
+        assert_eq!(
+            server.unprotect(fcnt, &HEADER, &protected).unwrap(),
+            payload.to_vec()
+        );
+    }
+
+    #[test]
+    fn rejected_frames() {
+        let (mut device, mut server) = link();
+        let (fcnt, protected) =
+            device.protect(&HEADER, b"temperature").unwrap();
+
+        // A tampered header or payload, and the wrong frame counter
+        let mut header = HEADER;
+        header[5] = 0x01;
+        assert_eq!(
+            server.unprotect(fcnt, &header, &protected),
+            Err(Error::Aead)
+        );
+        let mut tampered = protected.clone();
+        tampered[0] ^= 1;
+        assert_eq!(
+            server.unprotect(fcnt, &HEADER, &tampered),
+            Err(Error::Aead)
+        );
+        assert_eq!(server.unprotect(1, &HEADER, &protected), Err(Error::Aead));
+
+        // Failures don't move the counter, but replays are rejected
+        server.unprotect(fcnt, &HEADER, &protected).unwrap();
+        assert_eq!(
+            server.unprotect(fcnt, &HEADER, &protected),
+            Err(Error::CounterReuse)
+        );
+        assert_eq!(server.last_received_fcnt(), Some(0));
+
+        // A frame reflected back to the device is in the wrong direction
+        let (fcnt, protected) = device.protect(&HEADER, b"humidity").unwrap();
+        let mut reflector = FrameProtection::new(
+            DEV_ADDR,
+            Direction::Uplink,
+            &RFC_UPLINK,
+            &RFC_UPLINK,
+        )
+        .unwrap();
+        assert_eq!(
+            reflector.unprotect(fcnt, &HEADER, &protected),
+            Err(Error::Aead)
+        );
+    }
+
+    #[test]
+    fn reserved_frame_counters() {
+        let (mut device, mut server) = link();
+        assert_eq!(device.reserve_frame_counters(2), 2);
+        device.protect(&HEADER, b"").unwrap();
+        let (fcnt, protected) = device.protect(&HEADER, b"").unwrap();
+        assert_eq!(
+            device.protect(&HEADER, b""),
+            Err(Error::FrameCountersNotReserved)
+        );
+        server.unprotect(fcnt, &HEADER, &protected).unwrap();
+
+        // After a reboot, both continue from what they stored
+        let (mut device, mut server) = link();
+        device.restore_frame_counter(2);
+        server.set_last_received_fcnt(Some(fcnt));
+        assert_eq!(
+            device.protect(&HEADER, b""),
+            Err(Error::FrameCountersNotReserved)
+        );
+        assert_eq!(
+            server.unprotect(fcnt, &HEADER, &protected),
+            Err(Error::CounterReuse)
+        );
+        assert_eq!(device.reserve_frame_counters(16), 18);
+        let (fcnt, protected) = device.protect(&HEADER, b"").unwrap();
+        assert_eq!(fcnt, 2);
+        server.unprotect(fcnt, &HEADER, &protected).unwrap();
+    }
+
+    #[test]
+    fn counters() {
+        let (mut device, _) = link();
+        device.restore_frame_counter(u32::MAX as u64);
+        assert_eq!(device.reserve_frame_counters(16), 1 << 32);
+        device.protect(&HEADER, b"").unwrap();
+        assert_eq!(device.next_fcnt(), None);
+        assert_eq!(device.protect(&HEADER, b""), Err(Error::CounterExhausted));
+
+        assert_eq!(
+            FrameProtection::new(
+                DEV_ADDR,
+                Direction::Uplink,
+                &[0; 16],
+                &RFC_UPLINK
+            )
+            .err(),
+            Some(Error::InvalidKey)
+        );
+    }
+}
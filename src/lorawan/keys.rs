@@ -0,0 +1,169 @@
+//! The LoRaWAN 1.1 session keys, exported from a completed EDHOC session in
+//! place of the ones an OTAA join derives.
+//!
+//! Each key is exported with its own label from the private use range of
+//! the EDHOC exporter labels, and an empty context, since the session is
+//! already bound to DevEUI and JoinEUI through C_I and `ead_1`:
+//!
+//! | Label | Output                | Length |
+//! |-------|-----------------------|--------|
+//! | 32784 | FNwkSIntKey           | 16     |
+//! | 32785 | SNwkSIntKey           | 16     |
+//! | 32786 | NwkSEncKey            | 16     |
+//! | 32787 | AppSKey               | 16     |
+//! | 32788 | DevAddr material      | 4      |
+
+use alloc::vec::Vec;
+
+use super::Result;
+use crate::edhoc::Session;
+
+/// The exporter label of FNwkSIntKey.
+pub const F_NWK_S_INT_KEY: u16 = 32784;
+/// The exporter label of SNwkSIntKey.
+pub const S_NWK_S_INT_KEY: u16 = 32785;
+/// The exporter label of NwkSEncKey.
+pub const NWK_S_ENC_KEY: u16 = 32786;
+/// The exporter label of AppSKey.
+pub const APP_S_KEY: u16 = 32787;
+/// The exporter label of the material the DevAddr is assigned from.
+pub const DEV_ADDR: u16 = 32788;
+
+/// The length in bytes of the LoRaWAN session keys, which are AES-128 keys.
+pub const SESSION_KEY_LENGTH: usize = 16;
+
+/// The keys and address of a LoRaWAN 1.1 session.
+///
+/// The network server takes the three network session keys and the
+/// DevAddr, and the application server only AppSKey, just like after a
+/// join.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SessionKeys {
+    pub f_nwk_s_int_key: Vec<u8>,
+    pub s_nwk_s_int_key: Vec<u8>,
+    pub nwk_s_enc_key: Vec<u8>,
+    pub app_s_key: Vec<u8>,
+    pub dev_addr: [u8; 4],
+}
+
+impl SessionKeys {
+    /// Exports the session keys from a completed session, and assigns the
+    /// DevAddr by passing 4 exported bytes to `assign_dev_addr`.
+    ///
+    /// Both parties have to assign the same DevAddr, so the hook has to
+    /// be deterministic, e.g. `dev_addr_with_prefix` with the AddrPrefix of
+    /// the network. The DevAddr is in the order it's written in, with the
+    /// most significant byte first.
+    ///
+    /// draft-12 sessions can only export the OSCORE master secret and
+    /// salt, so this fails for them.
+    pub fn from_session<F>(
+        session: &Session,
+        assign_dev_addr: F,
+    ) -> Result<SessionKeys>
+    where
+        F: FnOnce([u8; 4]) -> [u8; 4],
+    {
+        let export =
+            |label| session.export(label, b"", SESSION_KEY_LENGTH);
+        let mut material = [0; 4];
+        material.copy_from_slice(&session.export(DEV_ADDR, b"", 4)?);
+
+        Ok(SessionKeys {
+            f_nwk_s_int_key: export(F_NWK_S_INT_KEY)?,
+            s_nwk_s_int_key: export(S_NWK_S_INT_KEY)?,
+            nwk_s_enc_key: export(NWK_S_ENC_KEY)?,
+            app_s_key: export(APP_S_KEY)?,
+            dev_addr: assign_dev_addr(material),
+        })
+    }
+}
+
+/// Returns a DevAddr assignment that keeps the `prefix_length` most
+/// significant bits of `addr_prefix`, the AddrPrefix of the network, and
+/// takes the remaining NwkAddr bits from the exported material.
+pub fn dev_addr_with_prefix(
+    addr_prefix: [u8; 4],
+    prefix_length: u32,
+) -> impl Fn([u8; 4]) -> [u8; 4] {
+    let mask = u32::MAX
+        .checked_shl(32_u32.saturating_sub(prefix_length))
+        .unwrap_or(0);
+    let prefix = u32::from_be_bytes(addr_prefix) & mask;
+
+    move |material| {
+        (prefix | u32::from_be_bytes(material) & !mask).to_be_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::error::Error;
+    use super::*;
+    use crate::edhoc::{suite::SUITE_2, util, Version};
+
+    fn session(version: Version) -> Session {
+        let (_, prk_4e3m) =
+            util::extract_prk(SUITE_2.hash, None, &[0x4E; 32]).unwrap();
+        Session::new(version, SUITE_2, &prk_4e3m, &[0x74; 32]).unwrap()
+    }
+
+    #[test]
+    fn session_keys() {
+        let session = session(Version::Rfc9528);
+        let keys = SessionKeys::from_session(&session, |material| material)
+            .unwrap();
+
+        assert_eq!(keys.f_nwk_s_int_key.len(), SESSION_KEY_LENGTH);
+        assert_eq!(
+            keys.app_s_key,
+            session.export(APP_S_KEY, b"", SESSION_KEY_LENGTH).unwrap()
+        );
+        let all = [
+            &keys.f_nwk_s_int_key,
+            &keys.s_nwk_s_int_key,
+            &keys.nwk_s_enc_key,
+            &keys.app_s_key,
+        ];
+        for (i, key) in all.iter().enumerate() {
+            assert!(all[i + 1..].iter().all(|other| other != key));
+        }
+        assert_eq!(
+            keys.dev_addr[..],
+            session.export(DEV_ADDR, b"", 4).unwrap()[..]
+        );
+
+        // A key update gives a new session with a new address
+        let mut updated = session.clone();
+        updated.key_update(b"rejoin").unwrap();
+        let new_keys =
+            SessionKeys::from_session(&updated, |material| material).unwrap();
+        assert_ne!(new_keys.app_s_key, keys.app_s_key);
+        assert_ne!(new_keys.dev_addr, keys.dev_addr);
+    }
+
+    #[test]
+    fn dev_addr_prefix() {
+        // The AddrPrefix of a type 0 NetID is 7 bits long
+        let assign = dev_addr_with_prefix([0x26, 0, 0, 0], 7);
+        assert_eq!(assign([0xFF, 0x01, 0x1B, 0xDA]), [0x27, 0x01, 0x1B, 0xDA]);
+        assert_eq!(assign([0x00, 0x01, 0x1B, 0xDA]), [0x26, 0x01, 0x1B, 0xDA]);
+
+        let keys = SessionKeys::from_session(&session(Version::Rfc9528), assign)
+            .unwrap();
+        assert_eq!(keys.dev_addr[0] & 0xFE, 0x26);
+
+        let whole = dev_addr_with_prefix([0x26, 0x01, 0x1B, 0xDA], 32);
+        assert_eq!(whole([0; 4]), [0x26, 0x01, 0x1B, 0xDA]);
+        let none = dev_addr_with_prefix([0x26, 0x01, 0x1B, 0xDA], 0);
+        assert_eq!(none([0; 4]), [0; 4]);
+    }
+
+    #[test]
+    fn draft12_session() {
+        assert_eq!(
+            SessionKeys::from_session(&session(Version::Draft12), |m| m),
+            Err(Error::UnsupportedLabel)
+        );
+    }
+}
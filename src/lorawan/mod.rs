@@ -0,0 +1,36 @@
+//! Protection of LoRaWAN frame payloads with the keys derived by EDHOC.
+//!
+//! Each party encrypts the frames it sends with its `sck` and decrypts the
+//! ones it receives with its `rck`, using AES-256-CCM with 4 byte tags like
+//! the MIC of LoRaWAN, so that a protected payload is only 4 bytes longer
+//! than the plaintext. The nonce binds the DevAddr, the direction and the
+//! frame counter, and a received frame counter has to be higher than the
+//! one before it.
+//!
+//! The LoRaWAN 1.1 session keys and the DevAddr can also be exported from
+//! the completed session, for servers that expect those of a join, and
+//! messages longer than a frame, like message_2 with EAD at DR0, can be
+//! split into fragments and reassembled. The sizes and airtime of the
+//! handshake messages of a configuration can be worked out in advance, and
+//! a Class A device can run the handshake with a driver that pulls
+//! message_2 and message_4 into its receive windows. The `sim` feature adds
+//! a simulated clock and radio to run the driver with.
+
+pub mod airtime;
+pub mod class_a;
+#[cfg_attr(tarpaulin, skip)]
+pub mod error;
+pub mod fragment;
+pub mod frame;
+pub mod keys;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+
+/// The result type for the `lorawan` module.
+type Result<T> = core::result::Result<T, error::Error>;
+
+pub use airtime::{HandshakeProfile, MessageSizes, Modulation};
+pub use class_a::{Clock, Driver, Radio};
+pub use fragment::Reassembler;
+pub use frame::FrameProtection;
+pub use keys::SessionKeys;
@@ -0,0 +1,140 @@
+//! A simulated clock and radio, to run the driver without hardware.
+//!
+//! The clock jumps to every instant waited for, so a handshake with all
+//! its backoffs and duty-cycle waits runs in no time. The radio hands each
+//! uplink to a `Network` standing in for R behind the network server, and
+//! delivers the answer in a receive window, or loses either, following a
+//! script. It records every uplink and receive window for inspection.
+
+use alloc::{collections::VecDeque, rc::Rc, vec::Vec};
+use core::{cell::Cell, time::Duration};
+
+use super::class_a::{Clock, Radio, Window};
+
+/// A clock that jumps to the instants waited for.
+///
+/// Clones share the same time, so the radio can read it.
+#[derive(Clone, Debug, Default)]
+pub struct SimClock(Rc<Cell<Duration>>);
+
+impl SimClock {
+    /// Returns a clock starting at zero.
+    pub fn new() -> SimClock {
+        SimClock::default()
+    }
+}
+
+impl Clock for SimClock {
+    fn now(&self) -> Duration {
+        self.0.get()
+    }
+
+    fn wait_until(&mut self, instant: Duration) {
+        if instant > self.0.get() {
+            self.0.set(instant);
+        }
+    }
+}
+
+/// What happens to an uplink and the answer to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Delivery {
+    /// The uplink doesn't reach the network.
+    Lost,
+    /// The network answers, but the device doesn't receive the answer.
+    Dropped,
+    /// The network answers in the given window.
+    In(Window),
+}
+
+/// The network side of a simulation, such as R behind a network server.
+pub trait Network {
+    /// Returns the downlink answering `uplink`, if any.
+    fn handle(&mut self, uplink: &[u8]) -> Option<Vec<u8>>;
+}
+
+/// A radio delivering uplinks to a `Network` as scripted, answering in RX1
+/// once the script runs out.
+pub struct SimRadio<N: Network> {
+    clock: SimClock,
+    network: N,
+    script: VecDeque<Delivery>,
+    downlink: Option<(Window, Vec<u8>)>,
+    /// The start and payload of every uplink.
+    uplinks: Vec<(Duration, Vec<u8>)>,
+    /// The opening of every receive window listened in.
+    windows: Vec<(Window, Duration)>,
+}
+
+impl<N: Network> SimRadio<N> {
+    /// Returns a radio that follows `script` for the first uplinks.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock of the driver, which the uplinks are timed with.
+    /// * `network` - The network answering the uplinks.
+    /// * `script` - What happens to each uplink, in order.
+    pub fn new(
+        clock: &SimClock,
+        network: N,
+        script: &[Delivery],
+    ) -> SimRadio<N> {
+        SimRadio {
+            clock: clock.clone(),
+            network,
+            script: script.iter().copied().collect(),
+            downlink: None,
+            uplinks: Vec::new(),
+            windows: Vec::new(),
+        }
+    }
+
+    /// Returns the network.
+    pub fn network(&self) -> &N {
+        &self.network
+    }
+
+    /// Returns the network, e.g. to change its behaviour mid-simulation.
+    pub fn network_mut(&mut self) -> &mut N {
+        &mut self.network
+    }
+
+    /// Returns the start and payload of every uplink sent.
+    pub fn uplinks(&self) -> &[(Duration, Vec<u8>)] {
+        &self.uplinks
+    }
+
+    /// Returns the window and opening time of every receive window
+    /// listened in.
+    pub fn windows(&self) -> &[(Window, Duration)] {
+        &self.windows
+    }
+}
+
+impl<N: Network> Radio for SimRadio<N> {
+    fn transmit(&mut self, payload: &[u8]) {
+        self.uplinks.push((self.clock.now(), payload.to_vec()));
+        let delivery =
+            self.script.pop_front().unwrap_or(Delivery::In(Window::Rx1));
+        self.downlink = match delivery {
+            Delivery::Lost => None,
+            Delivery::Dropped => {
+                self.network.handle(payload);
+                None
+            }
+            Delivery::In(window) => {
+                self.network.handle(payload).map(|answer| (window, answer))
+            }
+        };
+    }
+
+    fn receive(&mut self, window: Window) -> Option<Vec<u8>> {
+        self.windows.push((window, self.clock.now()));
+        match self.downlink.take() {
+            Some((w, answer)) if w == window => Some(answer),
+            downlink => {
+                self.downlink = downlink;
+                None
+            }
+        }
+    }
+}
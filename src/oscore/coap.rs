@@ -0,0 +1,250 @@
+//! The parts of CoAP (RFC 7252) that OSCORE needs to protect messages.
+
+use alloc::vec::Vec;
+
+use super::{error::Error, Result};
+
+pub const URI_HOST: u16 = 3;
+pub const OBSERVE: u16 = 6;
+pub const URI_PORT: u16 = 7;
+pub const OSCORE: u16 = 9;
+pub const URI_PATH: u16 = 11;
+pub const PROXY_URI: u16 = 35;
+pub const PROXY_SCHEME: u16 = 39;
+
+pub const CON: u8 = 0;
+pub const NON: u8 = 1;
+pub const ACK: u8 = 2;
+
+pub const GET: u8 = 0x01;
+pub const POST: u8 = 0x02;
+pub const FETCH: u8 = 0x05;
+pub const CHANGED: u8 = 0x44;
+pub const CONTENT: u8 = 0x45;
+
+/// The longest token, whose length has to fit in TKL.
+pub const MAX_TOKEN_LENGTH: usize = 8;
+
+/// The marker separating the options from the payload.
+const PAYLOAD_MARKER: u8 = 0xFF;
+
+/// A CoAP option, as a pair of its number and its value.
+pub type CoapOption = (u16, Vec<u8>);
+
+/// A CoAP message.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Message {
+    /// The type, 0 for confirmable up to 3 for reset.
+    pub mtype: u8,
+    pub code: u8,
+    pub message_id: u16,
+    /// The token, of up to `MAX_TOKEN_LENGTH` bytes.
+    pub token: Vec<u8>,
+    /// The options, in order of their numbers.
+    pub options: Vec<CoapOption>,
+    pub payload: Vec<u8>,
+}
+
+impl Message {
+    /// Returns whether the message is a request.
+    pub fn is_request(&self) -> bool {
+        self.code != 0 && self.code >> 5 == 0
+    }
+
+    /// Returns the value of the first option with the given number.
+    pub fn option(&self, number: u16) -> Option<&[u8]> {
+        self.options
+            .iter()
+            .find(|(n, _)| *n == number)
+            .map(|(_, value)| &value[..])
+    }
+}
+
+/// Serializes a CoAP message, which fails for a token that's too long.
+pub fn serialize_message(msg: &Message) -> Result<Vec<u8>> {
+    if msg.token.len() > MAX_TOKEN_LENGTH {
+        return Err(Error::InvalidMessage);
+    }
+    // Version 1, the type and the token length
+    let mut bytes =
+        vec![0x40 | (msg.mtype & 0x03) << 4 | msg.token.len() as u8, msg.code];
+    bytes.extend(&msg.message_id.to_be_bytes());
+    bytes.extend(&msg.token);
+    bytes.extend(serialize_options(&msg.options, &msg.payload));
+
+    Ok(bytes)
+}
+
+/// Deserializes a CoAP message.
+pub fn deserialize_message(bytes: &[u8]) -> Result<Message> {
+    if bytes.len() < 4 || bytes[0] >> 6 != 1 {
+        return Err(Error::InvalidMessage);
+    }
+    let token_length = usize::from(bytes[0] & 0x0F);
+    if token_length > MAX_TOKEN_LENGTH || bytes.len() < 4 + token_length {
+        return Err(Error::InvalidMessage);
+    }
+    let (options, payload) = deserialize_options(&bytes[4 + token_length..])?;
+
+    Ok(Message {
+        mtype: bytes[0] >> 4 & 0x03,
+        code: bytes[1],
+        message_id: u16::from_be_bytes([bytes[2], bytes[3]]),
+        token: bytes[4..4 + token_length].to_vec(),
+        options,
+        payload,
+    })
+}
+
+/// Serializes options followed by the payload, as they end a CoAP message
+/// and make up the plaintext of OSCORE.
+///
+/// The options are sorted by their numbers, keeping repeated options in
+/// their order.
+pub fn serialize_options(options: &[CoapOption], payload: &[u8]) -> Vec<u8> {
+    let mut sorted: Vec<&CoapOption> = options.iter().collect();
+    sorted.sort_by_key(|(number, _)| *number);
+
+    let mut bytes = Vec::new();
+    let mut previous = 0;
+    for (number, value) in sorted {
+        let (delta, delta_ext) = option_nibble(usize::from(number - previous));
+        let (length, length_ext) = option_nibble(value.len());
+        bytes.push(delta << 4 | length);
+        bytes.extend(delta_ext);
+        bytes.extend(length_ext);
+        bytes.extend(value);
+        previous = *number;
+    }
+    if !payload.is_empty() {
+        bytes.push(PAYLOAD_MARKER);
+        bytes.extend(payload);
+    }
+
+    bytes
+}
+
+/// Deserializes options followed by the payload.
+pub fn deserialize_options(
+    bytes: &[u8],
+) -> Result<(Vec<CoapOption>, Vec<u8>)> {
+    let mut options = Vec::new();
+    let mut number: usize = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == PAYLOAD_MARKER {
+            // A marker has to be followed by a payload
+            if i + 1 == bytes.len() {
+                return Err(Error::InvalidMessage);
+            }
+            return Ok((options, bytes[i + 1..].to_vec()));
+        }
+        let header = bytes[i];
+        i += 1;
+        let delta = option_value(header >> 4, bytes, &mut i)?;
+        let length = option_value(header & 0x0F, bytes, &mut i)?;
+        number += delta;
+        if number > usize::from(u16::MAX) || bytes.len() < i + length {
+            return Err(Error::InvalidMessage);
+        }
+        options.push((number as u16, bytes[i..i + length].to_vec()));
+        i += length;
+    }
+
+    Ok((options, Vec::new()))
+}
+
+/// Returns the nibble and the extended bytes an option delta or length is
+/// encoded with.
+fn option_nibble(value: usize) -> (u8, Vec<u8>) {
+    match value {
+        0..=12 => (value as u8, Vec::new()),
+        13..=268 => (13, vec![(value - 13) as u8]),
+        _ => (14, ((value - 269) as u16).to_be_bytes().to_vec()),
+    }
+}
+
+/// Returns the option delta or length encoded with the nibble, reading the
+/// extended bytes at `i`.
+fn option_value(nibble: u8, bytes: &[u8], i: &mut usize) -> Result<usize> {
+    match nibble {
+        0..=12 => Ok(usize::from(nibble)),
+        13 => {
+            let byte = bytes.get(*i).ok_or(Error::InvalidMessage)?;
+            *i += 1;
+            Ok(usize::from(*byte) + 13)
+        }
+        14 => {
+            let ext = bytes.get(*i..*i + 2).ok_or(Error::InvalidMessage)?;
+            *i += 2;
+            Ok(usize::from(u16::from_be_bytes([ext[0], ext[1]])) + 269)
+        }
+        _ => Err(Error::InvalidMessage),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_vectors::*;
+    use super::*;
+
+    #[test]
+    fn message() {
+        let request = deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        assert_eq!(request.mtype, 0);
+        assert_eq!(request.code, GET);
+        assert_eq!(request.token, [0x00, 0x00, 0x39, 0x74]);
+        assert_eq!(request.option(URI_HOST), Some(&b"localhost"[..]));
+        assert_eq!(request.option(URI_PATH), Some(&b"tv1"[..]));
+        assert!(request.is_request());
+        assert_eq!(
+            serialize_message(&request).unwrap(),
+            UNPROTECTED_REQUEST.to_vec()
+        );
+
+        let response = deserialize_message(&UNPROTECTED_RESPONSE).unwrap();
+        assert_eq!(response.payload, b"Hello World!".to_vec());
+        assert!(!response.is_request());
+        assert_eq!(
+            serialize_message(&response).unwrap(),
+            UNPROTECTED_RESPONSE.to_vec()
+        );
+    }
+
+    #[test]
+    fn extended_options() {
+        let options = vec![(300, vec![0xAB; 13]), (OBSERVE, vec![0; 270])];
+        let bytes = serialize_options(&options, b"");
+        let (decoded, payload) = deserialize_options(&bytes).unwrap();
+        assert_eq!(decoded, vec![options[1].clone(), options[0].clone()]);
+        assert!(payload.is_empty());
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(
+            deserialize_message(&[0x44, 0x01, 0x5D]),
+            Err(Error::InvalidMessage)
+        );
+        // A payload marker without payload
+        assert_eq!(deserialize_options(&[0xFF]), Err(Error::InvalidMessage));
+        // An option running past the end
+        assert_eq!(deserialize_options(&[0x35, 0x00]), Err(Error::InvalidMessage));
+        // The reserved nibble
+        assert_eq!(deserialize_options(&[0xF0]), Err(Error::InvalidMessage));
+    }
+
+    #[test]
+    fn long_token() {
+        let mut request = deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        request.token = vec![0xAB; MAX_TOKEN_LENGTH];
+        let bytes = serialize_message(&request).unwrap();
+        assert_eq!(deserialize_message(&bytes).unwrap(), request);
+
+        // TKL can't say 9 to 15, and 16 would spill into the type
+        for length in &[9, 15, 16] {
+            request.token = vec![0xAB; *length];
+            assert_eq!(serialize_message(&request), Err(Error::InvalidMessage));
+        }
+    }
+}
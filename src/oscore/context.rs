@@ -0,0 +1,879 @@
+//! The OSCORE security context, and the protection of messages with it.
+
+use alloc::vec::Vec;
+use serde_bytes::Bytes;
+
+use super::{
+    coap::{self, CoapOption, Message},
+    error::Error,
+    option::{self, OscoreOption},
+    replay::ReplayWindow,
+    Result,
+};
+use crate::{
+    cbor,
+    counter::Counter,
+    edhoc::{
+        cose,
+        suite::{Aead, Hash},
+        util, Session,
+    },
+};
+
+/// The largest sender sequence number, which fills the 5 bytes a Partial IV
+/// can have.
+const MAX_SEQUENCE_NUMBER: u64 = (1 << 40) - 1;
+
+/// The kid and Partial IV of a request, which bind its response to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestId {
+    pub kid: Vec<u8>,
+    pub partial_iv: Vec<u8>,
+}
+
+/// The security context of RFC 8613, consisting of the common context and
+/// the sender and recipient contexts derived from it.
+///
+/// The sender sequence number is a [`Counter`], which says how to keep it
+/// across reboots with `reserve_sequence_numbers` and
+/// `restore_sequence_number`. The replay window of the recipient context is
+/// restored with `set_replay_window`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityContext {
+    aead: Aead,
+    hash: Hash,
+    master_secret: Vec<u8>,
+    master_salt: Vec<u8>,
+    id_context: Option<Vec<u8>>,
+    common_iv: Vec<u8>,
+    sender_id: Vec<u8>,
+    sender_key: Vec<u8>,
+    recipient_id: Vec<u8>,
+    recipient_key: Vec<u8>,
+    sender_sequence_number: Counter,
+    replay_window: ReplayWindow,
+}
+
+impl SecurityContext {
+    /// Derives the sender key, the recipient key and the common IV.
+    ///
+    /// # Arguments
+    /// * `master_secret` - The master secret.
+    /// * `master_salt` - The master salt, which may be empty.
+    /// * `id_context` - The optional ID Context.
+    /// * `sender_id` - The ID we send with.
+    /// * `recipient_id` - The ID the peer sends with.
+    /// * `aead` - The AEAD algorithm.
+    /// * `hash` - The hash function of the HKDF.
+    pub fn new(
+        master_secret: &[u8],
+        master_salt: &[u8],
+        id_context: Option<&[u8]>,
+        sender_id: &[u8],
+        recipient_id: &[u8],
+        aead: Aead,
+        hash: Hash,
+    ) -> Result<SecurityContext> {
+        // The IDs are padded into the nonce, next to their length and the
+        // Partial IV
+        let max_id_length = aead.nonce_length() - 6;
+        if sender_id.len() > max_id_length || recipient_id.len() > max_id_length
+        {
+            return Err(Error::InvalidId);
+        }
+
+        let (_, prk) = util::extract_prk(hash, Some(master_salt), master_secret)?;
+        let derive = |id: &[u8], kind: &str, length: usize| -> Result<Vec<u8>> {
+            let info = cbor::encode((
+                Bytes::new(id),
+                id_context.map(Bytes::new),
+                aead.cose_algorithm(),
+                kind,
+                length,
+            ))?;
+            let mut okm = vec![0; length];
+            prk.expand(&info, &mut okm)?;
+            Ok(okm)
+        };
+
+        Ok(SecurityContext {
+            aead,
+            hash,
+            master_secret: master_secret.to_vec(),
+            master_salt: master_salt.to_vec(),
+            id_context: id_context.map(<[u8]>::to_vec),
+            common_iv: derive(b"", "IV", aead.nonce_length())?,
+            sender_id: sender_id.to_vec(),
+            sender_key: derive(sender_id, "Key", aead.key_length())?,
+            recipient_id: recipient_id.to_vec(),
+            recipient_key: derive(recipient_id, "Key", aead.key_length())?,
+            sender_sequence_number: Counter::new(MAX_SEQUENCE_NUMBER + 1),
+            replay_window: ReplayWindow::new(),
+        })
+    }
+
+    /// Derives the security context of the EDHOC initiator, which sends
+    /// with `C_R` and receives with `C_I`.
+    ///
+    /// The master secret and salt are exported from the session, and the
+    /// AEAD and HKDF are the application algorithms of its suite.
+    pub fn from_edhoc_initiator(
+        session: &Session,
+        c_i: &[u8],
+        c_r: &[u8],
+    ) -> Result<SecurityContext> {
+        SecurityContext::from_edhoc(session, c_r, c_i)
+    }
+
+    /// Derives the security context of the EDHOC responder, which sends
+    /// with `C_I` and receives with `C_R`.
+    pub fn from_edhoc_responder(
+        session: &Session,
+        c_i: &[u8],
+        c_r: &[u8],
+    ) -> Result<SecurityContext> {
+        SecurityContext::from_edhoc(session, c_i, c_r)
+    }
+
+    fn from_edhoc(
+        session: &Session,
+        sender_id: &[u8],
+        recipient_id: &[u8],
+    ) -> Result<SecurityContext> {
+        let (master_secret, master_salt) = session.oscore_secrets()?;
+        let suite = session.suite();
+
+        SecurityContext::new(
+            &master_secret,
+            &master_salt,
+            None,
+            sender_id,
+            recipient_id,
+            suite.app_aead,
+            suite.app_hash,
+        )
+    }
+
+    /// Returns the AEAD algorithm.
+    pub fn aead(&self) -> Aead {
+        self.aead
+    }
+
+    /// Returns the hash function of the HKDF.
+    pub fn hash(&self) -> Hash {
+        self.hash
+    }
+
+    /// Returns the master secret.
+    pub fn master_secret(&self) -> &[u8] {
+        &self.master_secret
+    }
+
+    /// Returns the master salt.
+    pub fn master_salt(&self) -> &[u8] {
+        &self.master_salt
+    }
+
+    /// Returns the ID Context, if there is one.
+    pub fn id_context(&self) -> Option<&[u8]> {
+        self.id_context.as_deref()
+    }
+
+    /// Returns the common IV.
+    pub fn common_iv(&self) -> &[u8] {
+        &self.common_iv
+    }
+
+    /// Returns the sender ID.
+    pub fn sender_id(&self) -> &[u8] {
+        &self.sender_id
+    }
+
+    /// Returns the sender key.
+    pub fn sender_key(&self) -> &[u8] {
+        &self.sender_key
+    }
+
+    /// Returns the recipient ID.
+    pub fn recipient_id(&self) -> &[u8] {
+        &self.recipient_id
+    }
+
+    /// Returns the recipient key.
+    pub fn recipient_key(&self) -> &[u8] {
+        &self.recipient_key
+    }
+
+    /// Returns the sender sequence number the next Partial IV is made of.
+    pub fn sender_sequence_number(&self) -> u64 {
+        self.sender_sequence_number.value()
+    }
+
+    /// Sets the sender sequence number, e.g. when restoring the context.
+    pub fn set_sender_sequence_number(&mut self, sequence_number: u64) {
+        self.sender_sequence_number.set_value(sequence_number);
+    }
+
+    /// Reserves the next `count` sender sequence numbers, as
+    /// `Counter::reserve` does.
+    pub fn reserve_sequence_numbers(&mut self, count: u64) -> u64 {
+        self.sender_sequence_number.reserve(count)
+    }
+
+    /// Restores the sender sequence number from a stored bound, as
+    /// `Counter::restore` does.
+    pub fn restore_sequence_number(&mut self, bound: u64) {
+        self.sender_sequence_number.restore(bound);
+    }
+
+    /// Returns the replay window of the recipient context.
+    pub fn replay_window(&self) -> &ReplayWindow {
+        &self.replay_window
+    }
+
+    /// Sets the replay window of the recipient context, e.g. when restoring
+    /// the context.
+    pub fn set_replay_window(&mut self, replay_window: ReplayWindow) {
+        self.replay_window = replay_window;
+    }
+
+    /// Protects a request, returning the OSCORE message together with the
+    /// `RequestId` its response is protected with.
+    pub fn protect_request(
+        &mut self,
+        request: &Message,
+    ) -> Result<(Message, RequestId)> {
+        check_token(request)?;
+        let request_id = RequestId {
+            kid: self.sender_id.clone(),
+            partial_iv: self.next_partial_iv()?,
+        };
+        let oscore_option = OscoreOption {
+            partial_iv: Some(request_id.partial_iv.clone()),
+            kid_context: self.id_context.clone(),
+            kid: Some(self.sender_id.clone()),
+        };
+        let nonce = self.nonce(&request_id.kid, &request_id.partial_iv);
+        let protected =
+            self.protect(request, &oscore_option, &nonce, &request_id)?;
+
+        Ok((protected, request_id))
+    }
+
+    /// Unprotects a request, returning the CoAP message together with the
+    /// `RequestId` its response has to be protected with.
+    ///
+    /// Requests are rejected if their Partial IV was already received, or
+    /// is too old to tell.
+    pub fn unprotect_request(
+        &mut self,
+        protected: &Message,
+    ) -> Result<(Message, RequestId)> {
+        let oscore_option = read_option(protected)?;
+        if oscore_option.kid.as_deref() != Some(&self.recipient_id[..])
+            || (oscore_option.kid_context.is_some()
+                && oscore_option.kid_context != self.id_context)
+        {
+            return Err(Error::UnknownKid);
+        }
+        let request_id = RequestId {
+            kid: self.recipient_id.clone(),
+            partial_iv: oscore_option
+                .partial_iv
+                .ok_or(Error::MissingPartialIv)?,
+        };
+        #[cfg(not(feature = "no_replay"))]
+        self.replay_window
+            .check(decode_partial_iv(&request_id.partial_iv))?;
+        let nonce = self.nonce(&request_id.kid, &request_id.partial_iv);
+        let request = self.unprotect(protected, &nonce, &request_id)?;
+        #[cfg(not(feature = "no_replay"))]
+        self.replay_window
+            .update(decode_partial_iv(&request_id.partial_iv));
+
+        Ok((request, request_id))
+    }
+
+    /// Protects the response to a request.
+    ///
+    /// Without a Partial IV of its own, the response reuses the nonce of
+    /// the request, which is only safe for a single response, so
+    /// notifications and other additional responses need one.
+    pub fn protect_response(
+        &mut self,
+        response: &Message,
+        request_id: &RequestId,
+        with_partial_iv: bool,
+    ) -> Result<Message> {
+        check_token(response)?;
+        let (oscore_option, nonce) = if with_partial_iv {
+            let partial_iv = self.next_partial_iv()?;
+            let nonce = self.nonce(&self.sender_id, &partial_iv);
+            let oscore_option = OscoreOption {
+                partial_iv: Some(partial_iv),
+                ..OscoreOption::default()
+            };
+            (oscore_option, nonce)
+        } else {
+            let nonce = self.nonce(&request_id.kid, &request_id.partial_iv);
+            (OscoreOption::default(), nonce)
+        };
+
+        self.protect(response, &oscore_option, &nonce, request_id)
+    }
+
+    /// Unprotects the response to a request.
+    ///
+    /// A response with a Partial IV of its own is checked against the
+    /// replay window like a request, while one without is bound to the
+    /// request.
+    pub fn unprotect_response(
+        &mut self,
+        protected: &Message,
+        request_id: &RequestId,
+    ) -> Result<Message> {
+        let oscore_option = read_option(protected)?;
+        let partial_iv = match oscore_option.partial_iv {
+            Some(partial_iv) => partial_iv,
+            None => {
+                let nonce =
+                    self.nonce(&request_id.kid, &request_id.partial_iv);
+                return self.unprotect(protected, &nonce, request_id);
+            }
+        };
+
+        #[cfg(not(feature = "no_replay"))]
+        self.replay_window.check(decode_partial_iv(&partial_iv))?;
+        let nonce = self.nonce(&self.recipient_id, &partial_iv);
+        let response = self.unprotect(protected, &nonce, request_id)?;
+        #[cfg(not(feature = "no_replay"))]
+        self.replay_window.update(decode_partial_iv(&partial_iv));
+
+        Ok(response)
+    }
+
+    /// Returns the Partial IV of the sender sequence number, and moves on to
+    /// the next one.
+    fn next_partial_iv(&mut self) -> Result<Vec<u8>> {
+        let sequence_number = self.sender_sequence_number.take()?;
+
+        Ok(encode_partial_iv(sequence_number))
+    }
+
+    /// Returns the AEAD nonce for the Partial IV generated by the party
+    /// sending with `id_piv`.
+    fn nonce(&self, id_piv: &[u8], partial_iv: &[u8]) -> Vec<u8> {
+        let length = self.common_iv.len();
+        let mut nonce = vec![0; length];
+        // The length of ID_PIV, ID_PIV left-padded to the nonce length
+        // minus 6 bytes, and the Partial IV left-padded to 5 bytes
+        nonce[0] = id_piv.len() as u8;
+        nonce[length - 5 - id_piv.len()..length - 5].copy_from_slice(id_piv);
+        nonce[length - partial_iv.len()..].copy_from_slice(partial_iv);
+        for (byte, iv_byte) in nonce.iter_mut().zip(&self.common_iv) {
+            *byte ^= iv_byte;
+        }
+
+        nonce
+    }
+
+    /// Returns the `Enc_structure` of the request or its response.
+    fn aad(&self, request_id: &RequestId) -> Result<Vec<u8>> {
+        let external_aad = cbor::encode((
+            1,
+            [self.aead.cose_algorithm()],
+            Bytes::new(&request_id.kid),
+            Bytes::new(&request_id.partial_iv),
+            // No Class I options are defined
+            Bytes::new(b""),
+        ))?;
+
+        Ok(cose::build_ad(&external_aad)?)
+    }
+
+    /// Encrypts the code, the inner options and the payload of a message
+    /// into the payload of the OSCORE message.
+    fn protect(
+        &self,
+        message: &Message,
+        oscore_option: &OscoreOption,
+        nonce: &[u8],
+        request_id: &RequestId,
+    ) -> Result<Message> {
+        let inner: Vec<_> = message
+            .options
+            .iter()
+            .filter(|(number, _)| is_inner(*number))
+            .cloned()
+            .collect();
+        let mut outer: Vec<_> = message
+            .options
+            .iter()
+            .filter(|(number, _)| is_outer(*number))
+            .cloned()
+            .collect();
+        outer.push((coap::OSCORE, option::serialize_option(oscore_option)));
+
+        let mut plaintext = vec![message.code];
+        plaintext.extend(coap::serialize_options(&inner, &message.payload));
+        let ciphertext = util::aead_seal(
+            self.aead,
+            &self.sender_key,
+            nonce,
+            &plaintext,
+            &self.aad(request_id)?,
+        )?;
+
+        // The outer code hides the method, only telling proxies whether
+        // this is an Observe request or notification
+        let observe = message.option(coap::OBSERVE).is_some();
+        let code = match (message.is_request(), observe) {
+            (true, false) => coap::POST,
+            (true, true) => coap::FETCH,
+            (false, false) => coap::CHANGED,
+            (false, true) => coap::CONTENT,
+        };
+
+        Ok(Message {
+            mtype: message.mtype,
+            code,
+            message_id: message.message_id,
+            token: message.token.clone(),
+            options: sort_options(outer),
+            payload: ciphertext,
+        })
+    }
+
+    /// Decrypts the payload of an OSCORE message, and returns the message
+    /// with its code, inner options and payload.
+    fn unprotect(
+        &self,
+        protected: &Message,
+        nonce: &[u8],
+        request_id: &RequestId,
+    ) -> Result<Message> {
+        let plaintext = util::aead_open(
+            self.aead,
+            &self.recipient_key,
+            nonce,
+            &protected.payload,
+            &self.aad(request_id)?,
+        )?;
+        let (code, rest) =
+            plaintext.split_first().ok_or(Error::InvalidMessage)?;
+        let (inner, payload) = coap::deserialize_options(rest)?;
+
+        // The inner options replace the outer ones, which are only kept
+        // where they are Class U alone
+        let mut options: Vec<_> = protected
+            .options
+            .iter()
+            .filter(|(number, _)| is_outer(*number) && !is_inner(*number))
+            .cloned()
+            .collect();
+        options.extend(inner.into_iter().filter(|(number, _)| is_inner(*number)));
+
+        Ok(Message {
+            mtype: protected.mtype,
+            code: *code,
+            message_id: protected.message_id,
+            token: protected.token.clone(),
+            options: sort_options(options),
+            payload,
+        })
+    }
+}
+
+/// Returns the OSCORE option of a protected message.
+fn read_option(protected: &Message) -> Result<OscoreOption> {
+    let value = protected.option(coap::OSCORE).ok_or(Error::NotProtected)?;
+    option::deserialize_option(value)
+}
+
+/// Returns whether an option is encrypted (Class E).
+fn is_inner(number: u16) -> bool {
+    !matches!(
+        number,
+        coap::URI_HOST
+            | coap::URI_PORT
+            | coap::OSCORE
+            | coap::PROXY_URI
+            | coap::PROXY_SCHEME
+    )
+}
+
+/// Returns whether an option is visible in the OSCORE message (Class U).
+fn is_outer(number: u16) -> bool {
+    matches!(
+        number,
+        coap::URI_HOST
+            | coap::OBSERVE
+            | coap::URI_PORT
+            | coap::PROXY_URI
+            | coap::PROXY_SCHEME
+    )
+}
+
+/// Sorts options by their numbers, keeping repeated options in order.
+fn sort_options(mut options: Vec<CoapOption>) -> Vec<CoapOption> {
+    options.sort_by_key(|(number, _)| *number);
+    options
+}
+
+/// Returns the sequence number of a Partial IV, which is at most 5 bytes.
+#[cfg(not(feature = "no_replay"))]
+fn decode_partial_iv(partial_iv: &[u8]) -> u64 {
+    partial_iv
+        .iter()
+        .fold(0, |number, byte| number << 8 | u64::from(*byte))
+}
+
+/// Checks that the token of a message to protect can be sent, before a
+/// sequence number is spent on it.
+fn check_token(message: &Message) -> Result<()> {
+    if message.token.len() > coap::MAX_TOKEN_LENGTH {
+        return Err(Error::InvalidMessage);
+    }
+
+    Ok(())
+}
+
+/// Returns the Partial IV of a sequence number, which is its big-endian
+/// encoding without leading zeros, but at least one byte.
+fn encode_partial_iv(sequence_number: u64) -> Vec<u8> {
+    let bytes = sequence_number.to_be_bytes();
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+
+    bytes[start..].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_vectors::*;
+    use super::*;
+    use crate::edhoc::{
+        suite::{SUITE_0, SUITE_24},
+        Version,
+    };
+
+    fn client(
+        master_salt: &[u8],
+        id_context: Option<&[u8]>,
+        client_id: &[u8],
+    ) -> SecurityContext {
+        SecurityContext::new(
+            &MASTER_SECRET,
+            master_salt,
+            id_context,
+            client_id,
+            &SERVER_ID,
+            Aead::AesCcm16_64_128,
+            Hash::Sha256,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn derivation() {
+        let context = client(&MASTER_SALT, None, &CLIENT_ID);
+        assert_eq!(context.sender_key(), &CLIENT_KEY);
+        assert_eq!(context.recipient_key(), &SERVER_KEY);
+        assert_eq!(context.common_iv(), &COMMON_IV);
+    }
+
+    #[test]
+    fn derivation_without_salt() {
+        let context = client(&[], None, &NOSALT_CLIENT_ID);
+        assert_eq!(context.sender_key(), &NOSALT_CLIENT_KEY);
+        assert_eq!(context.recipient_key(), &NOSALT_SERVER_KEY);
+        assert_eq!(context.common_iv(), &NOSALT_COMMON_IV);
+    }
+
+    #[test]
+    fn derivation_with_id_context() {
+        let context = client(&MASTER_SALT, Some(&ID_CONTEXT), &CLIENT_ID);
+        assert_eq!(context.sender_key(), &CONTEXT_CLIENT_KEY);
+        assert_eq!(context.recipient_key(), &CONTEXT_SERVER_KEY);
+        assert_eq!(context.common_iv(), &CONTEXT_COMMON_IV);
+    }
+
+    fn server() -> SecurityContext {
+        SecurityContext::new(
+            &MASTER_SECRET,
+            &MASTER_SALT,
+            None,
+            &SERVER_ID,
+            &CLIENT_ID,
+            Aead::AesCcm16_64_128,
+            Hash::Sha256,
+        )
+        .unwrap()
+    }
+
+    fn protect_vector(mut context: SecurityContext, protected: &[u8]) {
+        let request = coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        context.set_sender_sequence_number(SEQUENCE_NUMBER);
+        let (protected_request, request_id) =
+            context.protect_request(&request).unwrap();
+
+        assert_eq!(coap::serialize_message(&protected_request).unwrap(), protected);
+        assert_eq!(request_id.partial_iv, vec![0x14]);
+        assert_eq!(context.sender_sequence_number(), SEQUENCE_NUMBER + 1);
+    }
+
+    #[test]
+    fn protect_request() {
+        protect_vector(client(&MASTER_SALT, None, &CLIENT_ID), &PROTECTED_REQUEST);
+        protect_vector(
+            client(&[], None, &NOSALT_CLIENT_ID),
+            &NOSALT_PROTECTED_REQUEST,
+        );
+        protect_vector(
+            client(&MASTER_SALT, Some(&ID_CONTEXT), &CLIENT_ID),
+            &CONTEXT_PROTECTED_REQUEST,
+        );
+    }
+
+    #[test]
+    fn unprotect_request() {
+        let protected = coap::deserialize_message(&PROTECTED_REQUEST).unwrap();
+        let mut server = server();
+        let (request, request_id) =
+            server.unprotect_request(&protected).unwrap();
+
+        assert_eq!(coap::serialize_message(&request).unwrap(), UNPROTECTED_REQUEST.to_vec());
+        assert_eq!(
+            request_id,
+            RequestId {
+                kid: CLIENT_ID.to_vec(),
+                partial_iv: vec![0x14]
+            }
+        );
+    }
+
+    #[test]
+    fn responses() {
+        let response = coap::deserialize_message(&UNPROTECTED_RESPONSE).unwrap();
+        let request_id = RequestId {
+            kid: CLIENT_ID.to_vec(),
+            partial_iv: vec![0x14],
+        };
+        let mut server = server();
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+
+        let protected =
+            server.protect_response(&response, &request_id, false).unwrap();
+        assert_eq!(coap::serialize_message(&protected).unwrap(), PROTECTED_RESPONSE.to_vec());
+        assert_eq!(
+            client.unprotect_response(&protected, &request_id).unwrap(),
+            response
+        );
+
+        let protected =
+            server.protect_response(&response, &request_id, true).unwrap();
+        assert_eq!(
+            coap::serialize_message(&protected).unwrap(),
+            PIV_PROTECTED_RESPONSE.to_vec()
+        );
+        assert_eq!(
+            client.unprotect_response(&protected, &request_id).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn observe() {
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+        let mut server = server();
+        let mut request = coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        request.options.push((coap::OBSERVE, vec![]));
+        let request = Message {
+            options: sort_options(request.options),
+            ..request
+        };
+
+        let (protected, _) = client.protect_request(&request).unwrap();
+        assert_eq!(protected.code, coap::FETCH);
+        assert_eq!(protected.option(coap::OBSERVE), Some(&[][..]));
+        assert_eq!(protected.option(coap::URI_PATH), None);
+        let (unprotected, request_id) =
+            server.unprotect_request(&protected).unwrap();
+        assert_eq!(unprotected, request);
+
+        let mut notification =
+            coap::deserialize_message(&UNPROTECTED_RESPONSE).unwrap();
+        notification.options.push((coap::OBSERVE, vec![0x07]));
+        let protected =
+            server.protect_response(&notification, &request_id, true).unwrap();
+        assert_eq!(protected.code, coap::CONTENT);
+        assert_eq!(
+            client.unprotect_response(&protected, &request_id).unwrap(),
+            notification
+        );
+    }
+
+    #[test]
+    fn unprotect_failures() {
+        let mut server = server();
+        let request = coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        assert_eq!(
+            server.unprotect_request(&request),
+            Err(Error::NotProtected)
+        );
+
+        // Another client's request
+        let protected =
+            coap::deserialize_message(&NOSALT_PROTECTED_REQUEST).unwrap();
+        assert_eq!(server.unprotect_request(&protected), Err(Error::UnknownKid));
+
+        // A tampered ciphertext
+        let mut protected = coap::deserialize_message(&PROTECTED_REQUEST).unwrap();
+        protected.payload[0] ^= 1;
+        assert_eq!(server.unprotect_request(&protected), Err(Error::Aead));
+
+        // A request without Partial IV
+        let mut protected = coap::deserialize_message(&PROTECTED_REQUEST).unwrap();
+        protected.options[1].1 = vec![0x08];
+        assert_eq!(
+            server.unprotect_request(&protected),
+            Err(Error::MissingPartialIv)
+        );
+    }
+
+    #[test]
+    fn long_token() {
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+        let mut request =
+            coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        request.token = vec![0xAB; 16];
+        assert_eq!(
+            client.protect_request(&request),
+            Err(Error::InvalidMessage)
+        );
+        // No sequence number was spent on it
+        assert_eq!(client.sender_sequence_number(), 0);
+    }
+
+    #[test]
+    fn sequence_numbers() {
+        assert_eq!(encode_partial_iv(0), vec![0x00]);
+        assert_eq!(encode_partial_iv(0x0100), vec![0x01, 0x00]);
+
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+        let request = coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        client.set_sender_sequence_number(MAX_SEQUENCE_NUMBER);
+        let (_, request_id) = client.protect_request(&request).unwrap();
+        assert_eq!(request_id.partial_iv, vec![0xFF; 5]);
+        assert_eq!(
+            client.protect_request(&request),
+            Err(Error::SequenceNumberExhausted)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "no_replay"))]
+    fn replay() {
+        let mut server = server();
+        let protected = coap::deserialize_message(&PROTECTED_REQUEST).unwrap();
+        let (_, request_id) = server.unprotect_request(&protected).unwrap();
+        assert_eq!(server.unprotect_request(&protected), Err(Error::Replay));
+        assert_eq!(server.replay_window().highest(), Some(SEQUENCE_NUMBER));
+
+        // A notification replayed to the client
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+        let response = coap::deserialize_message(&UNPROTECTED_RESPONSE).unwrap();
+        let protected =
+            server.protect_response(&response, &request_id, true).unwrap();
+        client.unprotect_response(&protected, &request_id).unwrap();
+        assert_eq!(
+            client.unprotect_response(&protected, &request_id),
+            Err(Error::Replay)
+        );
+
+        // A restored server that only accepts newer requests
+        let mut server = self::server();
+        server.set_replay_window(ReplayWindow::above(SEQUENCE_NUMBER));
+        let protected = coap::deserialize_message(&PROTECTED_REQUEST).unwrap();
+        assert_eq!(server.unprotect_request(&protected), Err(Error::Replay));
+    }
+
+    #[test]
+    fn reserved_sequence_numbers() {
+        let mut client = client(&MASTER_SALT, None, &CLIENT_ID);
+        let request = coap::deserialize_message(&UNPROTECTED_REQUEST).unwrap();
+        assert_eq!(client.reserve_sequence_numbers(2), 2);
+        client.protect_request(&request).unwrap();
+        client.protect_request(&request).unwrap();
+        assert_eq!(
+            client.protect_request(&request),
+            Err(Error::SequenceNumbersNotReserved)
+        );
+        assert_eq!(client.reserve_sequence_numbers(2), 4);
+
+        // After a reboot, the client continues past the stored bound
+        let mut client = self::client(&MASTER_SALT, None, &CLIENT_ID);
+        client.restore_sequence_number(4);
+        assert_eq!(
+            client.protect_request(&request),
+            Err(Error::SequenceNumbersNotReserved)
+        );
+        assert_eq!(client.reserve_sequence_numbers(16), 20);
+        let (_, request_id) = client.protect_request(&request).unwrap();
+        assert_eq!(request_id.partial_iv, vec![0x04]);
+
+        client.set_sender_sequence_number(MAX_SEQUENCE_NUMBER);
+        assert_eq!(
+            client.reserve_sequence_numbers(16),
+            MAX_SEQUENCE_NUMBER + 1
+        );
+    }
+
+    #[test]
+    fn long_id() {
+        let context = SecurityContext::new(
+            &MASTER_SECRET,
+            &MASTER_SALT,
+            None,
+            &[0; 8],
+            &SERVER_ID,
+            Aead::AesCcm16_64_128,
+            Hash::Sha256,
+        );
+        assert_eq!(context, Err(Error::InvalidId));
+    }
+
+    #[test]
+    fn from_edhoc() {
+        for suite in &[SUITE_0, SUITE_24] {
+            let (_, prk_4e3m) =
+                util::extract_prk(suite.hash, None, &MASTER_SECRET).unwrap();
+            let session =
+                Session::new(Version::Rfc9528, *suite, &prk_4e3m, &MASTER_SALT)
+                    .unwrap();
+
+            let i_context =
+                SecurityContext::from_edhoc_initiator(&session, &[0x37], &[])
+                    .unwrap();
+            let r_context =
+                SecurityContext::from_edhoc_responder(&session, &[0x37], &[])
+                    .unwrap();
+            let (master_secret, master_salt) = session.oscore_secrets().unwrap();
+
+            assert_eq!(i_context.master_secret(), &master_secret[..]);
+            assert_eq!(i_context.master_salt(), &master_salt[..]);
+            assert_eq!(i_context.aead(), suite.app_aead);
+            assert_eq!(i_context.sender_id(), &[] as &[u8]);
+            assert_eq!(i_context.recipient_id(), &[0x37]);
+            assert_eq!(i_context.sender_key(), r_context.recipient_key());
+            assert_eq!(i_context.recipient_key(), r_context.sender_key());
+            assert_eq!(i_context.common_iv(), r_context.common_iv());
+            assert_eq!(
+                i_context.sender_key().len(),
+                suite.app_aead.key_length()
+            );
+        }
+    }
+}
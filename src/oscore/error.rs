@@ -0,0 +1,93 @@
+//! The errors of the `oscore` module.
+
+use core::fmt;
+
+use crate::{cbor, counter::CounterError, edhoc::error::Error as EdhocError};
+
+/// The catch-all error type for this module, mostly just wrapping errors from
+/// various libraries.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Wraps errors from the `cbor` module.
+    Cbor(cbor::CborError),
+    /// Wraps errors from `hkdf`.
+    Hkdf(hkdf::InvalidLength),
+    /// Error using the AEAD.
+    Aead,
+    /// A sender or recipient ID too long for the nonce of the AEAD.
+    InvalidId,
+    /// A malformed CoAP message.
+    InvalidMessage,
+    /// A message without the OSCORE option.
+    NotProtected,
+    /// A malformed OSCORE option.
+    InvalidOption,
+    /// A request for another recipient context than ours.
+    UnknownKid,
+    /// A request without a Partial IV.
+    MissingPartialIv,
+    /// All sequence numbers that fit in a Partial IV were used.
+    SequenceNumberExhausted,
+    /// All reserved sequence numbers were used.
+    SequenceNumbersNotReserved,
+    /// A message whose Partial IV was already received, or is too old.
+    Replay,
+    /// Wraps any other error from the `edhoc` module.
+    Edhoc(EdhocError),
+}
+
+impl From<cbor::CborError> for Error {
+    fn from(e: cbor::CborError) -> Error {
+        Error::Cbor(e)
+    }
+}
+
+impl From<hkdf::InvalidLength> for Error {
+    fn from(e: hkdf::InvalidLength) -> Error {
+        Error::Hkdf(e)
+    }
+}
+
+impl From<CounterError> for Error {
+    fn from(e: CounterError) -> Error {
+        match e {
+            CounterError::Exhausted => Error::SequenceNumberExhausted,
+            CounterError::NotReserved => Error::SequenceNumbersNotReserved,
+        }
+    }
+}
+
+impl From<EdhocError> for Error {
+    fn from(e: EdhocError) -> Error {
+        match e {
+            EdhocError::Cbor(e) => Error::Cbor(e),
+            EdhocError::Hkdf(e) => Error::Hkdf(e),
+            EdhocError::Aead => Error::Aead,
+            e => Error::Edhoc(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Cbor(e) => e.fmt(f),
+            Error::Hkdf(e) => e.fmt(f),
+            Error::Aead => write!(f, "Error using AEAD"),
+            Error::InvalidId => write!(f, "ID too long for the AEAD nonce"),
+            Error::InvalidMessage => write!(f, "Malformed CoAP message"),
+            Error::NotProtected => write!(f, "Message has no OSCORE option"),
+            Error::InvalidOption => write!(f, "Malformed OSCORE option"),
+            Error::UnknownKid => write!(f, "No security context for kid"),
+            Error::MissingPartialIv => write!(f, "Request has no Partial IV"),
+            Error::SequenceNumberExhausted => {
+                write!(f, "Sender sequence numbers exhausted")
+            }
+            Error::SequenceNumbersNotReserved => {
+                write!(f, "No sender sequence numbers reserved")
+            }
+            Error::Replay => write!(f, "Replayed message"),
+            Error::Edhoc(e) => e.fmt(f),
+        }
+    }
+}
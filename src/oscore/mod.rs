@@ -0,0 +1,30 @@
+//! Object Security for Constrained RESTful Environments (OSCORE).
+//!
+//! The security context of RFC 8613 is derived from a master secret and
+//! master salt, typically the ones an EDHOC `Session` exports. Its sender
+//! and recipient IDs are then the connection identifiers of the handshake,
+//! with each party receiving on its own identifier.
+//!
+//! A context protects and unprotects CoAP messages. Options that a proxy
+//! needs, like Uri-Host, stay in the outer message, while all others are
+//! encrypted together with the code and the payload. Observe is in both,
+//! and Proxy-Uri is not decomposed, so a proxy that forwards by it sees the
+//! whole URI.
+//!
+//! Received Partial IVs go through a replay window, which the `no_replay`
+//! feature turns off for benchmarking. Sender sequence numbers are a
+//! `counter::Counter`, which can be kept across reboots.
+
+pub mod coap;
+pub mod context;
+#[cfg_attr(tarpaulin, skip)]
+pub mod error;
+pub mod option;
+pub mod replay;
+#[cfg(test)]
+mod test_vectors;
+
+/// The result type for the `oscore` module.
+type Result<T> = core::result::Result<T, error::Error>;
+
+pub use context::{RequestId, SecurityContext};
@@ -0,0 +1,141 @@
+//! The value of the OSCORE option.
+
+use alloc::vec::Vec;
+
+use super::{error::Error, Result};
+
+/// The flag bit signalling a kid.
+const FLAG_KID: u8 = 0x08;
+/// The flag bit signalling a kid context.
+const FLAG_KID_CONTEXT: u8 = 0x10;
+/// The bits giving the length of the Partial IV.
+const MASK_PARTIAL_IV: u8 = 0x07;
+
+/// The OSCORE option, which is empty if none of its fields are present.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OscoreOption {
+    pub partial_iv: Option<Vec<u8>>,
+    pub kid_context: Option<Vec<u8>>,
+    pub kid: Option<Vec<u8>>,
+}
+
+/// Serializes the OSCORE option.
+pub fn serialize_option(option: &OscoreOption) -> Vec<u8> {
+    let mut flags = 0;
+    let mut bytes = vec![0];
+    if let Some(partial_iv) = &option.partial_iv {
+        flags |= partial_iv.len() as u8;
+        bytes.extend(partial_iv);
+    }
+    if let Some(kid_context) = &option.kid_context {
+        flags |= FLAG_KID_CONTEXT;
+        bytes.push(kid_context.len() as u8);
+        bytes.extend(kid_context);
+    }
+    if let Some(kid) = &option.kid {
+        flags |= FLAG_KID;
+        bytes.extend(kid);
+    }
+
+    if flags == 0 {
+        return Vec::new();
+    }
+    bytes[0] = flags;
+
+    bytes
+}
+
+/// Deserializes the OSCORE option.
+pub fn deserialize_option(bytes: &[u8]) -> Result<OscoreOption> {
+    let flags = match bytes.first() {
+        None => return Ok(OscoreOption::default()),
+        Some(flags) => *flags,
+    };
+    // The extension flag and the reserved bits and lengths are not in use
+    let piv_length = usize::from(flags & MASK_PARTIAL_IV);
+    if flags & !(FLAG_KID | FLAG_KID_CONTEXT | MASK_PARTIAL_IV) != 0
+        || piv_length > 5
+    {
+        return Err(Error::InvalidOption);
+    }
+
+    let mut rest = &bytes[1..];
+    let partial_iv = if piv_length > 0 {
+        Some(take(&mut rest, piv_length)?.to_vec())
+    } else {
+        None
+    };
+    let kid_context = if flags & FLAG_KID_CONTEXT != 0 {
+        let length = take(&mut rest, 1)?[0];
+        Some(take(&mut rest, usize::from(length))?.to_vec())
+    } else {
+        None
+    };
+    let kid = if flags & FLAG_KID != 0 {
+        Some(rest.to_vec())
+    } else if !rest.is_empty() {
+        return Err(Error::InvalidOption);
+    } else {
+        None
+    };
+
+    Ok(OscoreOption {
+        partial_iv,
+        kid_context,
+        kid,
+    })
+}
+
+/// Splits the first `n` bytes off `rest`.
+fn take<'a>(rest: &mut &'a [u8], n: usize) -> Result<&'a [u8]> {
+    if rest.len() < n {
+        return Err(Error::InvalidOption);
+    }
+    let (taken, remaining) = rest.split_at(n);
+    *rest = remaining;
+
+    Ok(taken)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn option() {
+        let option = OscoreOption {
+            partial_iv: Some(vec![0x14]),
+            kid_context: Some(vec![0x37, 0xCB]),
+            kid: Some(vec![0x01]),
+        };
+        let bytes = serialize_option(&option);
+        assert_eq!(bytes, vec![0x19, 0x14, 0x02, 0x37, 0xCB, 0x01]);
+        assert_eq!(deserialize_option(&bytes).unwrap(), option);
+
+        // An empty kid still sets the flag
+        let option = OscoreOption {
+            partial_iv: Some(vec![0x14]),
+            kid_context: None,
+            kid: Some(vec![]),
+        };
+        assert_eq!(serialize_option(&option), vec![0x09, 0x14]);
+        assert_eq!(deserialize_option(&[0x09, 0x14]).unwrap(), option);
+
+        assert_eq!(serialize_option(&OscoreOption::default()), Vec::<u8>::new());
+        assert_eq!(deserialize_option(&[]).unwrap(), OscoreOption::default());
+    }
+
+    #[test]
+    fn malformed_option() {
+        // Reserved Partial IV length
+        assert_eq!(deserialize_option(&[0x06]), Err(Error::InvalidOption));
+        // Extension flag
+        assert_eq!(deserialize_option(&[0x81, 0x00]), Err(Error::InvalidOption));
+        // Missing Partial IV
+        assert_eq!(deserialize_option(&[0x02, 0x00]), Err(Error::InvalidOption));
+        // Kid context longer than the option
+        assert_eq!(deserialize_option(&[0x10, 0x03, 0x00]), Err(Error::InvalidOption));
+        // Trailing bytes without the kid flag
+        assert_eq!(deserialize_option(&[0x01, 0x00, 0x01]), Err(Error::InvalidOption));
+    }
+}
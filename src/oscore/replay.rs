@@ -0,0 +1,127 @@
+//! Replay protection for the messages of a recipient context.
+
+use super::{error::Error, Result};
+
+/// The number of sequence numbers below the highest one received that are
+/// still accepted, which is the default of RFC 8613.
+pub const WINDOW_SIZE: u64 = 32;
+
+/// A sliding window over the sequence numbers received from the peer.
+///
+/// Sequence numbers above the window are accepted and move it up, those in
+/// it are accepted once, and those below it are rejected.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplayWindow {
+    /// The highest sequence number received, if any.
+    highest: Option<u64>,
+    /// Bit `i` is set if `highest - i` was received.
+    received: u32,
+}
+
+impl ReplayWindow {
+    /// Returns a window that accepts any sequence number.
+    pub fn new() -> ReplayWindow {
+        ReplayWindow::default()
+    }
+
+    /// Returns a window that only accepts sequence numbers above
+    /// `highest`, e.g. for a context restored after a reboot.
+    pub fn above(highest: u64) -> ReplayWindow {
+        ReplayWindow {
+            highest: Some(highest),
+            received: u32::MAX,
+        }
+    }
+
+    /// Returns the highest sequence number received, if any.
+    pub fn highest(&self) -> Option<u64> {
+        self.highest
+    }
+
+    /// Checks that a sequence number was not received before and is not too
+    /// old to tell.
+    pub fn check(&self, sequence_number: u64) -> Result<()> {
+        let highest = match self.highest {
+            Some(highest) if sequence_number <= highest => highest,
+            _ => return Ok(()),
+        };
+        let offset = highest - sequence_number;
+        if offset >= WINDOW_SIZE || self.received & 1 << offset != 0 {
+            return Err(Error::Replay);
+        }
+
+        Ok(())
+    }
+
+    /// Marks a sequence number as received, which is only done once the
+    /// message it belongs to was verified.
+    pub fn update(&mut self, sequence_number: u64) {
+        match self.highest {
+            Some(highest) if sequence_number <= highest => {
+                let offset = highest - sequence_number;
+                if offset < WINDOW_SIZE {
+                    self.received |= 1 << offset;
+                }
+            }
+            Some(highest) => {
+                let shift = sequence_number - highest;
+                self.received = if shift < WINDOW_SIZE {
+                    self.received << shift | 1
+                } else {
+                    1
+                };
+                self.highest = Some(sequence_number);
+            }
+            None => {
+                self.received = 1;
+                self.highest = Some(sequence_number);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn window() {
+        let mut window = ReplayWindow::new();
+        assert_eq!(window.check(5), Ok(()));
+        window.update(5);
+        assert_eq!(window.check(5), Err(Error::Replay));
+
+        // Out of order within the window
+        assert_eq!(window.check(3), Ok(()));
+        window.update(3);
+        assert_eq!(window.check(3), Err(Error::Replay));
+        assert_eq!(window.check(4), Ok(()));
+
+        // Moving the window up
+        window.update(40);
+        assert_eq!(window.highest(), Some(40));
+        assert_eq!(window.check(9), Ok(()));
+        assert_eq!(window.check(8), Err(Error::Replay));
+        window.update(9);
+        window.update(41);
+        assert_eq!(window.check(9), Err(Error::Replay));
+        assert_eq!(window.check(40), Err(Error::Replay));
+        assert_eq!(window.check(39), Ok(()));
+
+        // Jumping past the window forgets everything in it
+        window.update(1000);
+        assert_eq!(window.check(999), Ok(()));
+        assert_eq!(window.check(968), Err(Error::Replay));
+    }
+
+    #[test]
+    fn restored_window() {
+        let mut window = ReplayWindow::above(20);
+        assert_eq!(window.check(20), Err(Error::Replay));
+        assert_eq!(window.check(3), Err(Error::Replay));
+        assert_eq!(window.check(21), Ok(()));
+        window.update(22);
+        assert_eq!(window.check(21), Ok(()));
+        assert_eq!(window.check(20), Err(Error::Replay));
+    }
+}
@@ -0,0 +1,92 @@
+// Test Vector 1: Key Derivation with Master Salt (RFC 8613 Appendix C.1)
+pub const MASTER_SECRET: [u8; 16] = [
+    0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0A, 0x0B, 0x0C,
+    0x0D, 0x0E, 0x0F, 0x10,
+];
+pub const MASTER_SALT: [u8; 8] = [0x9E, 0x7C, 0xA9, 0x22, 0x23, 0x78, 0x63, 0x40];
+pub const CLIENT_ID: [u8; 0] = [];
+pub const SERVER_ID: [u8; 1] = [0x01];
+pub const CLIENT_KEY: [u8; 16] = [
+    0xF0, 0x91, 0x0E, 0xD7, 0x29, 0x5E, 0x6A, 0xD4, 0xB5, 0x4F, 0xC7, 0x93,
+    0x15, 0x43, 0x02, 0xFF,
+];
+pub const SERVER_KEY: [u8; 16] = [
+    0xFF, 0xB1, 0x4E, 0x09, 0x3C, 0x94, 0xC9, 0xCA, 0xC9, 0x47, 0x16, 0x48,
+    0xB4, 0xF9, 0x87, 0x10,
+];
+pub const COMMON_IV: [u8; 13] = [
+    0x46, 0x22, 0xD4, 0xDD, 0x6D, 0x94, 0x41, 0x68, 0xEE, 0xFB, 0x54, 0x98,
+    0x7C,
+];
+
+// Test Vector 2: Key Derivation without Master Salt (RFC 8613 Appendix C.2)
+pub const NOSALT_CLIENT_ID: [u8; 1] = [0x00];
+pub const NOSALT_CLIENT_KEY: [u8; 16] = [
+    0x32, 0x1B, 0x26, 0x94, 0x32, 0x53, 0xC7, 0xFF, 0xB6, 0x00, 0x3B, 0x0B,
+    0x64, 0xD7, 0x40, 0x41,
+];
+pub const NOSALT_SERVER_KEY: [u8; 16] = [
+    0xE5, 0x7B, 0x56, 0x35, 0x81, 0x51, 0x77, 0xCD, 0x67, 0x9A, 0xB4, 0xBC,
+    0xEC, 0x9D, 0x7D, 0xDA,
+];
+pub const NOSALT_COMMON_IV: [u8; 13] = [
+    0xBE, 0x35, 0xAE, 0x29, 0x7D, 0x2D, 0xAC, 0xE9, 0x10, 0xC5, 0x2E, 0x99,
+    0xF9,
+];
+
+// Test Vector 3: Key Derivation with ID Context (RFC 8613 Appendix C.3)
+pub const ID_CONTEXT: [u8; 8] = [0x37, 0xCB, 0xF3, 0x21, 0x00, 0x17, 0xA2, 0xD3];
+pub const CONTEXT_CLIENT_KEY: [u8; 16] = [
+    0xAF, 0x2A, 0x13, 0x00, 0xA5, 0xE9, 0x57, 0x88, 0xB3, 0x56, 0x33, 0x6E,
+    0xEE, 0xCD, 0x2B, 0x92,
+];
+pub const CONTEXT_SERVER_KEY: [u8; 16] = [
+    0xE3, 0x9A, 0x0C, 0x7C, 0x77, 0xB4, 0x3F, 0x03, 0xB4, 0xB3, 0x9A, 0xB9,
+    0xA2, 0x68, 0x69, 0x9F,
+];
+pub const CONTEXT_COMMON_IV: [u8; 13] = [
+    0x2C, 0xA5, 0x8F, 0xB8, 0x5F, 0xF1, 0xB8, 0x1C, 0x0B, 0x71, 0x81, 0xB8,
+    0x5E,
+];
+
+// Test Vectors 4 to 6: OSCORE Requests of the clients of Test Vectors 1 to 3,
+// with sender sequence number 20 (RFC 8613 Appendix C.4 to C.6)
+pub const SEQUENCE_NUMBER: u64 = 20;
+pub const UNPROTECTED_REQUEST: [u8; 22] = [
+    0x44, 0x01, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x39, 0x6C, 0x6F, 0x63,
+    0x61, 0x6C, 0x68, 0x6F, 0x73, 0x74, 0x83, 0x74, 0x76, 0x31,
+];
+pub const PROTECTED_REQUEST: [u8; 35] = [
+    0x44, 0x02, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x39, 0x6C, 0x6F, 0x63,
+    0x61, 0x6C, 0x68, 0x6F, 0x73, 0x74, 0x62, 0x09, 0x14, 0xFF, 0x61, 0x2F,
+    0x10, 0x92, 0xF1, 0x77, 0x6F, 0x1C, 0x16, 0x68, 0xB3, 0x82, 0x5E,
+];
+pub const NOSALT_PROTECTED_REQUEST: [u8; 36] = [
+    0x44, 0x02, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x39, 0x6C, 0x6F, 0x63,
+    0x61, 0x6C, 0x68, 0x6F, 0x73, 0x74, 0x63, 0x09, 0x14, 0x00, 0xFF, 0x4E,
+    0xD3, 0x39, 0xA5, 0xA3, 0x79, 0xB0, 0xB8, 0xBC, 0x73, 0x1F, 0xFF, 0xB0,
+];
+pub const CONTEXT_PROTECTED_REQUEST: [u8; 44] = [
+    0x44, 0x02, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x39, 0x6C, 0x6F, 0x63,
+    0x61, 0x6C, 0x68, 0x6F, 0x73, 0x74, 0x6B, 0x19, 0x14, 0x08, 0x37, 0xCB,
+    0xF3, 0x21, 0x00, 0x17, 0xA2, 0xD3, 0xFF, 0x72, 0xCD, 0x72, 0x73, 0xFD,
+    0x33, 0x1A, 0xC4, 0x5C, 0xFF, 0xBE, 0x55, 0xC3,
+];
+
+// Test Vectors 7 and 8: OSCORE Responses of the server of Test Vector 1 to
+// the request of Test Vector 4, the latter with its own Partial IV from
+// sender sequence number 0 (RFC 8613 Appendix C.7 and C.8)
+pub const UNPROTECTED_RESPONSE: [u8; 21] = [
+    0x64, 0x45, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0xFF, 0x48, 0x65, 0x6C,
+    0x6C, 0x6F, 0x20, 0x57, 0x6F, 0x72, 0x6C, 0x64, 0x21,
+];
+pub const PROTECTED_RESPONSE: [u8; 32] = [
+    0x64, 0x44, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x90, 0xFF, 0xDB, 0xAA,
+    0xD1, 0xE9, 0xA7, 0xE7, 0xB2, 0xA8, 0x13, 0xD3, 0xC3, 0x15, 0x24, 0x37,
+    0x83, 0x03, 0xCD, 0xAF, 0xAE, 0x11, 0x91, 0x06,
+];
+pub const PIV_PROTECTED_RESPONSE: [u8; 34] = [
+    0x64, 0x44, 0x5D, 0x1F, 0x00, 0x00, 0x39, 0x74, 0x92, 0x01, 0x00, 0xFF,
+    0x4D, 0x4C, 0x13, 0x66, 0x93, 0x84, 0xB6, 0x73, 0x54, 0xB2, 0xB6, 0x17,
+    0x5F, 0xF4, 0xB8, 0x65, 0x8C, 0x66, 0x6A, 0x6C, 0xF8, 0x8E,
+];
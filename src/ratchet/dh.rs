@@ -0,0 +1,391 @@
+//! The Diffie-Hellman ratchet, which mixes fresh ephemeral keys into `rk`.
+
+use alloc::vec::Vec;
+use serde_bytes::{ByteBuf, Bytes};
+use x25519_dalek_ng::StaticSecret;
+
+use super::{error::Error, Result};
+use crate::{
+    cbor,
+    edhoc::{util, DhSecret},
+};
+
+/// The party of the EDHOC handshake a ratchet belongs to.
+///
+/// The initiator starts the steps, which suits a Class A device that can
+/// only receive after sending, and the responder answers them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Role {
+    Initiator,
+    Responder,
+}
+
+/// The Diffie-Hellman ratchet of one party, holding the current root key
+/// and the `sck` and `rck` derived with it.
+///
+/// A step exchanges new ephemeral public keys in two ratchet messages,
+/// which can be carried along with application traffic. Each message holds
+/// the number of the step and the public key of the sender. Once both
+/// messages went through, the shared secret of the ephemeral keys is mixed
+/// into `rk`, so that someone who learned the keys before the step can't
+/// follow along.
+pub struct DhRatchet<K: DhSecret = StaticSecret> {
+    role: Role,
+    /// The number of steps completed.
+    epoch: u32,
+    rk: Vec<u8>,
+    sck: Vec<u8>,
+    rck: Vec<u8>,
+    /// The ephemeral key of a step the initiator started.
+    pending: Option<K>,
+    /// The step the responder answered, until the initiator confirms it.
+    answered: Option<Answered>,
+}
+
+/// A step the responder answered, with the keys it results in and the
+/// messages, in case the response gets lost and the request is repeated.
+struct Answered {
+    request: Vec<u8>,
+    response: Vec<u8>,
+    keys: Keys,
+}
+
+/// The root key, `sck` and `rck` of a step.
+struct Keys {
+    rk: Vec<u8>,
+    sck: Vec<u8>,
+    rck: Vec<u8>,
+}
+
+impl<K: DhSecret> DhRatchet<K> {
+    /// Starts the ratchet from the keys of the handshake, as they are
+    /// returned by `verify_message_3` for the responder and by
+    /// `handle_message_4` for the initiator.
+    pub fn new(role: Role, rk: Vec<u8>, sck: Vec<u8>, rck: Vec<u8>) -> DhRatchet<K> {
+        DhRatchet {
+            role,
+            epoch: 0,
+            rk,
+            sck,
+            rck,
+            pending: None,
+            answered: None,
+        }
+    }
+
+    /// Returns the number of steps completed.
+    pub fn epoch(&self) -> u32 {
+        self.epoch
+    }
+
+    /// Returns the current root key.
+    pub fn rk(&self) -> &[u8] {
+        &self.rk
+    }
+
+    /// Returns the current sending chain key.
+    pub fn sck(&self) -> &[u8] {
+        &self.sck
+    }
+
+    /// Returns the current receiving chain key.
+    pub fn rck(&self) -> &[u8] {
+        &self.rck
+    }
+
+    /// Returns the `rck` of the step the responder answered, under which
+    /// the initiator sends once it got the response.
+    pub fn pending_rck(&self) -> Option<&[u8]> {
+        self.answered.as_ref().map(|answered| &answered.keys.rck[..])
+    }
+
+    /// Starts a step as the initiator, returning the ratchet message with
+    /// the public key of `ephemeral` for the responder.
+    ///
+    /// Starting again before the step finished replaces the ephemeral key.
+    pub fn start_step(&mut self, ephemeral: K) -> Result<Vec<u8>> {
+        if self.role != Role::Initiator {
+            return Err(Error::WrongRole);
+        }
+        let message = serialize_ratchet_key::<K>(self.epoch + 1, &ephemeral)?;
+        self.pending = Some(ephemeral);
+
+        Ok(message)
+    }
+
+    /// Answers a step the initiator started as the responder, and returns
+    /// the ratchet message for the initiator.
+    ///
+    /// The keys stay the same until `confirm_step`, since the response may
+    /// get lost. A repeated request is answered with the same response.
+    pub fn respond_step(&mut self, message: &[u8], ephemeral: K) -> Result<Vec<u8>> {
+        if self.role != Role::Responder {
+            return Err(Error::WrongRole);
+        }
+        let (epoch, peer_public) = decode_ratchet_key::<K>(message)?;
+        // Starting the step after the answered one shows the initiator got
+        // the response
+        if self.answered.is_some() && epoch == self.epoch + 2 {
+            self.confirm_step()?;
+        }
+        if epoch != self.epoch + 1 {
+            return Err(Error::UnexpectedEpoch);
+        }
+        if let Some(answered) = &self.answered {
+            if answered.request == message {
+                return Ok(answered.response.clone());
+            }
+        }
+
+        // Otherwise the initiator started over with another ephemeral key
+        let response = serialize_ratchet_key::<K>(epoch, &ephemeral)?;
+        let keys = self.derive(&ephemeral.diffie_hellman(&peer_public))?;
+        self.answered = Some(Answered {
+            request: message.to_vec(),
+            response: response.clone(),
+            keys,
+        });
+
+        Ok(response)
+    }
+
+    /// Switches the responder to the keys of the step it answered, which
+    /// is called once a message from the initiator could be decrypted with
+    /// `pending_rck`.
+    pub fn confirm_step(&mut self) -> Result<()> {
+        if self.role != Role::Responder {
+            return Err(Error::WrongRole);
+        }
+        let answered = self.answered.take().ok_or(Error::NoStepStarted)?;
+        self.update(answered.keys);
+
+        Ok(())
+    }
+
+    /// Finishes the step the initiator started with the responder's
+    /// ratchet message.
+    pub fn finish_step(&mut self, message: &[u8]) -> Result<()> {
+        if self.role != Role::Initiator {
+            return Err(Error::WrongRole);
+        }
+        if self.pending.is_none() {
+            return Err(Error::NoStepStarted);
+        }
+        let (epoch, peer_public) = decode_ratchet_key::<K>(message)?;
+        if epoch != self.epoch + 1 {
+            return Err(Error::UnexpectedEpoch);
+        }
+        // Only forget the ephemeral key once the response checked out
+        let ephemeral = self.pending.take().ok_or(Error::NoStepStarted)?;
+        let keys = self.derive(&ephemeral.diffie_hellman(&peer_public))?;
+        self.update(keys);
+
+        Ok(())
+    }
+
+    /// Derives the next root key, `sck` and `rck` from the current root
+    /// key and the shared secret of the step.
+    fn derive(&self, shared_secret: &[u8]) -> Result<Keys> {
+        let rk = util::extract_expand(shared_secret, &self.rk, "RK", 32)?;
+        let uplink =
+            util::extract_expand(shared_secret, &self.rk, "UPLINK", 32)?;
+        let downlink =
+            util::extract_expand(shared_secret, &self.rk, "DOWNLINK", 32)?;
+
+        // The keys are assigned like the handshake does
+        let (sck, rck) = match self.role {
+            Role::Initiator => (downlink, uplink),
+            Role::Responder => (uplink, downlink),
+        };
+
+        Ok(Keys { rk, sck, rck })
+    }
+
+    /// Completes a step with its keys.
+    fn update(&mut self, keys: Keys) {
+        self.rk = keys.rk;
+        self.sck = keys.sck;
+        self.rck = keys.rck;
+        self.epoch += 1;
+    }
+}
+
+/// Returns the step number and the public key of a ratchet message.
+fn decode_ratchet_key<K: DhSecret>(message: &[u8]) -> Result<(u32, K::Public)> {
+    let mut temp = Vec::with_capacity(message.len() + 1);
+    let (epoch, public_key): (u32, ByteBuf) =
+        cbor::decode_sequence(message, 2, &mut temp)?;
+
+    Ok((epoch, K::decode_public(&public_key)?))
+}
+
+/// Serializes a ratchet message, the CBOR sequence of the step number and
+/// the public key of the ephemeral key.
+fn serialize_ratchet_key<K: DhSecret>(epoch: u32, ephemeral: &K) -> Result<Vec<u8>> {
+    let public_key = K::encode_public(&ephemeral.public_key());
+
+    Ok(cbor::encode_sequence((epoch, Bytes::new(&public_key)))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edhoc::test_vectors::{RFC_DOWNLINK, RFC_RK0, RFC_UPLINK};
+
+    /// Returns the ratchets of the device and the server as they start out
+    /// after the handshake of RFC 9529, where the device is the initiator.
+    fn ratchets() -> (DhRatchet, DhRatchet) {
+        (
+            DhRatchet::new(
+                Role::Initiator,
+                RFC_RK0.to_vec(),
+                RFC_DOWNLINK.to_vec(),
+                RFC_UPLINK.to_vec(),
+            ),
+            DhRatchet::new(
+                Role::Responder,
+                RFC_RK0.to_vec(),
+                RFC_UPLINK.to_vec(),
+                RFC_DOWNLINK.to_vec(),
+            ),
+        )
+    }
+
+    fn step(device: &mut DhRatchet, server: &mut DhRatchet, seed: u8) {
+        let request = device
+            .start_step(StaticSecret::from([seed; 32]))
+            .unwrap();
+        let response = server
+            .respond_step(&request, StaticSecret::from([seed + 1; 32]))
+            .unwrap();
+        device.finish_step(&response).unwrap();
+        server.confirm_step().unwrap();
+    }
+
+    #[test]
+    fn steps() {
+        let (mut device, mut server) = ratchets();
+
+        step(&mut device, &mut server, 1);
+        assert_eq!((device.epoch(), server.epoch()), (1, 1));
+        assert_eq!(device.rk(), server.rk());
+        assert_ne!(device.rk(), &RFC_RK0);
+        assert_eq!(device.sck(), server.rck());
+        assert_eq!(device.rck(), server.sck());
+        assert_ne!(device.sck(), &RFC_DOWNLINK);
+
+        let rk = device.rk().to_vec();
+        step(&mut device, &mut server, 3);
+        assert_eq!((device.epoch(), server.epoch()), (2, 2));
+        assert_eq!(device.rk(), server.rk());
+        assert_ne!(device.rk(), &rk[..]);
+    }
+
+    #[test]
+    fn leaked_keys() {
+        let (mut device, mut server) = ratchets();
+        // Someone who learned the device's keys before the step follows
+        // along with its own ephemeral key
+        let (mut attacker, _) = ratchets();
+
+        let request = device.start_step(StaticSecret::from([1; 32])).unwrap();
+        let response = server
+            .respond_step(&request, StaticSecret::from([2; 32]))
+            .unwrap();
+        device.finish_step(&response).unwrap();
+        attacker.start_step(StaticSecret::from([7; 32])).unwrap();
+        attacker.finish_step(&response).unwrap();
+
+        assert_eq!(attacker.epoch(), device.epoch());
+        assert_ne!(attacker.rk(), device.rk());
+        assert_ne!(attacker.rck(), device.rck());
+    }
+
+    #[test]
+    fn out_of_step() {
+        let (mut device, mut server) = ratchets();
+        let request = device.start_step(StaticSecret::from([1; 32])).unwrap();
+
+        assert_eq!(
+            server.start_step(StaticSecret::from([2; 32])).err(),
+            Some(Error::WrongRole)
+        );
+        assert_eq!(
+            device.respond_step(&request, StaticSecret::from([2; 32])).err(),
+            Some(Error::WrongRole)
+        );
+        assert_eq!(device.confirm_step(), Err(Error::WrongRole));
+        assert_eq!(server.confirm_step(), Err(Error::NoStepStarted));
+        let (mut fresh_device, _) = ratchets();
+        let response = server
+            .respond_step(&request, StaticSecret::from([2; 32]))
+            .unwrap();
+        assert_eq!(fresh_device.finish_step(&response), Err(Error::NoStepStarted));
+
+        device.finish_step(&response).unwrap();
+        assert_eq!(device.finish_step(&response), Err(Error::NoStepStarted));
+        assert_eq!(server.pending_rck(), Some(device.sck()));
+        server.confirm_step().unwrap();
+        assert_eq!(device.rk(), server.rk());
+
+        // A replayed request is for a step the server already took
+        assert_eq!(
+            server.respond_step(&request, StaticSecret::from([3; 32])).err(),
+            Some(Error::UnexpectedEpoch)
+        );
+        assert_eq!(server.epoch(), 1);
+    }
+
+    #[test]
+    fn lost_response() {
+        let (mut device, mut server) = ratchets();
+        let request = device.start_step(StaticSecret::from([1; 32])).unwrap();
+        let response = server
+            .respond_step(&request, StaticSecret::from([2; 32]))
+            .unwrap();
+
+        // The response is lost, so the server keeps using the old keys
+        assert_eq!(server.epoch(), 0);
+        assert_eq!(server.rck(), device.sck());
+        assert_eq!(server.sck(), device.rck());
+
+        // The repeated request gets the same response, even though the
+        // server has a new ephemeral key at hand
+        let repeated = server
+            .respond_step(&request, StaticSecret::from([3; 32]))
+            .unwrap();
+        assert_eq!(repeated, response);
+        device.finish_step(&repeated).unwrap();
+
+        // The first message under the new keys confirms the step
+        assert_eq!(server.pending_rck(), Some(device.sck()));
+        server.confirm_step().unwrap();
+        assert_eq!((device.epoch(), server.epoch()), (1, 1));
+        assert_eq!(device.rk(), server.rk());
+        assert_eq!(device.rck(), server.sck());
+        assert_eq!(server.pending_rck(), None);
+    }
+
+    #[test]
+    fn implicit_confirmation() {
+        let (mut device, mut server) = ratchets();
+        let request = device.start_step(StaticSecret::from([1; 32])).unwrap();
+        let response = server
+            .respond_step(&request, StaticSecret::from([2; 32]))
+            .unwrap();
+        device.finish_step(&response).unwrap();
+
+        // Starting the next step confirms the previous one as well
+        let request = device.start_step(StaticSecret::from([3; 32])).unwrap();
+        let response = server
+            .respond_step(&request, StaticSecret::from([4; 32]))
+            .unwrap();
+        assert_eq!(server.epoch(), 1);
+        assert_eq!(device.rk(), server.rk());
+
+        device.finish_step(&response).unwrap();
+        server.confirm_step().unwrap();
+        assert_eq!((device.epoch(), server.epoch()), (2, 2));
+        assert_eq!(device.rk(), server.rk());
+    }
+}
@@ -0,0 +1,70 @@
+//! The errors of the `ratchet` module.
+
+use core::fmt;
+
+use crate::{cbor, edhoc::error::Error as EdhocError};
+
+/// The catch-all error type for this module.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Wraps errors from the `cbor` module.
+    Cbor(cbor::CborError),
+    /// Wraps errors from `hkdf`.
+    Hkdf(hkdf::InvalidLength),
+    /// A public key is not a valid point of the group.
+    InvalidKey,
+    /// A message further ahead of the chain than the skipped keys kept.
+    TooManySkipped,
+    /// A message whose key was already used, or dropped.
+    Replay,
+    /// All counters of a chain were used.
+    CounterExhausted,
+    /// A ratchet message for another step than the next one.
+    UnexpectedEpoch,
+    /// A response to a step that wasn't started.
+    NoStepStarted,
+    /// An action of the other party's role.
+    WrongRole,
+    /// Wraps any other error from the `edhoc` module.
+    Edhoc(EdhocError),
+}
+
+impl From<cbor::CborError> for Error {
+    fn from(e: cbor::CborError) -> Error {
+        Error::Cbor(e)
+    }
+}
+
+impl From<hkdf::InvalidLength> for Error {
+    fn from(e: hkdf::InvalidLength) -> Error {
+        Error::Hkdf(e)
+    }
+}
+
+impl From<EdhocError> for Error {
+    fn from(e: EdhocError) -> Error {
+        match e {
+            EdhocError::Cbor(e) => Error::Cbor(e),
+            EdhocError::Hkdf(e) => Error::Hkdf(e),
+            EdhocError::InvalidKey => Error::InvalidKey,
+            e => Error::Edhoc(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Cbor(e) => e.fmt(f),
+            Error::Hkdf(e) => e.fmt(f),
+            Error::InvalidKey => write!(f, "Invalid public key"),
+            Error::TooManySkipped => write!(f, "Too many skipped messages"),
+            Error::Replay => write!(f, "Message key already used"),
+            Error::CounterExhausted => write!(f, "Chain counter exhausted"),
+            Error::UnexpectedEpoch => write!(f, "Ratchet message out of step"),
+            Error::NoStepStarted => write!(f, "No ratchet step started"),
+            Error::WrongRole => write!(f, "Ratchet step of the other role"),
+            Error::Edhoc(e) => e.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,22 @@
+//! Key ratchets driven by the root key `rk` both parties derive with EDHOC.
+//!
+//! The symmetric ratchet turns `rk` into a chain of keys for each
+//! direction of the link, deriving a fresh key for every message and
+//! forgetting the ones before it, so that a key leaking later doesn't expose
+//! earlier messages. Both parties run the same ratchet, differing only in
+//! the direction they send on.
+//!
+//! The Diffie-Hellman ratchet exchanges new ephemeral keys in-band, and
+//! mixes their shared secret into `rk` to get fresh `sck` and `rck`. That
+//! way the link recovers from a leaked key without another handshake.
+
+pub mod dh;
+#[cfg_attr(tarpaulin, skip)]
+pub mod error;
+pub mod symmetric;
+
+/// The result type for the `ratchet` module.
+type Result<T> = core::result::Result<T, error::Error>;
+
+pub use dh::{DhRatchet, Role};
+pub use symmetric::{Direction, SymmetricRatchet};
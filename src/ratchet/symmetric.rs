@@ -0,0 +1,280 @@
+//! The symmetric ratchet, a KDF chain for each direction of the link.
+
+use alloc::vec::Vec;
+
+use super::{error::Error, Result};
+use crate::edhoc::util;
+
+/// The number of keys of skipped messages a receiving chain keeps, which is
+/// also how far ahead of the chain a message may be.
+pub const MAX_SKIP: u32 = 32;
+
+/// The length in bytes of chain and message keys.
+pub const KEY_LENGTH: usize = 32;
+
+/// A direction of the link, where the device sends on the uplink and the
+/// server on the downlink.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Uplink,
+    Downlink,
+}
+
+impl Direction {
+    /// Returns the other direction.
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::Uplink => Direction::Downlink,
+            Direction::Downlink => Direction::Uplink,
+        }
+    }
+
+    /// Returns the label the first chain key is derived from `rk` with.
+    fn chain_label(self) -> &'static str {
+        match self {
+            Direction::Uplink => "UPLINK_CHAIN",
+            Direction::Downlink => "DOWNLINK_CHAIN",
+        }
+    }
+}
+
+/// A KDF chain, where every step derives the key of a message and the
+/// chain key of the next step.
+#[derive(Clone, Debug, PartialEq)]
+struct Chain {
+    key: Vec<u8>,
+    /// The counter of the message the next step is for.
+    counter: u32,
+}
+
+impl Chain {
+    /// Returns the chain of a direction, starting from the root key.
+    fn new(rk: &[u8], direction: Direction) -> Result<Chain> {
+        Ok(Chain {
+            key: util::extract_expand(rk, b"", direction.chain_label(), KEY_LENGTH)?,
+            counter: 0,
+        })
+    }
+
+    /// Returns the counter and key of the next message, and moves the chain
+    /// on, forgetting the chain key that derived them.
+    fn step(&mut self) -> Result<(u32, Vec<u8>)> {
+        let counter = self.counter;
+        let next_counter = counter.checked_add(1).ok_or(Error::CounterExhausted)?;
+        let message_key =
+            util::extract_expand(&self.key, b"", "MESSAGE_KEY", KEY_LENGTH)?;
+        self.key = util::extract_expand(&self.key, b"", "CHAIN_KEY", KEY_LENGTH)?;
+        self.counter = next_counter;
+
+        Ok((counter, message_key))
+    }
+}
+
+/// The symmetric ratchet of one party, with a chain for sending and one for
+/// receiving.
+///
+/// Messages are numbered by their counter in the chain. A received message
+/// may be up to `MAX_SKIP` messages ahead of its chain, and the keys of the
+/// messages skipped that way are kept until they arrive, or until newer
+/// skipped keys push them out.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SymmetricRatchet {
+    sending: Chain,
+    receiving: Chain,
+    /// The counters and keys of skipped messages, oldest first.
+    skipped: Vec<(u32, Vec<u8>)>,
+}
+
+impl SymmetricRatchet {
+    /// Starts the ratchet from the root key `rk`.
+    ///
+    /// # Arguments
+    /// * `rk` - The root key both parties derived.
+    /// * `sending` - The direction this party sends on.
+    pub fn new(rk: &[u8], sending: Direction) -> Result<SymmetricRatchet> {
+        Ok(SymmetricRatchet {
+            sending: Chain::new(rk, sending)?,
+            receiving: Chain::new(rk, sending.reverse())?,
+            skipped: Vec::new(),
+        })
+    }
+
+    /// Returns the counter and key of the next message to send.
+    pub fn next_sending_key(&mut self) -> Result<(u32, Vec<u8>)> {
+        self.sending.step()
+    }
+
+    /// Returns the key of the received message with the given counter,
+    /// leaving the ratchet as it is.
+    ///
+    /// Once the message is authenticated with the key, `commit` moves the
+    /// ratchet past it, so a forged counter can't use up any keys.
+    pub fn receiving_key(&self, counter: u32) -> Result<Vec<u8>> {
+        if counter < self.receiving.counter {
+            return self
+                .skipped
+                .iter()
+                .find(|(skipped, _)| *skipped == counter)
+                .map(|(_, key)| key.clone())
+                .ok_or(Error::Replay);
+        }
+        if counter - self.receiving.counter > MAX_SKIP {
+            return Err(Error::TooManySkipped);
+        }
+
+        // Step a copy of the chain up to the message
+        let mut receiving = self.receiving.clone();
+        while receiving.counter < counter {
+            receiving.step()?;
+        }
+        let (_, message_key) = receiving.step()?;
+        Ok(message_key)
+    }
+
+    /// Moves the ratchet past the received message with the given counter,
+    /// after it was authenticated with the key from `receiving_key`.
+    ///
+    /// Its key is forgotten, and the keys of the messages it skipped are
+    /// kept until they arrive.
+    pub fn commit(&mut self, counter: u32) -> Result<()> {
+        if counter < self.receiving.counter {
+            let position = self
+                .skipped
+                .iter()
+                .position(|(skipped, _)| *skipped == counter)
+                .ok_or(Error::Replay)?;
+            self.skipped.remove(position);
+            return Ok(());
+        }
+        if counter - self.receiving.counter > MAX_SKIP {
+            return Err(Error::TooManySkipped);
+        }
+
+        while self.receiving.counter < counter {
+            let skipped = self.receiving.step()?;
+            self.skipped.push(skipped);
+        }
+        let excess = self.skipped.len().saturating_sub(MAX_SKIP as usize);
+        self.skipped.drain(..excess);
+        self.receiving.step()?;
+
+        Ok(())
+    }
+
+    /// Returns the counter of the next message to send.
+    pub fn sending_counter(&self) -> u32 {
+        self.sending.counter
+    }
+
+    /// Returns the counter of the next message expected.
+    pub fn receiving_counter(&self) -> u32 {
+        self.receiving.counter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::edhoc::test_vectors::RFC_RK0;
+
+    /// Returns the ratchets of the device and the server, started from the
+    /// root key of the RFC 9529 session.
+    fn ratchets() -> (SymmetricRatchet, SymmetricRatchet) {
+        (
+            SymmetricRatchet::new(&RFC_RK0, Direction::Uplink).unwrap(),
+            SymmetricRatchet::new(&RFC_RK0, Direction::Downlink).unwrap(),
+        )
+    }
+
+    /// Looks up the key of a message and commits it, as if it was
+    /// authenticated.
+    fn receive(ratchet: &mut SymmetricRatchet, counter: u32) -> Result<Vec<u8>> {
+        let key = ratchet.receiving_key(counter)?;
+        ratchet.commit(counter)?;
+        Ok(key)
+    }
+
+    #[test]
+    fn in_order() {
+        let (mut device, mut server) = ratchets();
+        for expected in 0..4 {
+            let (counter, key) = device.next_sending_key().unwrap();
+            assert_eq!(counter, expected);
+            assert_eq!(receive(&mut server, counter).unwrap(), key);
+
+            let (counter, key) = server.next_sending_key().unwrap();
+            assert_eq!(receive(&mut device, counter).unwrap(), key);
+        }
+
+        // Every message has its own key, and the directions differ
+        let (_, up) = device.next_sending_key().unwrap();
+        let (_, next_up) = device.next_sending_key().unwrap();
+        let (_, down) = server.next_sending_key().unwrap();
+        assert_ne!(up, next_up);
+        assert_ne!(up, down);
+        assert_eq!(up.len(), KEY_LENGTH);
+    }
+
+    #[test]
+    fn skipped_messages() {
+        let (mut device, mut server) = ratchets();
+        let keys: Vec<_> =
+            (0..5).map(|_| device.next_sending_key().unwrap()).collect();
+
+        // Out of order, with the first message lost
+        assert_eq!(receive(&mut server, 3).unwrap(), keys[3].1);
+        assert_eq!(receive(&mut server, 1).unwrap(), keys[1].1);
+        assert_eq!(receive(&mut server, 4).unwrap(), keys[4].1);
+        assert_eq!(receive(&mut server, 2).unwrap(), keys[2].1);
+        assert_eq!(server.receiving_counter(), 5);
+
+        // Keys are only handed out once
+        assert_eq!(receive(&mut server, 3), Err(Error::Replay));
+        assert_eq!(receive(&mut server, 0).unwrap(), keys[0].1);
+        assert_eq!(receive(&mut server, 0), Err(Error::Replay));
+    }
+
+    #[test]
+    fn bounded_skipping() {
+        let (mut device, mut server) = ratchets();
+        let keys: Vec<_> = (0..=2 * MAX_SKIP + 1)
+            .map(|_| device.next_sending_key().unwrap())
+            .collect();
+
+        assert_eq!(
+            receive(&mut server, MAX_SKIP + 1),
+            Err(Error::TooManySkipped)
+        );
+        let counter = MAX_SKIP as usize;
+        assert_eq!(receive(&mut server, MAX_SKIP).unwrap(), keys[counter].1);
+        let counter = 2 * MAX_SKIP as usize + 1;
+        assert_eq!(
+            receive(&mut server, counter as u32).unwrap(),
+            keys[counter].1
+        );
+
+        // The oldest skipped keys were dropped for newer ones
+        assert_eq!(receive(&mut server, 0), Err(Error::Replay));
+        assert_eq!(
+            receive(&mut server, MAX_SKIP + 1).unwrap(),
+            keys[MAX_SKIP as usize + 1].1
+        );
+    }
+
+    #[test]
+    fn forged_counter() {
+        let (mut device, mut server) = ratchets();
+        let (counter, key) = device.next_sending_key().unwrap();
+
+        // A forged message far ahead fails authentication, so it's never
+        // committed and takes no keys with it
+        let forged = server.receiving_key(MAX_SKIP).unwrap();
+        assert_ne!(forged, key);
+        assert_eq!(server.receiving_counter(), 0);
+
+        assert_eq!(server.receiving_key(counter).unwrap(), key);
+        server.commit(counter).unwrap();
+        assert_eq!(server.receiving_counter(), 1);
+        assert_eq!(server.receiving_key(counter), Err(Error::Replay));
+    }
+}
@@ -0,0 +1,90 @@
+//! Reading and writing the bit-aligned residues of compressed packets.
+
+use alloc::vec::Vec;
+
+use super::{error::Error, Result};
+
+/// Appends bits to a buffer, most significant bit first.
+#[derive(Default)]
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    /// The number of bits written.
+    length: usize,
+}
+
+impl BitWriter {
+    pub fn new() -> BitWriter {
+        BitWriter::default()
+    }
+
+    /// Writes the `count` least significant bits of `value`.
+    pub fn write(&mut self, value: u64, count: usize) {
+        for i in (0..count).rev() {
+            self.write_bit(value >> i & 1 == 1);
+        }
+    }
+
+    /// Writes all bits of `bytes`.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.write(u64::from(*byte), 8);
+        }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        if self.length.is_multiple_of(8) {
+            self.bytes.push(0);
+        }
+        if bit {
+            self.bytes[self.length / 8] |= 0x80 >> (self.length % 8);
+        }
+        self.length += 1;
+    }
+
+    /// Returns the bytes written, padded with zeros to whole bytes.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+/// Reads bits from a buffer, most significant bit first.
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    /// The number of bits read.
+    position: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader { bytes, position: 0 }
+    }
+
+    /// Returns the number of bits left.
+    pub fn remaining(&self) -> usize {
+        8 * self.bytes.len() - self.position
+    }
+
+    /// Reads `count` bits, up to 64, into the least significant bits of the
+    /// result.
+    pub fn read(&mut self, count: usize) -> Result<u64> {
+        if count > self.remaining() {
+            return Err(Error::InvalidPacket);
+        }
+        let mut value = 0;
+        for _ in 0..count {
+            let bit = self.bytes[self.position / 8] >> (7 - self.position % 8);
+            value = value << 1 | u64::from(bit & 1);
+            self.position += 1;
+        }
+
+        Ok(value)
+    }
+
+    /// Reads `count` whole bytes.
+    pub fn read_bytes(&mut self, count: usize) -> Result<Vec<u8>> {
+        if 8 * count > self.remaining() {
+            return Err(Error::InvalidPacket);
+        }
+        (0..count).map(|_| Ok(self.read(8)? as u8)).collect()
+    }
+}
@@ -0,0 +1,140 @@
+//! The compressor and decompressor, which pick a rule for each message.
+
+use alloc::vec::Vec;
+
+use super::{bits::BitReader, error::Error, rule::Rule, Result};
+use crate::{
+    oscore::coap::{self, Message},
+    ratchet::Direction,
+};
+
+/// The rule ID of messages no rule matches, which are sent as they are.
+pub const NO_COMPRESSION: u8 = 0xFF;
+
+/// The compressor and decompressor of one party, holding the rules both
+/// parties share.
+///
+/// The rules are tried in order, and the first one that matches a message
+/// compresses it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Compressor {
+    rules: Vec<Rule>,
+}
+
+impl Compressor {
+    /// Returns a compressor with the given rules, checking that every
+    /// matching operator and action fit together, and that rule IDs are
+    /// unique and not `NO_COMPRESSION`.
+    pub fn new(rules: Vec<Rule>) -> Result<Compressor> {
+        for (i, rule) in rules.iter().enumerate() {
+            rule.check()?;
+            if rule.id == NO_COMPRESSION
+                || rules[..i].iter().any(|other| other.id == rule.id)
+            {
+                return Err(Error::InvalidRule);
+            }
+        }
+
+        Ok(Compressor { rules })
+    }
+
+    /// Compresses a message sent in the given direction, or prefixes it
+    /// with `NO_COMPRESSION` if no rule matches.
+    ///
+    /// Messages with a token longer than CoAP allows are rejected, since
+    /// they couldn't be decompressed.
+    pub fn compress(
+        &self,
+        msg: &Message,
+        direction: Direction,
+    ) -> Result<Vec<u8>> {
+        if msg.token.len() > coap::MAX_TOKEN_LENGTH {
+            return Err(Error::InvalidMessage);
+        }
+        let packet = match self
+            .rules
+            .iter()
+            .find(|rule| rule.matches(msg, direction))
+        {
+            Some(rule) => rule.compress(msg, direction),
+            None => {
+                let mut packet = vec![NO_COMPRESSION];
+                packet.extend(
+                    coap::serialize_message(msg)
+                        .map_err(|_| Error::InvalidMessage)?,
+                );
+                packet
+            }
+        };
+
+        Ok(packet)
+    }
+
+    /// Decompresses a packet received in the given direction.
+    pub fn decompress(
+        &self,
+        packet: &[u8],
+        direction: Direction,
+    ) -> Result<Message> {
+        let (&rule_id, residue) =
+            packet.split_first().ok_or(Error::InvalidPacket)?;
+        if rule_id == NO_COMPRESSION {
+            return coap::deserialize_message(residue)
+                .map_err(|_| Error::InvalidPacket);
+        }
+        let rule = self
+            .rules
+            .iter()
+            .find(|rule| rule.id == rule_id)
+            .ok_or(Error::UnknownRule)?;
+
+        rule.decompress(&mut BitReader::new(residue), direction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::profile;
+    use super::*;
+
+    #[test]
+    fn rules() {
+        let mut rules = profile::rules(None).unwrap();
+        let compressor = Compressor::new(rules.clone()).unwrap();
+        assert_eq!(
+            compressor.decompress(&[0x42], Direction::Uplink),
+            Err(Error::UnknownRule)
+        );
+        assert_eq!(
+            compressor.decompress(&[], Direction::Uplink),
+            Err(Error::InvalidPacket)
+        );
+        // A packet cut off in the residues
+        assert_eq!(
+            compressor.decompress(
+                &[profile::EDHOC_MESSAGE_1, 0x80],
+                Direction::Uplink
+            ),
+            Err(Error::InvalidPacket)
+        );
+
+        // A token too long for TKL, with or without a matching rule
+        let mut msg =
+            profile::edhoc_request(&[0xAB; 9], 1, &profile::TRUE_PREFIX, &[]);
+        assert_eq!(
+            compressor.compress(&msg, Direction::Uplink),
+            Err(Error::InvalidMessage)
+        );
+        msg.code = coap::GET;
+        assert_eq!(
+            compressor.compress(&msg, Direction::Uplink),
+            Err(Error::InvalidMessage)
+        );
+
+        rules.push(rules[0].clone());
+        assert_eq!(Compressor::new(rules.clone()), Err(Error::InvalidRule));
+        rules.pop();
+        rules[0].id = NO_COMPRESSION;
+        assert_eq!(Compressor::new(rules), Err(Error::InvalidRule));
+    }
+}
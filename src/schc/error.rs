@@ -0,0 +1,53 @@
+//! The errors of the `schc` module.
+
+use core::fmt;
+
+use crate::{cbor, edhoc::error::Error as EdhocError};
+
+/// The catch-all error type for this module.
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    /// Wraps errors from the `cbor` module.
+    Cbor(cbor::CborError),
+    /// A rule whose matching operators and actions don't fit together, or
+    /// whose ID is taken.
+    InvalidRule,
+    /// A compressed packet with a rule ID no rule has.
+    UnknownRule,
+    /// A compressed packet that is too short for its rule, or decompresses
+    /// into an invalid message.
+    InvalidPacket,
+    /// A message that can't be sent, like one with a token longer than
+    /// CoAP allows.
+    InvalidMessage,
+    /// Wraps any other error from the `edhoc` module.
+    Edhoc(EdhocError),
+}
+
+impl From<cbor::CborError> for Error {
+    fn from(e: cbor::CborError) -> Error {
+        Error::Cbor(e)
+    }
+}
+
+impl From<EdhocError> for Error {
+    fn from(e: EdhocError) -> Error {
+        match e {
+            EdhocError::Cbor(e) => Error::Cbor(e),
+            e => Error::Edhoc(e),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Cbor(e) => e.fmt(f),
+            Error::InvalidRule => write!(f, "Invalid compression rule"),
+            Error::UnknownRule => write!(f, "Unknown rule ID"),
+            Error::InvalidPacket => write!(f, "Invalid compressed packet"),
+            Error::InvalidMessage => write!(f, "Message can't be sent"),
+            Error::Edhoc(e) => e.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! Static Context Header Compression (SCHC, RFC 8724) of CoAP messages,
+//! with a profile of rules for EDHOC and OSCORE.
+//!
+//! Both parties share a set of rules. Each rule describes every field of
+//! the messages it applies to, with a target value, a matching operator
+//! and the action taking it apart and putting it back together. A message
+//! is compressed with the first rule that matches it into the rule ID and
+//! the residues of its fields, followed by the payload, and is sent whole
+//! after `NO_COMPRESSION` if none does.
+//!
+//! Only the CoAP header, the options and a prefix of the payload are
+//! compressed, since LoRaWAN carries CoAP directly.
+
+mod bits;
+pub mod compressor;
+#[cfg_attr(tarpaulin, skip)]
+pub mod error;
+pub mod profile;
+pub mod rule;
+
+/// The result type for the `schc` module.
+type Result<T> = core::result::Result<T, error::Error>;
+
+pub use compressor::{Compressor, NO_COMPRESSION};
+pub use rule::{Action, FieldDescriptor, FieldId, MatchingOperator, Rule};
@@ -0,0 +1,419 @@
+//! The rules for EDHOC carried over CoAP (RFC 9528 Appendix A.2) and for
+//! OSCORE traffic, with the device as the CoAP client sending on the
+//! uplink.
+//!
+//! | Rule | Messages                                                  |
+//! |------|-----------------------------------------------------------|
+//! | 1    | `message_1`, POSTed to /.well-known/edhoc after `true`    |
+//! | 2    | `message_3`, POSTed to /.well-known/edhoc after C_R       |
+//! | 3    | `message_2` and `message_4`, in 2.04 responses            |
+//! | 4    | OSCORE requests                                           |
+//! | 5    | OSCORE responses                                          |
+//!
+//! All of them send the message ID and the token, and elide everything
+//! else the EDHOC and OSCORE exchanges fix.
+
+use alloc::vec::Vec;
+
+use super::{
+    rule::{
+        Action::{self, *},
+        FieldDescriptor, FieldId,
+        MatchingOperator::{self, *},
+        Rule,
+    },
+    Result,
+};
+use crate::{
+    edhoc::util::{self, Version},
+    oscore::coap::{
+        Message, ACK, CHANGED, CON, CONTENT, FETCH, NON, OSCORE, POST,
+        URI_PATH,
+    },
+    ratchet::Direction,
+};
+
+pub const EDHOC_MESSAGE_1: u8 = 1;
+pub const EDHOC_MESSAGE_3: u8 = 2;
+pub const EDHOC_RESPONSE: u8 = 3;
+pub const OSCORE_REQUEST: u8 = 4;
+pub const OSCORE_RESPONSE: u8 = 5;
+
+/// The CBOR `true` that precedes `message_1`.
+pub const TRUE_PREFIX: [u8; 1] = [0xF5];
+
+/// The Uri-Path options of the EDHOC resource.
+const WELL_KNOWN_EDHOC: [&[u8]; 2] = [b".well-known", b"edhoc"];
+
+/// Returns the rules of the profile.
+///
+/// The rule for `message_3` elides C_R, so it's only part of the rules
+/// once C_R is known, which for the device is after `message_2`.
+pub fn rules(c_r: Option<&[u8]>) -> Result<Vec<Rule>> {
+    let mut edhoc_request = header(Direction::Uplink, &[CON, NON], &[POST]);
+    for (i, segment) in WELL_KNOWN_EDHOC.iter().enumerate() {
+        edhoc_request.push(
+            FieldDescriptor::new(
+                FieldId::Option(URI_PATH),
+                segment,
+                Equal,
+                NotSent,
+            )
+            .at(i + 1)
+            .only(Direction::Uplink),
+        );
+    }
+    let prefixed = |prefix: &[u8]| {
+        let mut fields = edhoc_request.clone();
+        fields.push(
+            FieldDescriptor::new(
+                FieldId::PayloadPrefix(prefix.len()),
+                prefix,
+                Equal,
+                NotSent,
+            )
+            .only(Direction::Uplink),
+        );
+        fields
+    };
+
+    let mut rules = vec![Rule {
+        id: EDHOC_MESSAGE_1,
+        fields: prefixed(&TRUE_PREFIX),
+    }];
+    if let Some(c_r) = c_r {
+        rules.push(Rule {
+            id: EDHOC_MESSAGE_3,
+            fields: prefixed(&util::serialize_identifier(
+                Version::Rfc9528,
+                c_r,
+            )?),
+        });
+    }
+    rules.push(Rule {
+        id: EDHOC_RESPONSE,
+        fields: header(Direction::Downlink, &[ACK, CON, NON], &[CHANGED]),
+    });
+
+    let oscore_option = |direction| {
+        FieldDescriptor::new(FieldId::Option(OSCORE), &[], Ignore, ValueSent)
+            .only(direction)
+    };
+    let mut oscore_request =
+        header(Direction::Uplink, &[CON, NON], &[POST, FETCH]);
+    oscore_request.push(oscore_option(Direction::Uplink));
+    let mut oscore_response =
+        header(Direction::Downlink, &[ACK, CON, NON], &[CHANGED, CONTENT]);
+    oscore_response.push(oscore_option(Direction::Downlink));
+    rules.push(Rule {
+        id: OSCORE_REQUEST,
+        fields: oscore_request,
+    });
+    rules.push(Rule {
+        id: OSCORE_RESPONSE,
+        fields: oscore_response,
+    });
+
+    Ok(rules)
+}
+
+/// Returns the descriptors of the CoAP header in one direction, with the
+/// types and codes it may have.
+fn header(
+    direction: Direction,
+    types: &[u8],
+    codes: &[u8],
+) -> Vec<FieldDescriptor> {
+    let (code_mo, code_cda): (MatchingOperator, Action) = match codes {
+        [_] => (Equal, NotSent),
+        _ => (
+            MatchMapping(codes.iter().map(|code| vec![*code]).collect()),
+            MappingSent,
+        ),
+    };
+
+    vec![
+        FieldDescriptor::new(FieldId::Version, &[1], Equal, NotSent),
+        FieldDescriptor::new(
+            FieldId::Type,
+            &[],
+            MatchMapping(types.iter().map(|mtype| vec![*mtype]).collect()),
+            MappingSent,
+        ),
+        FieldDescriptor::new(FieldId::TokenLength, &[], Ignore, Compute),
+        FieldDescriptor::new(FieldId::Code, &codes[..1], code_mo, code_cda),
+        FieldDescriptor::new(FieldId::MessageId, &[], Ignore, ValueSent),
+        FieldDescriptor::new(FieldId::Token, &[], Ignore, ValueSent),
+    ]
+    .into_iter()
+    .map(|field| field.only(direction))
+    .collect()
+}
+
+/// Returns the confirmable request that carries an EDHOC message from the
+/// device, after `TRUE_PREFIX` for `message_1` and after C_R otherwise.
+pub fn edhoc_request(
+    token: &[u8],
+    message_id: u16,
+    prefix: &[u8],
+    message: &[u8],
+) -> Message {
+    Message {
+        mtype: CON,
+        code: POST,
+        message_id,
+        token: token.to_vec(),
+        options: WELL_KNOWN_EDHOC
+            .iter()
+            .map(|segment| (URI_PATH, segment.to_vec()))
+            .collect(),
+        payload: [prefix, message].concat(),
+    }
+}
+
+/// Returns the piggybacked response that carries an EDHOC message to the
+/// device.
+pub fn edhoc_response(request: &Message, message: &[u8]) -> Message {
+    Message {
+        mtype: ACK,
+        code: CHANGED,
+        message_id: request.message_id,
+        token: request.token.clone(),
+        options: Vec::new(),
+        payload: message.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use x25519_dalek_ng::{PublicKey, StaticSecret};
+
+    use super::super::{Compressor, NO_COMPRESSION};
+    use super::*;
+    use crate::{
+        edhoc::{
+            suite::SUITE_0,
+            util::{Message1, Message2, Message3, Message4},
+            PartyI, PartyR,
+        },
+        oscore::{
+            coap::{CONTENT, GET},
+            SecurityContext,
+        },
+    };
+
+    const C_I: [u8; 1] = [0x0C];
+    const C_R: [u8; 1] = [0x27];
+
+    /// Sends a message through the compressors, checking that it got
+    /// smaller.
+    fn send(
+        sender: &Compressor,
+        receiver: &Compressor,
+        msg: &Message,
+        direction: Direction,
+        rule: u8,
+    ) -> Message {
+        let packet = sender.compress(msg, direction).unwrap();
+        assert_eq!(packet[0], rule);
+        let uncompressed =
+            crate::oscore::coap::serialize_message(msg).unwrap();
+        assert!(packet.len() < uncompressed.len());
+
+        receiver.decompress(&packet, direction).unwrap()
+    }
+
+    #[test]
+    fn handshake_and_traffic() {
+        let i_static = StaticSecret::from([0x11; 32]);
+        let i_public = PublicKey::from(&i_static);
+        let r_static = StaticSecret::from([0x22; 32]);
+        let r_public = PublicKey::from(&r_static);
+        let mut device = Compressor::new(rules(None).unwrap()).unwrap();
+        let server = Compressor::new(rules(Some(&C_R)).unwrap()).unwrap();
+
+        let msg1_sender = PartyI::new(
+            C_I.to_vec(),
+            None,
+            StaticSecret::from([0x33; 32]),
+            i_static,
+            i_public,
+            vec![0x2B],
+            Version::Rfc9528,
+        );
+        let (msg_1, msg2_receiver) = msg1_sender
+            .generate_message_1(util::METHOD_STAT_STAT, &[SUITE_0])
+            .unwrap();
+        let request = edhoc_request(&[0x01], 0x100, &TRUE_PREFIX, &msg_1);
+        let received = send(
+            &device,
+            &server,
+            &request,
+            Direction::Uplink,
+            EDHOC_MESSAGE_1,
+        );
+        assert_eq!(received, request);
+        let msg1: Message1 = util::deserialize_message_1(
+            Version::Rfc9528,
+            &received.payload[1..],
+        )
+        .unwrap();
+        assert_eq!(msg1.c_i, C_I.to_vec());
+
+        let msg1_receiver = PartyR::new(
+            StaticSecret::from([0x44; 32]),
+            r_static,
+            r_public,
+            vec![0x32],
+            Version::Rfc9528,
+        );
+        let (msg2_sender, _) = msg1_receiver
+            .handle_message_1(received.payload[1..].to_vec())
+            .unwrap();
+        let (msg_2, msg3_receiver) =
+            msg2_sender.generate_message_2(C_R.to_vec(), None).unwrap();
+        let response = edhoc_response(&received, &msg_2);
+        let received = send(
+            &server,
+            &device,
+            &response,
+            Direction::Downlink,
+            EDHOC_RESPONSE,
+        );
+        let msg2: Message2 = util::deserialize_message_2(
+            Version::Rfc9528,
+            &received.payload,
+            32,
+        )
+        .unwrap();
+        assert_eq!(util::serialize_message_2(&msg2).unwrap(), msg_2);
+
+        let (_, c_r, msg2_verifier) = msg2_receiver
+            .unpack_message_2_return_kid(received.payload)
+            .unwrap();
+        assert_eq!(c_r, C_R.to_vec());
+        // Now the device knows C_R
+        device = Compressor::new(rules(Some(&c_r)).unwrap()).unwrap();
+        let (msg4_receiver, msg_3) = msg2_verifier
+            .verify_message_2(r_public.as_bytes())
+            .unwrap()
+            .generate_message_3(None)
+            .unwrap();
+        let prefix =
+            util::serialize_identifier(Version::Rfc9528, &c_r).unwrap();
+        let request = edhoc_request(&[0x02], 0x101, &prefix, &msg_3);
+        let received = send(
+            &device,
+            &server,
+            &request,
+            Direction::Uplink,
+            EDHOC_MESSAGE_3,
+        );
+        assert_eq!(received.payload[..prefix.len()], prefix[..]);
+        let msg3: Message3 =
+            util::deserialize_message_3(&received.payload[prefix.len()..])
+                .unwrap();
+        assert_eq!(util::serialize_message_3(&msg3).unwrap(), msg_3);
+
+        let (msg3_verifier, _) = msg3_receiver
+            .unpack_message_3_return_kid(
+                received.payload[prefix.len()..].to_vec(),
+            )
+            .unwrap();
+        let (msg4_sender, _, _, _) =
+            msg3_verifier.verify_message_3(i_public.as_bytes()).unwrap();
+        let (msg_4, r_session) =
+            msg4_sender.generate_message_4_session(None).unwrap();
+        let response = edhoc_response(&received, &msg_4);
+        let received = send(
+            &server,
+            &device,
+            &response,
+            Direction::Downlink,
+            EDHOC_RESPONSE,
+        );
+        let msg4: Message4 =
+            util::deserialize_message_4(&received.payload).unwrap();
+        assert_eq!(util::serialize_message_4(&msg4).unwrap(), msg_4);
+        let (i_session, _) = msg4_receiver
+            .handle_message_4_session(received.payload)
+            .unwrap();
+
+        // OSCORE traffic over the established context
+        let mut client =
+            SecurityContext::from_edhoc_initiator(&i_session, &C_I, &C_R)
+                .unwrap();
+        let mut resource =
+            SecurityContext::from_edhoc_responder(&r_session, &C_I, &C_R)
+                .unwrap();
+        let request = Message {
+            mtype: CON,
+            code: GET,
+            message_id: 0x102,
+            token: vec![0x03],
+            options: vec![(URI_PATH, b"temperature".to_vec())],
+            payload: Vec::new(),
+        };
+        let (protected, request_id) =
+            client.protect_request(&request).unwrap();
+        let received = send(
+            &device,
+            &server,
+            &protected,
+            Direction::Uplink,
+            OSCORE_REQUEST,
+        );
+        assert_eq!(received, protected);
+        let (unprotected, _) = resource.unprotect_request(&received).unwrap();
+        assert_eq!(unprotected, request);
+
+        let response = Message {
+            mtype: ACK,
+            code: CONTENT,
+            message_id: 0x102,
+            token: vec![0x03],
+            options: Vec::new(),
+            payload: b"21.5".to_vec(),
+        };
+        let protected = resource
+            .protect_response(&response, &request_id, false)
+            .unwrap();
+        let received = send(
+            &server,
+            &device,
+            &protected,
+            Direction::Downlink,
+            OSCORE_RESPONSE,
+        );
+        assert_eq!(
+            client.unprotect_response(&received, &request_id).unwrap(),
+            response
+        );
+    }
+
+    #[test]
+    fn uncompressed() {
+        let compressor = Compressor::new(rules(Some(&C_R)).unwrap()).unwrap();
+
+        // An EDHOC request after an unknown C_R, and a request to another
+        // resource
+        let request = edhoc_request(&[0x01], 0x100, &[0x08], b"message");
+        let mut other = request.clone();
+        other.options[1].1 = b"other".to_vec();
+        for msg in &[request, other] {
+            let packet = compressor.compress(msg, Direction::Uplink).unwrap();
+            assert_eq!(packet[0], NO_COMPRESSION);
+            assert_eq!(
+                compressor.decompress(&packet, Direction::Uplink),
+                Ok(msg.clone())
+            );
+        }
+
+        // A request is not a downlink
+        let request = edhoc_request(&[0x01], 0x100, &TRUE_PREFIX, b"message");
+        assert_eq!(
+            compressor.compress(&request, Direction::Downlink).unwrap()[0],
+            NO_COMPRESSION
+        );
+    }
+}
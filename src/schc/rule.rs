@@ -0,0 +1,574 @@
+//! Compression rules, and how a rule compresses and decompresses the
+//! fields of a CoAP message.
+
+use alloc::vec::Vec;
+
+use super::{
+    bits::{BitReader, BitWriter},
+    error::Error,
+    Result,
+};
+use crate::{oscore::coap::Message, ratchet::Direction};
+
+/// A field of a CoAP message (RFC 8824).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldId {
+    Version,
+    Type,
+    TokenLength,
+    Code,
+    MessageId,
+    Token,
+    /// The option with the given number.
+    Option(u16),
+    /// The given number of bytes at the start of the payload, like the
+    /// prefix EDHOC puts in front of its messages when carried over CoAP.
+    PayloadPrefix(usize),
+}
+
+impl FieldId {
+    /// Returns the length in bits of the field, or `None` if it varies.
+    pub fn length(self) -> Option<usize> {
+        match self {
+            FieldId::Version | FieldId::Type => Some(2),
+            FieldId::TokenLength => Some(4),
+            FieldId::Code => Some(8),
+            FieldId::MessageId => Some(16),
+            FieldId::Token | FieldId::Option(_) => None,
+            FieldId::PayloadPrefix(length) => Some(8 * length),
+        }
+    }
+}
+
+/// The matching operator of a field (RFC 8724 Section 7.3).
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchingOperator {
+    /// The field equals the target value.
+    Equal,
+    /// Any value matches.
+    Ignore,
+    /// The given number of most significant bits equal those of the target
+    /// value.
+    MsbMatch(usize),
+    /// The field equals one of the values.
+    MatchMapping(Vec<Vec<u8>>),
+}
+
+/// The compression and decompression action of a field (RFC 8724 Section
+/// 7.4).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Nothing is sent, the target value is restored.
+    NotSent,
+    /// The value is sent, preceded by its length in bytes if it varies.
+    ValueSent,
+    /// The bits after the ones `MsbMatch` matched are sent.
+    Lsb,
+    /// The index of the value in the `MatchMapping` is sent.
+    MappingSent,
+    /// Nothing is sent, the value is computed from the other fields, which
+    /// only works for the token length.
+    Compute,
+}
+
+/// How a rule treats a field.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldDescriptor {
+    pub id: FieldId,
+    /// Which occurrence of the field this is, starting from 1, for options
+    /// that are repeated.
+    pub position: usize,
+    /// The direction of the messages the field is in, or `None` for both.
+    pub direction: Option<Direction>,
+    /// The target value, with fields of fixed length as a big-endian
+    /// number.
+    pub target: Vec<u8>,
+    pub mo: MatchingOperator,
+    pub cda: Action,
+}
+
+impl FieldDescriptor {
+    /// Returns the descriptor of the first occurrence of a field in both
+    /// directions.
+    pub fn new(
+        id: FieldId,
+        target: &[u8],
+        mo: MatchingOperator,
+        cda: Action,
+    ) -> FieldDescriptor {
+        FieldDescriptor {
+            id,
+            position: 1,
+            direction: None,
+            target: target.to_vec(),
+            mo,
+            cda,
+        }
+    }
+
+    /// Returns the descriptor for another occurrence of the field.
+    pub fn at(self, position: usize) -> FieldDescriptor {
+        FieldDescriptor { position, ..self }
+    }
+
+    /// Returns the descriptor restricted to one direction.
+    pub fn only(self, direction: Direction) -> FieldDescriptor {
+        FieldDescriptor {
+            direction: Some(direction),
+            ..self
+        }
+    }
+
+    /// Checks that the matching operator and action fit together and the
+    /// field.
+    fn check(&self) -> Result<()> {
+        let valid = match (&self.mo, self.cda, self.id.length()) {
+            (MatchingOperator::MsbMatch(msb), _, Some(length))
+                if length <= 64 =>
+            {
+                *msb <= length && self.cda != Action::MappingSent
+            }
+            (_, Action::Lsb, _) | (MatchingOperator::MsbMatch(_), _, _) => {
+                false
+            }
+            (MatchingOperator::MatchMapping(values), cda, _) => {
+                cda == Action::MappingSent && !values.is_empty()
+            }
+            (_, Action::MappingSent, _) => false,
+            (_, Action::Compute, _) => self.id == FieldId::TokenLength,
+            _ => true,
+        };
+
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::InvalidRule)
+        }
+    }
+
+    /// Returns whether a value of the field matches.
+    fn matches(&self, value: &[u8]) -> bool {
+        let length = self.id.length();
+        match &self.mo {
+            MatchingOperator::Equal => equal(length, value, &self.target),
+            MatchingOperator::Ignore => true,
+            MatchingOperator::MsbMatch(msb) => {
+                let lsb = (length.unwrap_or(0) - msb) as u32;
+                to_u64(value).checked_shr(lsb)
+                    == to_u64(&self.target).checked_shr(lsb)
+            }
+            MatchingOperator::MatchMapping(values) => {
+                values.iter().any(|mapped| equal(length, value, mapped))
+            }
+        }
+    }
+
+    /// Writes the residue of a value of the field.
+    fn compress(&self, value: &[u8], residue: &mut BitWriter) {
+        let length = self.id.length();
+        match (self.cda, &self.mo, length) {
+            (Action::ValueSent, _, Some(length)) if length <= 64 => {
+                residue.write(to_u64(value), length)
+            }
+            (Action::ValueSent, _, Some(_)) => residue.write_bytes(value),
+            (Action::ValueSent, _, None) => {
+                write_length(value.len(), residue);
+                residue.write_bytes(value);
+            }
+            (Action::Lsb, MatchingOperator::MsbMatch(msb), Some(length)) => {
+                residue.write(to_u64(value), length - msb)
+            }
+            (
+                Action::MappingSent,
+                MatchingOperator::MatchMapping(values),
+                _,
+            ) => {
+                let index = values
+                    .iter()
+                    .position(|mapped| equal(length, value, mapped))
+                    .unwrap_or(0);
+                residue.write(index as u64, index_length(values.len()));
+            }
+            _ => (),
+        }
+    }
+
+    /// Reads the value of the field from the residue.
+    fn decompress(&self, residue: &mut BitReader) -> Result<Vec<u8>> {
+        let length = self.id.length();
+        match (self.cda, &self.mo, length) {
+            (Action::ValueSent, _, Some(length)) if length <= 64 => {
+                Ok(from_u64(residue.read(length)?, length))
+            }
+            (Action::ValueSent, _, Some(length)) => {
+                residue.read_bytes(length / 8)
+            }
+            (Action::ValueSent, _, None) => {
+                let length = read_length(residue)?;
+                residue.read_bytes(length)
+            }
+            (Action::Lsb, MatchingOperator::MsbMatch(msb), Some(length)) => {
+                let lsb = residue.read(length - msb)?;
+                let mask = u64::MAX
+                    .checked_shr((64 - length + msb) as u32)
+                    .unwrap_or(0);
+                Ok(from_u64(to_u64(&self.target) & !mask | lsb, length))
+            }
+            (
+                Action::MappingSent,
+                MatchingOperator::MatchMapping(values),
+                _,
+            ) => {
+                let index = residue.read(index_length(values.len()))?;
+                values
+                    .get(index as usize)
+                    .cloned()
+                    .ok_or(Error::InvalidPacket)
+            }
+            _ => Ok(self.target.clone()),
+        }
+    }
+}
+
+/// A compression rule, which describes every field of the messages it
+/// compresses.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    pub id: u8,
+    pub fields: Vec<FieldDescriptor>,
+}
+
+impl Rule {
+    /// Checks the descriptors of the rule.
+    pub(super) fn check(&self) -> Result<()> {
+        self.fields.iter().try_for_each(FieldDescriptor::check)
+    }
+
+    /// Returns the descriptors of the fields of messages in a direction.
+    fn fields(
+        &self,
+        direction: Direction,
+    ) -> impl Iterator<Item = &FieldDescriptor> {
+        self.fields.iter().filter(move |field| {
+            field.direction.unwrap_or(direction) == direction
+        })
+    }
+
+    /// Returns whether the rule describes every field of the message, and
+    /// every value matches.
+    pub(super) fn matches(&self, msg: &Message, direction: Direction) -> bool {
+        let fields = header_fields(msg);
+        let described = self.fields(direction).all(|descriptor| {
+            match field_value(&fields, &msg.payload, descriptor) {
+                Some(value) => descriptor.matches(value),
+                None => false,
+            }
+        });
+        let covered = fields.iter().all(|(id, position, _)| {
+            self.fields(direction)
+                .filter(|descriptor| {
+                    descriptor.id == *id && descriptor.position == *position
+                })
+                .count()
+                == 1
+        });
+
+        described && covered
+    }
+
+    /// Compresses a message the rule matches, returning the rule ID
+    /// followed by the residues of the fields and the rest of the payload.
+    pub(super) fn compress(
+        &self,
+        msg: &Message,
+        direction: Direction,
+    ) -> Vec<u8> {
+        let fields = header_fields(msg);
+        let mut residue = BitWriter::new();
+        residue.write(u64::from(self.id), 8);
+        let mut payload = &msg.payload[..];
+
+        for descriptor in self.fields(direction) {
+            let value = field_value(&fields, &msg.payload, descriptor)
+                .unwrap_or_default();
+            descriptor.compress(value, &mut residue);
+            if let FieldId::PayloadPrefix(length) = descriptor.id {
+                payload = &payload[length..];
+            }
+        }
+        residue.write_bytes(payload);
+
+        residue.into_bytes()
+    }
+
+    /// Restores a message from the residues and payload following the rule
+    /// ID.
+    pub(super) fn decompress(
+        &self,
+        residue: &mut BitReader,
+        direction: Direction,
+    ) -> Result<Message> {
+        let mut msg = Message {
+            mtype: 0,
+            code: 0,
+            message_id: 0,
+            token: Vec::new(),
+            options: Vec::new(),
+            payload: Vec::new(),
+        };
+        let mut token_length = None;
+
+        for descriptor in self.fields(direction) {
+            let value = descriptor.decompress(residue)?;
+            match descriptor.id {
+                FieldId::Version if to_u64(&value) != 1 => {
+                    return Err(Error::InvalidPacket)
+                }
+                FieldId::Version => (),
+                FieldId::Type => msg.mtype = to_u64(&value) as u8,
+                FieldId::TokenLength if descriptor.cda != Action::Compute => {
+                    token_length = Some(to_u64(&value) as usize)
+                }
+                FieldId::TokenLength => (),
+                FieldId::Code => msg.code = to_u64(&value) as u8,
+                FieldId::MessageId => msg.message_id = to_u64(&value) as u16,
+                FieldId::Token => msg.token = value,
+                FieldId::Option(number) => msg.options.push((number, value)),
+                FieldId::PayloadPrefix(_) => msg.payload.extend(value),
+            }
+        }
+        if token_length.is_some_and(|length| length != msg.token.len()) {
+            return Err(Error::InvalidPacket);
+        }
+        // Whatever doesn't make a whole byte is padding
+        let payload = residue.read_bytes(residue.remaining() / 8)?;
+        msg.payload.extend(payload);
+
+        Ok(msg)
+    }
+}
+
+/// Returns the header fields and options of a message, together with their
+/// positions.
+fn header_fields(msg: &Message) -> Vec<(FieldId, usize, Vec<u8>)> {
+    let mut fields = vec![
+        (FieldId::Version, 1, vec![1]),
+        (FieldId::Type, 1, vec![msg.mtype]),
+        (FieldId::TokenLength, 1, vec![msg.token.len() as u8]),
+        (FieldId::Code, 1, vec![msg.code]),
+        (FieldId::MessageId, 1, msg.message_id.to_be_bytes().to_vec()),
+        (FieldId::Token, 1, msg.token.clone()),
+    ];
+    for (i, (number, value)) in msg.options.iter().enumerate() {
+        let position = msg.options[..i]
+            .iter()
+            .filter(|(other, _)| other == number)
+            .count()
+            + 1;
+        fields.push((FieldId::Option(*number), position, value.clone()));
+    }
+
+    fields
+}
+
+/// Returns the value of the field a descriptor is for, if the message has
+/// it.
+fn field_value<'a>(
+    fields: &'a [(FieldId, usize, Vec<u8>)],
+    payload: &'a [u8],
+    descriptor: &FieldDescriptor,
+) -> Option<&'a [u8]> {
+    if let FieldId::PayloadPrefix(length) = descriptor.id {
+        return payload.get(..length);
+    }
+    fields
+        .iter()
+        .find(|(id, position, _)| {
+            *id == descriptor.id && *position == descriptor.position
+        })
+        .map(|(_, _, value)| &value[..])
+}
+
+/// Returns whether two values of a field are equal, where those of fixed
+/// length are compared as numbers.
+fn equal(length: Option<usize>, value: &[u8], other: &[u8]) -> bool {
+    match length {
+        Some(length) if length <= 64 => to_u64(value) == to_u64(other),
+        _ => value == other,
+    }
+}
+
+/// Returns the big-endian number in `bytes`, keeping the last 8 bytes.
+fn to_u64(bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(0, |value, byte| value << 8 | u64::from(*byte))
+}
+
+/// Returns a number as the bytes of a field of `length` bits.
+fn from_u64(value: u64, length: usize) -> Vec<u8> {
+    let bytes = length.div_ceil(8);
+    value.to_be_bytes()[8 - bytes..].to_vec()
+}
+
+/// Returns the number of bits the index of one of `count` values takes.
+fn index_length(count: usize) -> usize {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()) as usize
+}
+
+/// Writes the length of a variable-length value in bytes (RFC 8724
+/// Section 7.4.2).
+fn write_length(length: usize, residue: &mut BitWriter) {
+    match length {
+        0..=14 => residue.write(length as u64, 4),
+        15..=254 => {
+            residue.write(0x0F, 4);
+            residue.write(length as u64, 8);
+        }
+        _ => {
+            residue.write(0xFFF, 12);
+            residue.write(length as u64, 16);
+        }
+    }
+}
+
+/// Reads the length of a variable-length value.
+fn read_length(residue: &mut BitReader) -> Result<usize> {
+    match residue.read(4)? {
+        0x0F => match residue.read(8)? {
+            0xFF => Ok(residue.read(16)? as usize),
+            length => Ok(length as usize),
+        },
+        length => Ok(length as usize),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oscore::coap::{GET, URI_PATH};
+
+    fn request() -> Message {
+        Message {
+            mtype: 0,
+            code: GET,
+            message_id: 0x1234,
+            token: vec![0xAB],
+            options: vec![
+                (URI_PATH, b"a".to_vec()),
+                (URI_PATH, b"b".to_vec()),
+            ],
+            payload: b"payload".to_vec(),
+        }
+    }
+
+    fn rule() -> Rule {
+        use Action::*;
+        use MatchingOperator::*;
+
+        Rule {
+            id: 1,
+            fields: vec![
+                FieldDescriptor::new(FieldId::Version, &[1], Equal, NotSent),
+                FieldDescriptor::new(
+                    FieldId::Type,
+                    &[],
+                    MatchMapping(vec![vec![0], vec![1]]),
+                    MappingSent,
+                ),
+                FieldDescriptor::new(
+                    FieldId::TokenLength,
+                    &[],
+                    Ignore,
+                    Compute,
+                ),
+                FieldDescriptor::new(FieldId::Code, &[GET], Equal, NotSent),
+                FieldDescriptor::new(
+                    FieldId::MessageId,
+                    &[0x12, 0x00],
+                    MsbMatch(8),
+                    Lsb,
+                ),
+                FieldDescriptor::new(FieldId::Token, &[], Ignore, ValueSent),
+                FieldDescriptor::new(
+                    FieldId::Option(URI_PATH),
+                    b"a",
+                    Equal,
+                    NotSent,
+                ),
+                FieldDescriptor::new(
+                    FieldId::Option(URI_PATH),
+                    b"",
+                    Ignore,
+                    ValueSent,
+                )
+                .at(2),
+            ],
+        }
+    }
+
+    #[test]
+    fn fields() {
+        let rule = rule();
+        rule.check().unwrap();
+        let msg = request();
+        assert!(rule.matches(&msg, Direction::Uplink));
+
+        let compressed = rule.compress(&msg, Direction::Uplink);
+        // The rule ID, then 1 bit of type, 8 of the message ID, 4 + 8 of the
+        // token and 4 + 8 of the second Uri-Path, before the 7 bytes of
+        // payload and 7 bits of padding
+        assert_eq!(compressed.len(), 1 + 12);
+        assert_eq!(compressed[..5], [1, 0x1A, 0x0D, 0x58, 0xB1]);
+
+        let mut residue = BitReader::new(&compressed[1..]);
+        assert_eq!(rule.decompress(&mut residue, Direction::Uplink), Ok(msg));
+    }
+
+    #[test]
+    fn mismatches() {
+        let rule = rule();
+        let mut msg = request();
+        msg.message_id = 0x1334;
+        assert!(!rule.matches(&msg, Direction::Uplink));
+
+        // An option the rule doesn't describe
+        let mut msg = request();
+        msg.options.push((URI_PATH, b"c".to_vec()));
+        assert!(!rule.matches(&msg, Direction::Uplink));
+
+        // Fields only described for the other direction
+        let downlink = Rule {
+            fields: rule
+                .fields
+                .iter()
+                .cloned()
+                .map(|field| field.only(Direction::Downlink))
+                .collect(),
+            ..rule
+        };
+        assert!(!downlink.matches(&request(), Direction::Uplink));
+        assert!(downlink.matches(&request(), Direction::Downlink));
+    }
+
+    #[test]
+    fn lengths() {
+        for length in &[0, 14, 15, 254, 255, 300] {
+            let mut residue = BitWriter::new();
+            write_length(*length, &mut residue);
+            let bytes = residue.into_bytes();
+            assert_eq!(read_length(&mut BitReader::new(&bytes)), Ok(*length));
+        }
+        assert_eq!(index_length(1), 0);
+        assert_eq!(index_length(2), 1);
+        assert_eq!(index_length(5), 3);
+
+        let invalid = FieldDescriptor::new(
+            FieldId::Token,
+            &[],
+            MatchingOperator::MsbMatch(4),
+            Action::Lsb,
+        );
+        assert_eq!(invalid.check(), Err(Error::InvalidRule));
+    }
+}